@@ -7,8 +7,22 @@ pub const IDENTIFIER: u8 = 37;
 pub const INT: u8 = 38;
 pub const FLOAT: u8 = 39;
 pub const STRING: u8 = 40;
+pub const LABEL: u8 = 55;
+pub const DECIMAL: u8 = 71;
+pub const BIGINT: u8 = 73;
 
 
+// the patterns of the tokens that are not in the language table
+pub struct Patterns {
+    string: Regex,
+    integer: Regex,
+    float: Regex,
+    decimal: Regex,
+    bigint: Regex,
+    identifier: Regex,
+    label: Regex,
+}
+
 pub struct Token {
     pub token_string: String,
     pub token_num: u8,
@@ -17,27 +31,31 @@ pub struct Token {
 
 impl Token {
     pub fn new(token_string: &String, line_num: u64) -> Token {
-        let token = Token {
+        Token {
             token_string: token_string.to_string(),
             token_num: 0,
-            line_num: line_num,
-        };
-        token
+            line_num,
+        }
     }
 
-    pub fn find_token_num(&mut self, lex_language: &HashMap<String, u8>, string: &regex::Regex, integer: &regex::Regex,
-        float: &regex::Regex, identifier: &regex::Regex) -> bool {
+    pub fn find_token_num(&mut self, lex_language: &HashMap<String, u8>, patterns: &Patterns) -> bool {
         match lex_language.get(&self.token_string) {
             Some(num) => self.token_num = *num,
             None => {
-                if string.is_match(self.token_string.as_str()) {
+                if patterns.string.is_match(self.token_string.as_str()) {
                     self.token_num = STRING;
-                } else if integer.is_match(self.token_string.as_str()) {
+                } else if patterns.integer.is_match(self.token_string.as_str()) {
                     self.token_num = INT;
-                } else if float.is_match(self.token_string.as_str()) {
+                } else if patterns.float.is_match(self.token_string.as_str()) {
                     self.token_num = FLOAT;
-                } else if identifier.is_match(self.token_string.as_str()) {
+                } else if patterns.decimal.is_match(self.token_string.as_str()) {
+                    self.token_num = DECIMAL;
+                } else if patterns.bigint.is_match(self.token_string.as_str()) {
+                    self.token_num = BIGINT;
+                } else if patterns.identifier.is_match(self.token_string.as_str()) {
                     self.token_num = IDENTIFIER;
+                } else if patterns.label.is_match(self.token_string.as_str()) {
+                    self.token_num = LABEL;
                 } else {
                     println!("Unkown token: {} at line {}.", self.token_string, self.line_num);
                     return true;
@@ -48,7 +66,7 @@ impl Token {
     }
 }
 
-pub fn lexer(source: &String) -> (Vec<Token>, bool) {
+pub fn lexer(source: &str) -> (Vec<Token>, bool) {
     let mut lex_language: HashMap<String, u8> = HashMap::new();
 
     // single char tokens
@@ -95,6 +113,44 @@ pub fn lexer(source: &String) -> (Vec<Token>, bool) {
     lex_language.insert("vec_int".to_string(), 34);
     lex_language.insert("vec_float".to_string(), 35);
     lex_language.insert("vec_string".to_string(), 36);
+    lex_language.insert("bool".to_string(), 41);
+    lex_language.insert("true".to_string(), 42);
+    lex_language.insert("false".to_string(), 43);
+    lex_language.insert("not".to_string(), 44);
+    lex_language.insert("&".to_string(), 45);
+    lex_language.insert("|".to_string(), 46);
+    lex_language.insert("^".to_string(), 47);
+    lex_language.insert("<<".to_string(), 48);
+    lex_language.insert(">>".to_string(), 49);
+    lex_language.insert("**".to_string(), 50);
+    lex_language.insert("for".to_string(), 51);
+    lex_language.insert("in".to_string(), 52);
+    lex_language.insert("continue".to_string(), 53);
+    lex_language.insert("..".to_string(), 54);
+    lex_language.insert("struct".to_string(), 56);
+    lex_language.insert(".".to_string(), 57);
+    lex_language.insert("enum".to_string(), 58);
+    lex_language.insert("match".to_string(), 59);
+    lex_language.insert("::".to_string(), 60);
+    lex_language.insert("=>".to_string(), 61);
+    lex_language.insert("map".to_string(), 62);
+    lex_language.insert("const".to_string(), 63);
+    lex_language.insert("static".to_string(), 64);
+    lex_language.insert("import".to_string(), 65);
+    lex_language.insert("mod".to_string(), 66);
+    lex_language.insert("pub".to_string(), 67);
+    lex_language.insert("->".to_string(), 68);
+    lex_language.insert("as".to_string(), 69);
+    lex_language.insert("checked".to_string(), 70);
+    lex_language.insert("decimal".to_string(), 72);
+    lex_language.insert("bigint".to_string(), 74);
+    lex_language.insert("?".to_string(), 75);
+    lex_language.insert("try".to_string(), 76);
+    lex_language.insert("catch".to_string(), 77);
+    lex_language.insert("throw".to_string(), 78);
+    lex_language.insert("ref".to_string(), 79);
+    lex_language.insert("mut".to_string(), 80);
+    lex_language.insert("file".to_string(), 81);
 
     let mut single_char_tokens: HashSet<char> = HashSet::new();
     single_char_tokens.insert(';');
@@ -111,11 +167,22 @@ pub fn lexer(source: &String) -> (Vec<Token>, bool) {
     single_char_tokens.insert('/');
     single_char_tokens.insert('-');
     single_char_tokens.insert('%');
+    single_char_tokens.insert('&');
+    single_char_tokens.insert('|');
+    single_char_tokens.insert('^');
+    single_char_tokens.insert('<');
+    single_char_tokens.insert('>');
+    single_char_tokens.insert('?');
 
-    let string = Regex::new(r"^\x22[^\x22]*\x22$").unwrap();
-    let integer = Regex::new(r"^-?[0-9]+$").unwrap();
-    let float = Regex::new(r"^-?[0-9]+\.[0-9]+$").unwrap();
-    let identifier = Regex::new(r"^[_a-zA-Z][_a-zA-z0-9]*$").unwrap();
+    let patterns = Patterns {
+        string: Regex::new(r"^\x22[^\x22]*\x22$").unwrap(),
+        integer: Regex::new(r"^-?[0-9]+$").unwrap(),
+        float: Regex::new(r"^-?[0-9]+\.[0-9]+$").unwrap(),
+        decimal: Regex::new(r"^-?[0-9]+(\.[0-9]+)?d$").unwrap(),
+        bigint: Regex::new(r"^-?[0-9]+n$").unwrap(),
+        identifier: Regex::new(r"^[_a-zA-Z][_a-zA-z0-9]*$").unwrap(),
+        label: Regex::new(r"^'[_a-zA-Z][_a-zA-z0-9]*$").unwrap(),
+    };
 
     let mut tokens: Vec<Token> = Vec::new();
     let mut error: bool = false;
@@ -123,8 +190,15 @@ pub fn lexer(source: &String) -> (Vec<Token>, bool) {
     let mut is_current_char_a_string: bool = false;
     let mut current_line_number: u64 = 1;
 
+    let chars: Vec<char> = source.chars().collect();
+    let mut skip_next_char: bool = false;
     let mut i: u64 = 0;
     for c in source.chars() {
+        if skip_next_char {
+            skip_next_char = false;
+            i += 1;
+            continue;
+        }
         if c as u8 > 127 {
             println!("Non-ascii character: {} at line {}.", c, current_line_number);
         }
@@ -133,23 +207,72 @@ pub fn lexer(source: &String) -> (Vec<Token>, bool) {
                 current_line_number += 1;
             }
             if single_char_tokens.contains(&c) {
-                if c != '-' || (c == '-' && source.chars().nth(i as usize + 1) == Some(' ')) {
+                let next_char = chars.get(i as usize + 1).copied();
+                // a '-' that starts a token and is followed by a digit is part of a negative number
+                let is_negative_number = c == '-' && current_token_string.is_empty() && next_char.is_some_and(|x| x.is_ascii_digit());
+                let is_arrow = c == '>' && current_token_string == "=";
+                if !is_negative_number && !is_arrow {
                     if !current_token_string.is_empty() {
                         let mut token = Token::new(&current_token_string, current_line_number);
-                        if token.find_token_num(&lex_language, &string, &integer, &float, &identifier) {
+                        if token.find_token_num(&lex_language, &patterns) {
                             error = true;
                         }
                         tokens.push(token);
                         current_token_string = String::from("");
                     }
-                    let mut token = Token::new(&String::from(c), current_line_number);
-                    if token.find_token_num(&lex_language, &string, &integer, &float, &identifier) {
+                    let mut token_string = String::from(c);
+                    if c == '*' && next_char == Some('*') {
+                        token_string.push('*');
+                        skip_next_char = true;
+                    } else if c == '-' && next_char == Some('>') {
+                        token_string.push('>');
+                        skip_next_char = true;
+                    } else if c == ':' && next_char == Some(':') {
+                        token_string.push(':');
+                        skip_next_char = true;
+                    } else if (c == '<' || c == '>') && (next_char == Some('=') || next_char == Some(c)) {
+                        // '<=', '>=', '<<' and '>>'
+                        token_string.push(next_char.unwrap());
+                        skip_next_char = true;
+                    }
+                    let mut token = Token::new(&token_string, current_line_number);
+                    if token.find_token_num(&lex_language, &patterns) {
                         error = true;
                     }
                     tokens.push(token);
                 } else {
                     current_token_string.push(c);
                 }
+            } else if c == '.' && chars.get(i as usize + 1) == Some(&'.') {
+                if !current_token_string.is_empty() {
+                    let mut token = Token::new(&current_token_string, current_line_number);
+                    if token.find_token_num(&lex_language, &patterns) {
+                        error = true;
+                    }
+                    tokens.push(token);
+                    current_token_string = String::from("");
+                }
+                let mut token = Token::new(&String::from(".."), current_line_number);
+                if token.find_token_num(&lex_language, &patterns) {
+                    error = true;
+                }
+                tokens.push(token);
+                skip_next_char = true;
+            } else if c == '.' && (current_token_string.starts_with(|x: char| x.is_ascii_alphabetic() || x == '_')
+                || (tokens.last().is_some_and(|x| x.token_string == ".") && !current_token_string.is_empty()
+                    && current_token_string.chars().all(|x| x.is_ascii_digit()))) {
+                // field access, a '.' after a number is part of a float unless the number is a tuple element
+                let mut token = Token::new(&current_token_string, current_line_number);
+                if token.find_token_num(&lex_language, &patterns) {
+                    error = true;
+                }
+                tokens.push(token);
+                current_token_string = String::from("");
+                let mut token = Token::new(&String::from("."), current_line_number);
+                if token.find_token_num(&lex_language, &patterns) {
+                    error = true;
+                }
+                tokens.push(token);
             } else if c == '"' {
                 is_current_char_a_string = true;
                 current_token_string.push(c);
@@ -157,7 +280,7 @@ pub fn lexer(source: &String) -> (Vec<Token>, bool) {
                 current_token_string.push(c);
             } else if !current_token_string.is_empty() {
                 let mut token = Token::new(&current_token_string, current_line_number);
-                if token.find_token_num(&lex_language, &string, &integer, &float, &identifier) {
+                if token.find_token_num(&lex_language, &patterns) {
                     error = true;
                 }
                 tokens.push(token);
@@ -1,20 +1,63 @@
 use std::env;
-use std::fs;
+use std::path::Path;
 use std::time::Instant;
 
+use vsl_policy::Policy;
+
+mod modules;
 mod parser;
 
 fn main() {
     let start = Instant::now();
     let args: Vec<String> = env::args().collect();
-    if args.len() == 2 {
-        let filename = &args[1];
-        let source: String = fs::read_to_string(filename).expect("Failed to open file.");
-
-        let (tokens, mut error): (Vec<parser::lexer::Token>, bool) = parser::lexer::lexer(&source);
+    // vsl_compiler [-c] [--checked] [--policy file] file.vsl
+    let flags = if args.len() >= 2 { &args[1..args.len() - 1] } else { &args[0..0] };
+    let object_mode = flags.contains(&"-c".to_string());
+    let checked_mode = flags.contains(&"--checked".to_string());
+    let mut policy_file: Option<String> = None;
+    let mut is_valid = true;
+    let mut i = 0;
+    while i < flags.len() {
+        if flags[i] == "--policy" && i + 1 < flags.len() {
+            policy_file = Some(flags[i + 1].clone());
+            i += 2;
+            continue;
+        }
+        if flags[i] == "--policy" {
+            println!("Option '--policy' must be followed by a policy file.");
+            is_valid = false;
+        } else if flags[i] != "-c" && flags[i] != "--checked" {
+            println!("Unknown option '{}'.", flags[i]);
+            is_valid = false;
+        }
+        i += 1;
+    }
+    if !is_valid {
+        println!("Usage: vsl_compiler [-c] [--checked] [--policy file] file.vsl");
+        std::process::exit(1);
+    }
+    // vsl.policy in the working directory is used when no other policy is given
+    if policy_file.is_none() && Path::new("vsl.policy").exists() {
+        policy_file = Some("vsl.policy".to_string());
+    }
+    if args.len() >= 2 {
+        let filename = &args[args.len() - 1];
+        let policy = match &policy_file {
+            Some(x) => Policy::load(x),
+            None => Some(Policy::new()),
+        };
+        let policy = match policy {
+            Some(x) => x,
+            None => {
+                let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
+                println!("Program completed in {} seconds.", time);
+                return;
+            },
+        };
+        let (tokens, root_start, exports, mut error) = modules::load(filename, object_mode);
 
         if error {
-            let time: f64 = start.elapsed().as_micros() as f64 / 1000000 as f64;
+            let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
             println!("Program completed in {} seconds.", time);
             return;
         }
@@ -25,17 +68,29 @@ fn main() {
         */
 
         let mut parser = parser::Parser::new(&tokens);
+        if object_mode {
+            parser.set_object_mode(root_start, exports);
+        }
+        if checked_mode {
+            parser.set_checked_mode();
+        }
+        parser.set_policy(policy);
         error = parser.parse(&tokens);
 
         if error {
-            let time: f64 = start.elapsed().as_micros() as f64 / 1000000 as f64;
+            let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
             println!("Failed to compile program due to errors.");
             println!("Program completed in {} seconds.", time);
             return;
         }
 
-        parser.output_code();
+        if object_mode {
+            let stem = Path::new(filename).file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+            parser.output_object(&format!("{}.vso", stem));
+        } else {
+            parser.output_code();
+        }
     }
-    let time: f64 = start.elapsed().as_micros() as f64 / 1000000 as f64;
+    let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
     println!("Program completed in {} seconds.", time);
 }
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::parser::lexer;
+
+const SEMI_COLON: u8 = 0;
+const LEFT_PARENTHESIS: u8 = 1;
+const LEFT_CURLEY: u8 = 3;
+const RIGHT_CURLEY: u8 = 4;
+const COLON: u8 = 6;
+const LESS: u8 = 15;
+const FN: u8 = 24;
+const IDENTIFIER: u8 = 37;
+const STRING: u8 = 40;
+const STRUCT: u8 = 56;
+const DOT: u8 = 57;
+const ENUM: u8 = 58;
+const DOUBLE_COLON: u8 = 60;
+const CONST: u8 = 63;
+const STATIC: u8 = 64;
+const IMPORT: u8 = 65;
+const MOD: u8 = 66;
+const PUB: u8 = 67;
+const CHECKED: u8 = 70;
+
+struct Module {
+    pub name: String,
+    pub file_name: String,
+    pub tokens: Vec<lexer::Token>,
+    pub imports: Vec<String>,
+    // top level names and whether they are pub
+    pub functions: HashMap<String, bool>,
+    pub globals: HashMap<String, bool>,
+    pub types: HashMap<String, bool>,
+    // generic functions are declared with their name followed by '<'
+    pub generics: Vec<String>,
+}
+
+struct Loader {
+    error: bool,
+    modules: Vec<Module>,
+    module_paths: HashMap<String, PathBuf>,
+    loading: Vec<PathBuf>,
+}
+
+// Loads a file and every module it imports, giving the tokens of one program with dependencies first.
+// Top level names of imported modules are renamed to module::name. For an object file the file itself is
+// a module too (apart from main), the tokens of the file start at the returned index and its pub functions
+// and statics are returned as exports.
+pub fn load(filename: &String, object_mode: bool) -> (Vec<lexer::Token>, usize, Vec<String>, bool) {
+    let mut loader = Loader {
+        error: false,
+        modules: Vec::new(),
+        module_paths: HashMap::new(),
+        loading: Vec::new(),
+    };
+    let path = Path::new(filename);
+    let mut name = String::new();
+    if object_mode {
+        name = path.file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+    }
+    loader.load_module(path, name);
+    if loader.error {
+        return (Vec::new(), 0, Vec::new(), true);
+    }
+    loader.link_names();
+
+    let mut exports: Vec<String> = Vec::new();
+    let root = &loader.modules[loader.modules.len() - 1];
+    for (identifier, is_pub) in root.functions.iter().chain(root.globals.iter()) {
+        if identifier == "main" {
+            exports.push(identifier.clone());
+        } else if *is_pub {
+            exports.push(format!("{}::{}", root.name, identifier));
+        }
+    }
+    let mut tokens: Vec<lexer::Token> = Vec::new();
+    let mut root_start = 0;
+    for module in loader.modules.iter_mut() {
+        root_start = tokens.len();
+        tokens.append(&mut module.tokens);
+    }
+    (tokens, root_start, exports, loader.error)
+}
+
+impl Loader {
+    fn load_module(&mut self, path: &Path, name: String) -> Option<String> {
+        let canonical_path = match fs::canonicalize(path) {
+            Ok(x) => x,
+            Err(_error) => {
+                println!("Failed to open file '{}'.", path.display());
+                self.error = true;
+                return None;
+            },
+        };
+        if self.loading.contains(&canonical_path) {
+            let mut cycle: Vec<String> = Vec::new();
+            for loading_path in self.loading.iter() {
+                cycle.push(loading_path.display().to_string());
+            }
+            cycle.push(canonical_path.display().to_string());
+            println!("Import cycle: {}.", cycle.join(" -> "));
+            self.error = true;
+            return None;
+        }
+        if let Some(x) = self.module_paths.get(&name) {
+            if *x == canonical_path {
+                return Some(name);
+            }
+            println!("Module name '{}' is used by both '{}' and '{}'.", name, x.display(), canonical_path.display());
+            self.error = true;
+            return None;
+        }
+        self.module_paths.insert(name.clone(), canonical_path.clone());
+
+        let file_name = path.display().to_string();
+        let source: String = match fs::read_to_string(&canonical_path) {
+            Ok(x) => x,
+            Err(_error) => {
+                println!("Failed to open file '{}'.", file_name);
+                self.error = true;
+                return None;
+            },
+        };
+        let (tokens, error) = lexer::lexer(&source);
+        if error {
+            println!("Failed to lex file '{}'.", file_name);
+            self.error = true;
+            return None;
+        }
+
+        self.loading.push(canonical_path.clone());
+        let directory = canonical_path.parent().map(|x| x.to_path_buf()).unwrap_or(PathBuf::new());
+        let mut module = Module {
+            name,
+            file_name,
+            tokens: Vec::new(),
+            imports: Vec::new(),
+            functions: HashMap::new(),
+            globals: HashMap::new(),
+            types: HashMap::new(),
+            generics: Vec::new(),
+        };
+
+        // import "path"; and mod name; are only allowed outside of functions and are removed here
+        let mut depth = 0;
+        let mut is_pub = false;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token_num = tokens[i].token_num;
+            if depth == 0 && (token_num == IMPORT || token_num == MOD) {
+                let is_import = token_num == IMPORT;
+                let line_num = tokens[i].line_num;
+                let expected = if is_import { STRING } else { IDENTIFIER };
+                if i + 2 >= tokens.len() || tokens[i + 1].token_num != expected || tokens[i + 2].token_num != SEMI_COLON {
+                    if is_import {
+                        println!("Expected 'import \"path\";' on line {} of '{}'.", line_num, module.file_name);
+                    } else {
+                        println!("Expected 'mod name;' on line {} of '{}'.", line_num, module.file_name);
+                    }
+                    self.error = true;
+                    i += 1;
+                    continue;
+                }
+                let import_path = if is_import {
+                    directory.join(tokens[i + 1].token_string.trim_matches('"'))
+                } else {
+                    directory.join(format!("{}.vsl", tokens[i + 1].token_string))
+                };
+                let import_name = import_path.file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+                if let Some(x) = self.load_module(&import_path, import_name) {
+                    module.imports.push(x);
+                }
+                i += 3;
+                continue;
+            }
+            if depth == 0 && token_num == PUB {
+                is_pub = true;
+                i += 1;
+                continue;
+            }
+            // fn also starts function types, which follow a name
+            let is_fn_declaration = token_num == FN
+                && module.tokens.last().is_none_or(|x| x.token_num == SEMI_COLON || x.token_num == RIGHT_CURLEY || x.token_num == CHECKED);
+            if depth == 0 && (is_fn_declaration || token_num == STRUCT || token_num == ENUM || token_num == CONST || token_num == STATIC) {
+                // the declared name is the first identifier, except for functions where it comes before '(' or '<'
+                // and is not the return type (fn Option<int>:0 name)
+                let mut j = i + 1;
+                while j + 1 < tokens.len() && (tokens[j].token_num != IDENTIFIER
+                    || (token_num == FN && tokens[j + 1].token_num != LEFT_PARENTHESIS && tokens[j + 1].token_num != LESS)
+                    || (token_num == FN && tokens[j - 1].token_num == FN)) {
+                    j += 1;
+                }
+                if j < tokens.len() {
+                    let identifier = tokens[j].token_string.clone();
+                    if token_num == FN && j + 1 < tokens.len() && tokens[j + 1].token_num == LESS {
+                        module.generics.push(identifier.clone());
+                    }
+                    if token_num == FN {
+                        module.functions.insert(identifier, is_pub);
+                    } else if token_num == STRUCT || token_num == ENUM {
+                        module.types.insert(identifier, is_pub);
+                    } else {
+                        module.globals.insert(identifier, is_pub);
+                    }
+                }
+            } else if is_pub && token_num == CHECKED {
+                // pub checked fn
+                module.tokens.push(lexer::Token {
+                    token_string: tokens[i].token_string.clone(),
+                    token_num,
+                    line_num: tokens[i].line_num,
+                });
+                i += 1;
+                continue;
+            } else if is_pub {
+                println!("Expected declaration after 'pub' on line {} of '{}'.", tokens[i].line_num, module.file_name);
+                self.error = true;
+            }
+            is_pub = false;
+            if token_num == LEFT_CURLEY {
+                depth += 1;
+            } else if token_num == RIGHT_CURLEY {
+                depth -= 1;
+            }
+            module.tokens.push(lexer::Token {
+                token_string: tokens[i].token_string.clone(),
+                token_num,
+                line_num: tokens[i].line_num,
+            });
+            i += 1;
+        }
+        self.loading.pop();
+        let module_name = module.name.clone();
+        self.modules.push(module);
+        Some(module_name)
+    }
+
+    fn link_names(&mut self) {
+        let root_num = self.modules.len() - 1;
+        for module_num in 0..self.modules.len() {
+            let tokens = std::mem::take(&mut self.modules[module_num].tokens);
+            let tokens = self.resolve_paths(module_num, tokens);
+            let tokens = self.prefix_names(module_num, tokens, module_num == root_num);
+            self.modules[module_num].tokens = tokens;
+        }
+    }
+
+    // module::name becomes one identifier when the module is imported and the name is pub.
+    fn resolve_paths(&mut self, module_num: usize, tokens: Vec<lexer::Token>) -> Vec<lexer::Token> {
+        let mut resolved: Vec<lexer::Token> = Vec::new();
+        let mut i = 0;
+        while i < tokens.len() {
+            let is_path = tokens[i].token_num == IDENTIFIER && i + 2 < tokens.len()
+                && tokens[i + 1].token_num == DOUBLE_COLON && tokens[i + 2].token_num == IDENTIFIER
+                && self.modules[module_num].imports.contains(&tokens[i].token_string)
+                && (i == 0 || tokens[i - 1].token_num != DOUBLE_COLON);
+            if !is_path {
+                resolved.push(lexer::Token {
+                    token_string: tokens[i].token_string.clone(),
+                    token_num: tokens[i].token_num,
+                    line_num: tokens[i].line_num,
+                });
+                i += 1;
+                continue;
+            }
+            let module_name = tokens[i].token_string.clone();
+            let identifier = tokens[i + 2].token_string.clone();
+            if let Some(x) = self.modules.iter().find(|x| x.name == module_name) {
+                let is_pub = x.functions.get(&identifier).or(x.globals.get(&identifier)).or(x.types.get(&identifier));
+                match is_pub {
+                    Some(true) => {},
+                    Some(false) => {
+                        println!("'{}' is not pub in module '{}' on line {} of '{}'.", identifier, module_name, tokens[i].line_num, self.modules[module_num].file_name);
+                        self.error = true;
+                    },
+                    None => {
+                        println!("Module '{}' has no '{}' on line {} of '{}'.", module_name, identifier, tokens[i].line_num, self.modules[module_num].file_name);
+                        self.error = true;
+                    },
+                }
+            }
+            resolved.push(lexer::Token {
+                token_string: format!("{}::{}", module_name, identifier),
+                token_num: IDENTIFIER,
+                line_num: tokens[i].line_num,
+            });
+            i += 3;
+        }
+        resolved
+    }
+
+    // Renames the top level names of an imported module and every use of them within the module.
+    fn prefix_names(&mut self, module_num: usize, mut tokens: Vec<lexer::Token>, is_root: bool) -> Vec<lexer::Token> {
+        let module = &self.modules[module_num];
+        if module.name.is_empty() {
+            return tokens;
+        }
+        let mut is_type_body = false;
+        for i in 0..tokens.len() {
+            if tokens[i].token_num == STRUCT || tokens[i].token_num == ENUM {
+                is_type_body = true;
+            } else if tokens[i].token_num == RIGHT_CURLEY {
+                is_type_body = false;
+            }
+            if tokens[i].token_num != IDENTIFIER {
+                continue;
+            }
+            // fields and enum variants are never renamed
+            if i > 0 && (tokens[i - 1].token_num == DOT || tokens[i - 1].token_num == DOUBLE_COLON) {
+                continue;
+            }
+            // only the types of fields are renamed in struct and enum declarations
+            if is_type_body && !module.types.contains_key(&tokens[i].token_string) {
+                continue;
+            }
+            let next_token_num = if i + 1 < tokens.len() { tokens[i + 1].token_num } else { SEMI_COLON };
+            let identifier = &tokens[i].token_string;
+            if is_root && identifier == "main" {
+                continue;
+            }
+            // functions are also renamed where they are used as values, only not as fields of struct literals
+            let is_name = module.types.contains_key(identifier)
+                || (module.functions.contains_key(identifier) && next_token_num != COLON)
+                || (module.generics.contains(identifier) && next_token_num == LESS)
+                || (module.globals.contains_key(identifier) && next_token_num != COLON && next_token_num != LEFT_PARENTHESIS);
+            if is_name {
+                tokens[i].token_string = format!("{}::{}", module.name, identifier);
+            }
+        }
+        tokens
+    }
+}
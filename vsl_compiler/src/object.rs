@@ -0,0 +1,185 @@
+use std::fs;
+use std::io::Write;
+
+const POP: i64 = 1;
+const LOCAL_LOAD: i64 = 2;
+const LOCAL_STORE: i64 = 3;
+const I_CONSTANT: i64 = 4;
+const F_CONSTANT: i64 = 15;
+const S_CONSTANT: i64 = 26;
+const JUMP_IF_FALSE: i64 = 32;
+const JUMP: i64 = 33;
+const CALL: i64 = 34;
+const ARG_LOAD: i64 = 37;
+const ARG_STORE: i64 = 38;
+const USE: i64 = 39;
+const JUMP_IF_TRUE: i64 = 42;
+const LOCAL_ALLOC: i64 = 54;
+const STRUCT_NEW: i64 = 55;
+const FIELD_LOAD: i64 = 56;
+const FIELD_STORE: i64 = 57;
+const JUMP_TABLE: i64 = 58;
+const GLOBAL_LOAD: i64 = 59;
+const GLOBAL_STORE: i64 = 60;
+const CLOSURE_NEW: i64 = 61;
+const CALL_INDIRECT: i64 = 62;
+const TRY_BEGIN: i64 = 82;
+const THROW_ERROR: i64 = 84;
+const LOCAL_ADDRESS: i64 = 85;
+const ARG_ADDRESS: i64 = 86;
+const REF_LOAD: i64 = 87;
+const REF_STORE: i64 = 88;
+
+// standard library functions with a type after the function number
+const PRINT: i64 = 0;
+const READ: i64 = 1;
+const MAP_NEW: i64 = 29;
+const READ_OPTION: i64 = 59;
+const ENV_GET: i64 = 60;
+const FILE_OPEN: i64 = 61;
+const FILE_WRITE: i64 = 63;
+const DIR_LIST: i64 = 67;
+
+// "VSLOBJ" followed by the format version
+pub const MAGIC: i64 = 0x5653_4c4f_424a_0001;
+
+pub const FUNCTION: i64 = 0;
+pub const STATIC: i64 = 1;
+
+// Types are written by name so that user defined types match across objects.
+pub struct Signature {
+    pub var_type: String,
+    pub security: i64,
+    pub arg_types: Vec<String>,
+    pub arg_securities: Vec<i64>,
+}
+
+// Exports give the code location of a function or the global slot of a static. Imported functions list the
+// CALL and CLOSURE_NEW operands that need their address, imported statics give the global slot the object uses for them.
+pub struct Symbol {
+    pub kind: i64,
+    pub name: String,
+    pub value: i64,
+    pub signature: Signature,
+    pub refs: Vec<usize>,
+}
+
+// The first init_len words of code initialize statics, the rest are functions.
+pub struct Object {
+    pub init_len: usize,
+    pub init_locals: i64,
+    pub num_globals: i64,
+    pub code: Vec<i64>,
+    pub exports: Vec<Symbol>,
+    pub imports: Vec<Symbol>,
+}
+
+// Walks the code and gives the locations of operands holding code addresses and of operands holding global slots.
+pub fn operand_locations(code: &[i64]) -> (Vec<usize>, Vec<usize>) {
+    let mut addresses: Vec<usize> = Vec::new();
+    let mut globals: Vec<usize> = Vec::new();
+    let mut ip = 0;
+    while ip < code.len() {
+        let opcode = code[ip];
+        ip += 1;
+        match opcode {
+            JUMP_IF_FALSE | JUMP_IF_TRUE | JUMP => {
+                addresses.push(ip);
+                ip += 1;
+            },
+            CALL | CLOSURE_NEW | TRY_BEGIN => {
+                addresses.push(ip);
+                ip += 2;
+            },
+            GLOBAL_LOAD | GLOBAL_STORE => {
+                globals.push(ip);
+                ip += 1;
+            },
+            JUMP_TABLE => {
+                let count = code[ip + 1] as usize;
+                for i in 0..count + 1 {
+                    addresses.push(ip + 2 + i);
+                }
+                ip += count + 3;
+            },
+            POP | LOCAL_LOAD | LOCAL_STORE | I_CONSTANT | F_CONSTANT | ARG_LOAD | ARG_STORE | LOCAL_ALLOC | FIELD_STORE | CALL_INDIRECT | THROW_ERROR
+                | LOCAL_ADDRESS | ARG_ADDRESS | REF_LOAD | REF_STORE => ip += 1,
+            FIELD_LOAD => ip += 2,
+            S_CONSTANT => {
+                while code[ip] != 0 {
+                    ip += 1;
+                }
+                ip += 1;
+            },
+            USE => {
+                if code[ip] == PRINT || code[ip] == READ || code[ip] == MAP_NEW || code[ip] == READ_OPTION || code[ip] == ENV_GET
+                    || code[ip] == FILE_OPEN || code[ip] == FILE_WRITE || code[ip] == DIR_LIST {
+                    ip += 1;
+                }
+                ip += 1;
+            },
+            STRUCT_NEW => ip += code[ip] as usize + 1,
+            _ => {},
+        }
+    }
+    (addresses, globals)
+}
+
+fn push_string(data: &mut Vec<i64>, string: &str) {
+    for c in string.chars() {
+        data.push(c as u8 as i64);
+    }
+    data.push(0);
+}
+
+fn push_symbol(data: &mut Vec<i64>, symbol: &Symbol, is_import: bool) {
+    data.push(symbol.kind);
+    push_string(data, &symbol.name);
+    push_string(data, &symbol.signature.var_type);
+    data.push(symbol.signature.security);
+    data.push(symbol.signature.arg_types.len() as i64);
+    for i in 0..symbol.signature.arg_types.len() {
+        push_string(data, &symbol.signature.arg_types[i]);
+        data.push(symbol.signature.arg_securities[i]);
+    }
+    if is_import && symbol.kind == FUNCTION {
+        data.push(symbol.refs.len() as i64);
+        for location in symbol.refs.iter() {
+            data.push(*location as i64);
+        }
+    } else {
+        data.push(symbol.value);
+    }
+}
+
+// magic init_len init_locals num_globals code_len code... num_relocations relocations...
+// num_global_relocations global_relocations... num_exports exports... num_imports imports...
+pub fn output_object(object: &Object, filename: &String) {
+    let (mut addresses, globals) = operand_locations(&object.code);
+    // calls to other objects are patched from the import table instead
+    for import in object.imports.iter() {
+        addresses.retain(|x| !import.refs.contains(x));
+    }
+
+    let mut data: Vec<i64> = vec![MAGIC, object.init_len as i64, object.init_locals, object.num_globals, object.code.len() as i64];
+    data.extend(object.code.iter());
+    data.push(addresses.len() as i64);
+    data.extend(addresses.iter().map(|x| *x as i64));
+    data.push(globals.len() as i64);
+    data.extend(globals.iter().map(|x| *x as i64));
+    data.push(object.exports.len() as i64);
+    for symbol in object.exports.iter() {
+        push_symbol(&mut data, symbol, false);
+    }
+    data.push(object.imports.len() as i64);
+    for symbol in object.imports.iter() {
+        push_symbol(&mut data, symbol, true);
+    }
+
+    let mut binary_data: Vec<u8> = Vec::new();
+    for chunk in data {
+        binary_data.extend_from_slice(&chunk.to_be_bytes());
+    }
+    let mut file = fs::File::create(filename).expect("Failed to create object file.");
+    file.write_all(&binary_data).expect("Failed to write to object file");
+}
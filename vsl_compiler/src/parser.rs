@@ -2,13 +2,17 @@ use std::fs;
 use std::io::Write;
 use std::collections::HashMap;
 
+use vsl_policy::Policy;
+
 #[path = "lexer.rs"]
 pub mod lexer;
 
 #[path = "standard_library.rs"]
 pub mod standard_library;
 
-const POP: i64 = 1;
+#[path = "object.rs"]
+pub mod object;
+
 const LOCAL_LOAD: i64 = 2;
 const LOCAL_STORE: i64 = 3;
 
@@ -23,6 +27,14 @@ const I_GREATER: i64 = 11;
 const I_NOT_EQUAL: i64 = 12;
 const I_LESS_EQUAL: i64 = 13;
 const I_GREATER_EQUAL: i64 = 14;
+const I_MOD: i64 = 43;
+const I_BIT_AND: i64 = 45;
+const I_BIT_OR: i64 = 46;
+const I_BIT_XOR: i64 = 47;
+const I_SHIFT_LEFT: i64 = 48;
+const I_SHIFT_RIGHT: i64 = 49;
+const I_POW: i64 = 50;
+const I_NEG: i64 = 52;
 
 const F_CONSTANT: i64 = 15;
 const F_ADD: i64 = 16;
@@ -35,17 +47,20 @@ const F_GREATER: i64 = 22;
 const F_NOT_EQUAL: i64 = 23;
 const F_LESS_EQUAL: i64 = 24;
 const F_GREATER_EQUAL: i64 = 25;
+const F_MOD: i64 = 44;
+const F_POW: i64 = 51;
+const F_NEG: i64 = 53;
 
 const S_CONSTANT: i64 = 26;
 const S_ADD: i64 = 27;
 const S_EQUAL: i64 = 28;
 const S_NOT_EQUAL: i64 = 29;
 
-const OP_AND: i64 = 30;
-const OP_OR: i64 = 31;
+const OP_NOT: i64 = 41;
 
 const JUMP_IF_FALSE: i64 = 32;
 const JUMP: i64 = 33;
+const JUMP_IF_TRUE: i64 = 42;
 
 const CALL: i64 = 34;
 const RETURN_VAL: i64 = 35;
@@ -57,6 +72,54 @@ const USE: i64 = 39;
 
 const HALT: i64 = 40;
 
+const LOCAL_ALLOC: i64 = 54;
+
+const STRUCT_NEW: i64 = 55;
+const FIELD_LOAD: i64 = 56;
+const FIELD_STORE: i64 = 57;
+
+const JUMP_TABLE: i64 = 58;
+
+const GLOBAL_LOAD: i64 = 59;
+const GLOBAL_STORE: i64 = 60;
+
+const CLOSURE_NEW: i64 = 61;
+const CALL_INDIRECT: i64 = 62;
+
+const I_ADD_CHECKED: i64 = 63;
+const I_SUB_CHECKED: i64 = 64;
+const I_MUL_CHECKED: i64 = 65;
+const I_DIV_CHECKED: i64 = 66;
+const I_POW_CHECKED: i64 = 67;
+const I_NEG_CHECKED: i64 = 68;
+
+const D_ADD: i64 = 69;
+const D_SUB: i64 = 70;
+const D_MUL: i64 = 71;
+const D_DIV: i64 = 72;
+const D_NEG: i64 = 73;
+
+const B_ADD: i64 = 74;
+const B_SUB: i64 = 75;
+const B_MUL: i64 = 76;
+const B_DIV: i64 = 77;
+const B_MOD: i64 = 78;
+const B_POW: i64 = 79;
+const B_NEG: i64 = 80;
+const B_COMPARE: i64 = 81;
+
+const TRY_BEGIN: i64 = 82;
+const TRY_END: i64 = 83;
+const THROW_ERROR: i64 = 84;
+
+const LOCAL_ADDRESS: i64 = 85;
+const ARG_ADDRESS: i64 = 86;
+const REF_LOAD: i64 = 87;
+const REF_STORE: i64 = 88;
+
+const ARGS_LOAD: i64 = 89;
+const EXIT: i64 = 90;
+
 //----------------------------------------------------------------------------------
 
 const SEMI_COLON: u8 = 0;
@@ -73,7 +136,7 @@ const MUL: u8 = 11;
 const DIV: u8 = 12;
 const SUB: u8 = 13;
 
-//const MOD: u8 = 14;
+const MOD: u8 = 14;
 const LESS: u8 = 15;
 const GREATER: u8 = 16;
 const EQUAL_EQUAL: u8 = 17;
@@ -104,8 +167,367 @@ const INT: u8 = 38;
 const FLOAT: u8 = 39;
 const STRING: u8 = 40;
 
+const BOOL: u8 = 41;
+const TRUE: u8 = 42;
+const FALSE: u8 = 43;
+const NOT: u8 = 44;
+const FOR: u8 = 51;
+const IN: u8 = 52;
+const CONTINUE: u8 = 53;
+const RANGE: u8 = 54;
+const LABEL: u8 = 55;
+const STRUCT: u8 = 56;
+const DOT: u8 = 57;
+const ENUM: u8 = 58;
+const MATCH: u8 = 59;
+const DOUBLE_COLON: u8 = 60;
+const ARROW: u8 = 61;
+const MAP: u8 = 62;
+const CONST: u8 = 63;
+const STATIC: u8 = 64;
+const THIN_ARROW: u8 = 68;
+const AS: u8 = 69;
+const CHECKED: u8 = 70;
+const DECIMAL: u8 = 71;
+const DECIMAL_TYPE: u8 = 72;
+const BIGINT: u8 = 73;
+const BIGINT_TYPE: u8 = 74;
+const QUESTION: u8 = 75;
+const TRY: u8 = 76;
+const CATCH: u8 = 77;
+const THROW: u8 = 78;
+const REF: u8 = 79;
+const MUT: u8 = 80;
+const FILE: u8 = 81;
+const BIT_AND: u8 = 45;
+const BIT_OR: u8 = 46;
+const BIT_XOR: u8 = 47;
+const SHIFT_LEFT: u8 = 48;
+const SHIFT_RIGHT: u8 = 49;
+const POW: u8 = 50;
+
+// expression type that accepts any type
+const ANY: u8 = 255;
+// types from FIRST_USER_TYPE up to ANY are given to user defined types
+const FIRST_USER_TYPE: u8 = 100;
+
 //----------------------------------------------------------------------------------
 
+fn token_to_type(token_num: u8) -> u8 {
+    match token_num {
+        INT_TYPE => INT,
+        FLOAT_TYPE => FLOAT,
+        STRING_TYPE => STRING,
+        DECIMAL_TYPE => DECIMAL,
+        BIGINT_TYPE => BIGINT,
+        VOID | VEC_INT | VEC_FLOAT | VEC_STRING | BOOL | FILE => token_num,
+        _ => INT,
+    }
+}
+
+fn operator_name(operator: u8) -> &'static str {
+    match operator {
+        ADD => "+",
+        SUB => "-",
+        MUL => "*",
+        DIV => "/",
+        MOD => "%",
+        BIT_AND => "&",
+        BIT_OR => "|",
+        BIT_XOR => "^",
+        SHIFT_LEFT => "<<",
+        SHIFT_RIGHT => ">>",
+        POW => "**",
+        LESS => "<",
+        GREATER => ">",
+        EQUAL_EQUAL => "==",
+        NOT_EQUAL => "!=",
+        LESS_EQUAL => "<=",
+        GREATER_EQUAL => ">=",
+        _ => "unknown",
+    }
+}
+
+// value of a constant expression, ints, bools and floats are kept as their bytecode value
+enum Constant {
+    Number(i64),
+    String(String),
+}
+
+// why a constant expression could not be folded
+enum FoldError {
+    NotConstant,
+    Overflow,
+    DivisionByZero,
+}
+
+fn float_value(value: i64) -> f64 {
+    f64::from_be_bytes(value.to_be_bytes())
+}
+
+fn float_bits(value: f64) -> i64 {
+    i64::from_be_bytes(value.to_be_bytes())
+}
+
+// Runs the code of a constant expression the way the VM would.
+fn fold_constant(code: &[i64], start: usize) -> Result<Constant, FoldError> {
+    let mut stack: Vec<Constant> = Vec::new();
+    // hidden locals hold an operand while the one below it is converted
+    let mut locals: HashMap<i64, Constant> = HashMap::new();
+    let mut ip = start;
+    while ip < code.len() {
+        let opcode = code[ip];
+        ip += 1;
+        match opcode {
+            I_CONSTANT | F_CONSTANT => {
+                stack.push(Constant::Number(code[ip]));
+                ip += 1;
+            },
+            S_CONSTANT => {
+                let mut string = String::new();
+                while code[ip] != 0 {
+                    string.push(code[ip] as u8 as char);
+                    ip += 1;
+                }
+                ip += 1;
+                stack.push(Constant::String(string));
+            },
+            S_ADD | S_EQUAL | S_NOT_EQUAL => {
+                let b = match stack.pop() { Some(Constant::String(x)) => x, _ => return Err(FoldError::NotConstant) };
+                let a = match stack.pop() { Some(Constant::String(x)) => x, _ => return Err(FoldError::NotConstant) };
+                match opcode {
+                    S_ADD => stack.push(Constant::String(a + &b)),
+                    S_EQUAL => stack.push(Constant::Number((a == b) as i64)),
+                    _ => stack.push(Constant::Number((a != b) as i64)),
+                }
+            },
+            I_NEG | I_NEG_CHECKED | D_NEG | F_NEG | OP_NOT => {
+                let a = match stack.pop() { Some(Constant::Number(x)) => x, _ => return Err(FoldError::NotConstant) };
+                match opcode {
+                    I_NEG => stack.push(Constant::Number(a.wrapping_neg())),
+                    I_NEG_CHECKED | D_NEG => stack.push(Constant::Number(a.checked_neg().ok_or(FoldError::Overflow)?)),
+                    F_NEG => stack.push(Constant::Number(float_bits(-float_value(a)))),
+                    _ => stack.push(Constant::Number((a == 0) as i64)),
+                }
+            },
+            LOCAL_STORE => {
+                locals.insert(code[ip], stack.pop().ok_or(FoldError::NotConstant)?);
+                ip += 1;
+            },
+            LOCAL_LOAD => {
+                stack.push(locals.remove(&code[ip]).ok_or(FoldError::NotConstant)?);
+                ip += 1;
+            },
+            USE => {
+                let a = match stack.pop() { Some(Constant::Number(x)) => x, _ => return Err(FoldError::NotConstant) };
+                match code[ip] {
+                    standard_library::INT_TO_FLOAT => stack.push(Constant::Number(float_bits(a as f64))),
+                    standard_library::FLOAT_TO_INT => stack.push(Constant::Number(float_value(a) as i64)),
+                    standard_library::INT_TO_DECIMAL => stack.push(Constant::Number(a.checked_mul(DECIMAL_ONE).ok_or(FoldError::Overflow)?)),
+                    standard_library::DECIMAL_TO_INT => stack.push(Constant::Number(a / DECIMAL_ONE)),
+                    _ => return Err(FoldError::NotConstant),
+                }
+                ip += 1;
+            },
+            JUMP => ip = code[ip] as usize,
+            JUMP_IF_FALSE | JUMP_IF_TRUE => {
+                let a = match stack.pop() { Some(Constant::Number(x)) => x, _ => return Err(FoldError::NotConstant) };
+                if (a != 0) == (opcode == JUMP_IF_TRUE) {
+                    ip = code[ip] as usize;
+                } else {
+                    ip += 1;
+                }
+            },
+            _ => {
+                let b = match stack.pop() { Some(Constant::Number(x)) => x, _ => return Err(FoldError::NotConstant) };
+                let a = match stack.pop() { Some(Constant::Number(x)) => x, _ => return Err(FoldError::NotConstant) };
+                stack.push(Constant::Number(fold_operator(opcode, a, b)?));
+            },
+        }
+    }
+    if stack.len() != 1 {
+        return Err(FoldError::NotConstant);
+    }
+    stack.pop().ok_or(FoldError::NotConstant)
+}
+
+// decimals hold their value times 10^4
+const DECIMAL_ONE: i64 = 10000;
+
+// Value of a decimal literal such as -12.5d.
+fn decimal_literal(literal: &str) -> Option<i64> {
+    let digits = literal.trim_end_matches('d');
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((x, y)) => (x, y),
+        None => (digits, ""),
+    };
+    if fraction.len() > 4 {
+        return None;
+    }
+    let whole_value = whole.parse::<i64>().ok()?.checked_mul(DECIMAL_ONE)?;
+    let fraction_value = format!("{:0<4}", fraction).parse::<i64>().ok()?;
+    if whole.starts_with('-') {
+        return whole_value.checked_sub(fraction_value);
+    }
+    whole_value.checked_add(fraction_value)
+}
+
+// n / d rounded half to even the way the VM multiplies and divides decimals.
+fn decimal_round_div(n: i128, d: i128) -> Option<i64> {
+    if d == 0 {
+        return None;
+    }
+    let mut quotient = n / d;
+    let twice_remainder = (n % d).abs() * 2;
+    if twice_remainder > d.abs() || (twice_remainder == d.abs() && quotient % 2 != 0) {
+        quotient += if (n < 0) != (d < 0) { -1 } else { 1 };
+    }
+    if quotient > i64::MAX as i128 || quotient < i64::MIN as i128 {
+        return None;
+    }
+    Some(quotient as i64)
+}
+
+// Integer power for exponents of any size, wrapping around on overflow.
+fn int_pow_wrapping(a: i64, b: u64) -> i64 {
+    let mut result: i64 = 1;
+    let mut base = a;
+    let mut exponent = b;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.wrapping_mul(base);
+        }
+        base = base.wrapping_mul(base);
+        exponent >>= 1;
+    }
+    result
+}
+
+// Integer power, None on overflow. Bases other than -1, 0 and 1 overflow before the exponent reaches 64.
+fn int_pow_checked(a: i64, b: u64) -> Option<i64> {
+    if (-1..=1).contains(&a) {
+        return Some(int_pow_wrapping(a, b));
+    }
+    if b >= 64 {
+        return None;
+    }
+    a.checked_pow(b as u32)
+}
+
+fn fold_operator(opcode: i64, a: i64, b: i64) -> Result<i64, FoldError> {
+    let (x, y) = (float_value(a), float_value(b));
+    if b == 0 && (opcode == I_DIV || opcode == I_DIV_CHECKED || opcode == I_MOD || opcode == D_DIV) {
+        return Err(FoldError::DivisionByZero);
+    }
+    let result = match opcode {
+        I_ADD => a.wrapping_add(b),
+        I_SUB => a.wrapping_sub(b),
+        I_MUL => a.wrapping_mul(b),
+        I_DIV => a.wrapping_div(b),
+        I_ADD_CHECKED | D_ADD => a.checked_add(b).ok_or(FoldError::Overflow)?,
+        D_SUB => a.checked_sub(b).ok_or(FoldError::Overflow)?,
+        D_MUL => decimal_round_div(a as i128 * b as i128, DECIMAL_ONE as i128).ok_or(FoldError::Overflow)?,
+        D_DIV => decimal_round_div(a as i128 * DECIMAL_ONE as i128, b as i128).ok_or(FoldError::Overflow)?,
+        I_SUB_CHECKED => a.checked_sub(b).ok_or(FoldError::Overflow)?,
+        I_MUL_CHECKED => a.checked_mul(b).ok_or(FoldError::Overflow)?,
+        I_DIV_CHECKED => a.checked_div(b).ok_or(FoldError::Overflow)?,
+        I_MOD => a.wrapping_rem(b),
+        I_BIT_AND => a & b,
+        I_BIT_OR => a | b,
+        I_BIT_XOR => a ^ b,
+        I_SHIFT_LEFT => a.wrapping_shl(b as u32),
+        I_SHIFT_RIGHT => a.wrapping_shr(b as u32),
+        I_POW | I_POW_CHECKED => {
+            if b >= 0 && opcode == I_POW_CHECKED {
+                int_pow_checked(a, b as u64).ok_or(FoldError::Overflow)?
+            } else if b >= 0 {
+                int_pow_wrapping(a, b as u64)
+            } else if a == 0 {
+                return Err(FoldError::DivisionByZero);
+            } else if a == 1 || (a == -1 && b % 2 == 0) {
+                1
+            } else if a == -1 {
+                -1
+            } else {
+                0
+            }
+        },
+        I_EQUAL => (a == b) as i64,
+        I_NOT_EQUAL => (a != b) as i64,
+        I_LESS => (a < b) as i64,
+        I_GREATER => (a > b) as i64,
+        I_LESS_EQUAL => (a <= b) as i64,
+        I_GREATER_EQUAL => (a >= b) as i64,
+        F_ADD => float_bits(x + y),
+        F_SUB => float_bits(x - y),
+        F_MUL => float_bits(x * y),
+        F_DIV => float_bits(x / y),
+        F_MOD => float_bits(x % y),
+        F_POW => float_bits(x.powf(y)),
+        F_EQUAL => (x == y) as i64,
+        F_NOT_EQUAL => (x != y) as i64,
+        F_LESS => (x < y) as i64,
+        F_GREATER => (x > y) as i64,
+        F_LESS_EQUAL => (x <= y) as i64,
+        F_GREATER_EQUAL => (x >= y) as i64,
+        _ => return Err(FoldError::NotConstant),
+    };
+    Ok(result)
+}
+
+fn is_number(var_type: u8) -> bool {
+    var_type == INT || var_type == FLOAT || var_type == DECIMAL || var_type == BIGINT
+}
+
+// standard library function converting an int to a float, decimal or bigint
+fn int_conversion(var_type: u8) -> i64 {
+    if var_type == DECIMAL {
+        return standard_library::INT_TO_DECIMAL;
+    }
+    if var_type == BIGINT {
+        return standard_library::INT_TO_BIGINT;
+    }
+    standard_library::INT_TO_FLOAT
+}
+
+fn type_to_sl_type(var_type: u8) -> i64 {
+    match var_type {
+        INT => standard_library::INT,
+        FLOAT => standard_library::FLOAT,
+        STRING => standard_library::STRING,
+        BOOL => standard_library::BOOL,
+        DECIMAL => standard_library::DECIMAL,
+        BIGINT => standard_library::BIGINT,
+        VEC_INT => standard_library::VEC_INT,
+        VEC_FLOAT => standard_library::VEC_FLOAT,
+        VEC_STRING => standard_library::VEC_STRING,
+        FILE => standard_library::FILE,
+        _ => standard_library::VOID,
+    }
+}
+
+fn sl_type_to_type(sl_type: i64) -> u8 {
+    match sl_type {
+        standard_library::INT => INT,
+        standard_library::FLOAT => FLOAT,
+        standard_library::STRING => STRING,
+        standard_library::BOOL => BOOL,
+        standard_library::DECIMAL => DECIMAL,
+        standard_library::BIGINT => BIGINT,
+        standard_library::VEC_INT => VEC_INT,
+        standard_library::VEC_FLOAT => VEC_FLOAT,
+        standard_library::VEC_STRING => VEC_STRING,
+        standard_library::FILE => FILE,
+        standard_library::ANY => ANY,
+        _ => VOID,
+    }
+}
+
+// how an argument is passed, a reference argument holds the stack location of the caller's variable
+const BY_VALUE: u8 = 0;
+const BY_REF: u8 = 1;
+const BY_MUT_REF: u8 = 2;
+
 struct Variable {
     pub mem_location: i64,
     pub var_type: u8,
@@ -114,6 +536,83 @@ struct Variable {
     pub arg_location: i64,
     pub scope: i64,
     pub function_name: String,
+    pub pass_by: u8,
+}
+
+struct Struct {
+    pub type_num: u8,
+    pub field_names: Vec<String>,
+    pub field_types: Vec<u8>,
+    pub field_securities: Vec<i64>,
+}
+
+// enum values are stored like structs with the variant number as the first field
+struct Enum {
+    pub type_num: u8,
+    pub variant_names: Vec<String>,
+    pub payload_types: Vec<Vec<u8>>,
+    pub payload_securities: Vec<Vec<i64>>,
+}
+
+struct Map {
+    pub type_num: u8,
+    pub key_type: u8,
+    pub key_security: i64,
+    pub value_type: u8,
+    pub value_security: i64,
+}
+
+// constants are folded into the code using them, statics live in the VM globals
+struct Global {
+    pub mem_location: i64,
+    pub var_type: u8,
+    pub security: i64,
+    pub is_const: bool,
+    pub value: Vec<i64>,
+    pub is_extern: bool,
+}
+
+// fn(arg_type:security, ...) -> return_type:security, the return type of fn(...) is void
+struct FnType {
+    pub type_num: u8,
+    pub arg_types: Vec<u8>,
+    pub arg_securities: Vec<i64>,
+    pub return_type: u8,
+    pub return_security: i64,
+}
+
+// A function literal being parsed. Variables of the enclosing function that it uses are captured by value
+// and passed after its arguments.
+struct Closure {
+    pub name: String,
+    pub outer_vars: HashMap<String, Variable>,
+    pub num_args: i64,
+    pub captures: Vec<String>,
+    pub security: i64,
+}
+
+// A generic function is compiled from its tokens for every set of types it is called with.
+struct Generic {
+    pub start: usize,
+    pub type_params: Vec<String>,
+}
+
+struct Instance {
+    pub name: String,
+    pub start: usize,
+    pub bindings: HashMap<String, u8>,
+    pub levels: HashMap<String, i64>,
+    pub line_num: u64,
+}
+
+struct Loop {
+    pub label: String,
+    // the variable of a for loop, which its body can not change
+    pub variable: String,
+    pub breaks: Vec<usize>,
+    pub continues: Vec<usize>,
+    // try blocks entered outside the loop
+    pub try_depth: usize,
 }
 
 struct Function {
@@ -123,7 +622,9 @@ struct Function {
     pub num_args: i64,
     pub arg_types: Vec<u8>,
     pub arg_securities: Vec<i64>,
+    pub arg_pass_by: Vec<u8>,
     pub times_parsed: i64,
+    pub is_extern: bool,
 }
 
 pub struct Parser {
@@ -135,15 +636,46 @@ pub struct Parser {
     stack_size: i64,
     fn_data: HashMap<String, Function>,
     current_fn_name: String,
-    return_num: i64,
+    // whether control can not continue past the last statement parsed, and if it can, the line it falls through on
+    terminates: bool,
+    open_line: u64,
     current_scope: i64,
     sl_data: HashMap<String, standard_library::SlData>,
-    loop_nest_num: i64,
+    loops: Vec<Loop>,
+    // try blocks of the current function the parser is within, their handlers are removed by an early exit
+    try_depth: usize,
+    fn_calls: Vec<(String, usize)>,
+    struct_data: HashMap<String, Struct>,
+    enum_data: HashMap<String, Enum>,
+    map_data: HashMap<String, Map>,
+    global_data: HashMap<String, Global>,
+    num_globals: i64,
+    user_types: Vec<String>,
+    fn_type_data: HashMap<String, FnType>,
+    closures: Vec<Closure>,
+    num_closures: i64,
+    generic_data: HashMap<String, Generic>,
+    instances: Vec<Instance>,
+    // types of the type parameters of the generic instance being parsed
+    type_params: HashMap<String, u8>,
+    // levels of the security level parameters of the generic instance being parsed
+    level_params: HashMap<String, i64>,
+    instance_name: String,
+    // with -c, declarations before root_start belong to other objects
+    object_mode: bool,
+    root_start: usize,
+    exports: Vec<String>,
+    init_len: usize,
+    init_locals: i64,
+    // integer overflow stops the program in every function with --checked, or in the current one
+    checked_mode: bool,
+    is_checked: bool,
+    policy: Policy,
 }
 
 impl Parser {
-    pub fn new(tokens: &Vec<lexer::Token>) -> Parser {
-        let parser = Parser {
+    pub fn new(tokens: &[lexer::Token]) -> Parser {
+        Parser {
             error: false,
             num_tokens: tokens.len(),
             current_token_num: 0,
@@ -152,12 +684,36 @@ impl Parser {
             stack_size: 0,
             fn_data: HashMap::new(),
             current_fn_name: String::new(),
-            return_num: 0,
+            terminates: false,
+            open_line: 0,
             current_scope: -1,
             sl_data: standard_library::get_sl_data(),
-            loop_nest_num: 0,
-        };
-        parser
+            loops: Vec::new(),
+            try_depth: 0,
+            fn_calls: Vec::new(),
+            struct_data: HashMap::new(),
+            enum_data: HashMap::new(),
+            map_data: HashMap::new(),
+            global_data: HashMap::new(),
+            num_globals: 0,
+            user_types: Vec::new(),
+            fn_type_data: HashMap::new(),
+            closures: Vec::new(),
+            num_closures: 0,
+            generic_data: HashMap::new(),
+            instances: Vec::new(),
+            type_params: HashMap::new(),
+            level_params: HashMap::new(),
+            instance_name: String::new(),
+            object_mode: false,
+            checked_mode: false,
+            is_checked: false,
+            policy: Policy::new(),
+            root_start: 0,
+            exports: Vec::new(),
+            init_len: 0,
+            init_locals: 0,
+        }
     }
 
     fn is_last_token(&mut self) -> bool {
@@ -168,7 +724,7 @@ impl Parser {
     }
 
     fn consume_token(&mut self) {
-        if self.is_last_token() == false {
+        if !self.is_last_token() {
             self.current_token_num += 1;
         }
     }
@@ -176,7 +732,7 @@ impl Parser {
     pub fn output_code(&mut self) {
         let mut binary_data: Vec<u8> = Vec::new();
     
-        for chunk in self.code.to_vec() {
+        for chunk in self.code.iter() {
             let bytes = chunk.to_be_bytes();
             binary_data.push(bytes[0]);
             binary_data.push(bytes[1]);
@@ -192,1439 +748,3637 @@ impl Parser {
         file.write_all(&binary_data).expect("Failed to write to binary file");
     }
 
-    fn index_functions(&mut self, tokens: &Vec<lexer::Token>) {
-        let mut instruction_counter: i64 = 3;
-        
-        while self.is_last_token() == false {
-            match tokens[self.current_token_num].token_num {
-                FN => {
-                    let mut fn_type: u8 = INT;
-                    let identifier: String;
-                    let mut security: i64 = 0;
-                    let mem_location: i64 = instruction_counter;
-                    let mut num_args: i64 = 0;
-                    let mut arg_types = Vec::new();
-                    let mut arg_securities = Vec::new();
-
-                    // fn
-                    self.consume_token();
+    // Only the declarations of tokens before root_start are compiled, their functions and statics are imported.
+    pub fn set_object_mode(&mut self, root_start: usize, exports: Vec<String>) {
+        self.object_mode = true;
+        self.root_start = root_start;
+        self.exports = exports;
+    }
 
-                    if tokens[self.current_token_num].token_num != VOID {
-                        if tokens[self.current_token_num].token_num == INT_TYPE {
-                            fn_type = INT;
-                        } else if tokens[self.current_token_num].token_num == FLOAT_TYPE {
-                            fn_type = FLOAT;
-                        } else if tokens[self.current_token_num].token_num == STRING_TYPE {
-                            fn_type = STRING;
-                        }
-                        // type
-                        self.consume_token();
-                        // colon
-                        self.consume_token();
-                        if tokens[self.current_token_num].token_num == INT {
-                            security = tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer.");
-                        }
-                        // security
-                        self.consume_token();
-                    } else {
-                        fn_type = VOID;
-                        // type
-                        self.consume_token();
-                    }
-                    identifier = tokens[self.current_token_num].token_string.clone();
-                    if self.sl_data.contains_key(&identifier) {
-                        println!("Function name '{}' cannot be the same as a standard library function on line {}.", identifier, tokens[self.current_token_num].line_num);
-                        self.error = true;
-                    }
-                    // identifier
-                    self.consume_token();
-                    // left parenthesis
-                    self.consume_token();
+    pub fn set_checked_mode(&mut self) {
+        self.checked_mode = true;
+        self.is_checked = true;
+    }
 
-                    if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-                        loop {
-                            let mut var_type = INT;
-                            let mut var_security: i64 = 0;
-                            num_args += 1;
-    
-                            // identifier
-                            self.consume_token();
-                            if tokens[self.current_token_num].token_num == INT_TYPE {
-                                var_type = INT;
-                            } else if tokens[self.current_token_num].token_num == FLOAT_TYPE {
-                                var_type = FLOAT;
-                            } else if tokens[self.current_token_num].token_num == STRING_TYPE {
-                                var_type = STRING;
-                            } else if tokens[self.current_token_num].token_num == VEC_INT {
-                                var_type = VEC_INT;
-                            } else if tokens[self.current_token_num].token_num == VEC_FLOAT {
-                                var_type = VEC_FLOAT;
-                            } else if tokens[self.current_token_num].token_num == VEC_STRING {
-                                var_type = VEC_STRING;
-                            }
-                            arg_types.push(var_type);
-                            // var type
-                            self.consume_token();
-                            // colon
-                            self.consume_token();
-                            if tokens[self.current_token_num].token_num == INT {
-                                var_security = tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer.");
-                            }
-                            arg_securities.push(var_security);
-                            // security
-                            self.consume_token();
-    
-                            if tokens[self.current_token_num].token_num != COMMA {
-                                break;
-                            } else {
-                                self.consume_token();
-                            }
-                        }
-                    }
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
 
-                    // right parenthesis
-                    self.consume_token();
+    fn is_extern(&self) -> bool {
+        self.object_mode && self.current_token_num < self.root_start
+    }
 
-                    let new_fn = Function {
-                        mem_location: mem_location,
-                        fn_type: fn_type,
-                        security: security,
-                        num_args: num_args,
-                        arg_types: arg_types,
-                        arg_securities: arg_securities,
-                        times_parsed: 0,
-                    };
+    fn fn_signature(&self, function: &Function) -> object::Signature {
+        let mut arg_types: Vec<String> = Vec::new();
+        for i in 0..function.arg_types.len() {
+            let prefix = match function.arg_pass_by[i] {
+                BY_REF => "ref ",
+                BY_MUT_REF => "mut ref ",
+                _ => "",
+            };
+            arg_types.push(format!("{}{}", prefix, self.type_name(function.arg_types[i])));
+        }
+        object::Signature {
+            var_type: self.type_name(function.fn_type),
+            security: function.security,
+            arg_types,
+            arg_securities: function.arg_securities.clone(),
+        }
+    }
 
-                    self.fn_data.insert(identifier, new_fn);
-                },
-                INT => {
-                    // check to make sure not variable security
-                    if self.current_token_num != 0 {
-                        if tokens[self.current_token_num - 1].token_num != COLON {
-                            instruction_counter += 2;
-                            self.consume_token();
-                        } else {
-                            self.consume_token();
-                        }
-                    } else {
-                        self.consume_token();
-                    }
-                },
-                FLOAT => {
-                    instruction_counter += 2;
-                    self.consume_token();
-                },
-                STRING => {
-                    // S_CONSTANT
-                    instruction_counter += 1;
-                    for c in tokens[self.current_token_num].token_string.chars() {
-                        // Every character
-                        if c != '"' && c != '\\' {
-                            instruction_counter += 1;
-                        }
-                    }
-                    // null terminator
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                LET => {
-                    self.consume_token();
-                    if self.current_token_num + 1 < self.num_tokens {
-                        if tokens[self.current_token_num + 1].token_num == INT_TYPE
-                            || tokens[self.current_token_num + 1].token_num == FLOAT_TYPE 
-                            || tokens[self.current_token_num + 1].token_num == STRING_TYPE 
-                            || tokens[self.current_token_num + 1].token_num == VEC_INT
-                            || tokens[self.current_token_num + 1].token_num == VEC_FLOAT
-                            || tokens[self.current_token_num + 1].token_num == VEC_STRING {
-                                instruction_counter += 2;
-                            }
-                    }
-                },
-                IDENTIFIER => {
-                    self.consume_token();
-                    if tokens[self.current_token_num].token_num != LEFT_PARENTHESIS {
-                        instruction_counter += 2;
-                    } else if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
-                        if self.sl_data.contains_key(&tokens[self.current_token_num - 1].token_string) {
-                            if tokens[self.current_token_num - 1].token_string == "print".to_string() {
-                                instruction_counter += 3;
-                                let mut tmp_current_token_num = self.current_token_num;
-                                while tmp_current_token_num < self.num_tokens && tokens[tmp_current_token_num].token_num != SEMI_COLON {
-                                    if tokens[tmp_current_token_num].token_num == IDENTIFIER {
-                                        tmp_current_token_num += 1;
-                                        if tmp_current_token_num < self.num_tokens && tokens[tmp_current_token_num].token_num == LEFT_PARENTHESIS {
-                                            let mut num_parenthesis: i64 = 1;
-                                            tmp_current_token_num += 1;
-                                            while num_parenthesis != 0 && tmp_current_token_num < self.num_tokens {
-                                                if tokens[tmp_current_token_num].token_num == LEFT_PARENTHESIS {
-                                                    num_parenthesis += 1;
-                                                    tmp_current_token_num += 1;
-                                                } else if tokens[tmp_current_token_num].token_num == RIGHT_PARENTHESIS {
-                                                    num_parenthesis -= 1;
-                                                    tmp_current_token_num += 1;
-                                                } else {
-                                                    tmp_current_token_num += 1;
-                                                }
-                                            }
-                                        }
-                                    }
-                                    if tmp_current_token_num < self.num_tokens && tokens[tmp_current_token_num].token_num == COMMA {
-                                        instruction_counter += 3;
-                                    }
-                                    tmp_current_token_num += 1;
-                                }
-                            } else if tokens[self.current_token_num - 1].token_string == "read".to_string() {
-                                instruction_counter += 3;
-                            } else {
-                                instruction_counter += 2;
-                            }
-                        } else {
-                            instruction_counter += 3;
-                        }
-                    }
-                },
-                ADD => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                SUB => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                DIV => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                MUL => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                EQUAL_EQUAL => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                NOT_EQUAL => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                LESS => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                LESS_EQUAL => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                GREATER => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                GREATER_EQUAL => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                AND => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                OR => {
-                    instruction_counter += 1;
-                    self.consume_token();
-                },
-                IF => {
-                    instruction_counter += 4;
-                    self.consume_token();
-                },
-                WHILE => {
-                    instruction_counter += 4;
-                    self.consume_token();
+    fn global_signature(&self, global: &Global) -> object::Signature {
+        object::Signature {
+            var_type: self.type_name(global.var_type),
+            security: global.security,
+            arg_types: Vec::new(),
+            arg_securities: Vec::new(),
+        }
+    }
+
+    pub fn output_object(&mut self, filename: &String) {
+        let mut exports: Vec<object::Symbol> = Vec::new();
+        for identifier in self.exports.iter() {
+            if let Some(x) = self.fn_data.get(identifier) {
+                exports.push(object::Symbol {
+                    kind: object::FUNCTION,
+                    name: identifier.clone(),
+                    value: x.mem_location,
+                    signature: self.fn_signature(x),
+                    refs: Vec::new(),
+                });
+            } else if let Some(x) = self.global_data.get(identifier) {
+                if !x.is_const {
+                    exports.push(object::Symbol {
+                        kind: object::STATIC,
+                        name: identifier.clone(),
+                        value: x.mem_location,
+                        signature: self.global_signature(x),
+                        refs: Vec::new(),
+                    });
                 }
-                RETURN => {
-                    instruction_counter += 1;
-                    self.consume_token();
+            }
+        }
+
+        let mut imports: Vec<object::Symbol> = Vec::new();
+        for (identifier, code_location) in self.fn_calls.iter() {
+            if let Some(x) = self.fn_data.get(identifier) {
+                if !x.is_extern {
+                    continue;
+                }
+                match imports.iter_mut().find(|y| y.name == *identifier) {
+                    Some(y) => y.refs.push(*code_location),
+                    None => imports.push(object::Symbol {
+                        kind: object::FUNCTION,
+                        name: identifier.clone(),
+                        value: 0,
+                        signature: self.fn_signature(x),
+                        refs: vec![*code_location],
+                    }),
                 }
-                _ => {
-                    self.consume_token();
-                },
             }
         }
+        // only statics of other objects the code uses are imported, in slot order so the object is the same every time
+        let (_, global_locations) = object::operand_locations(&self.code);
+        let mut extern_globals: Vec<(&String, &Global)> = self.global_data.iter()
+            .filter(|(_, x)| x.is_extern && !x.is_const && global_locations.iter().any(|y| self.code[*y] == x.mem_location))
+            .collect();
+        extern_globals.sort_by_key(|(_, x)| x.mem_location);
+        for (identifier, global) in extern_globals {
+            imports.push(object::Symbol {
+                kind: object::STATIC,
+                name: identifier.clone(),
+                value: global.mem_location,
+                signature: self.global_signature(global),
+                refs: Vec::new(),
+            });
+        }
+
+        let object = object::Object {
+            init_len: self.init_len,
+            init_locals: self.init_locals,
+            num_globals: self.num_globals,
+            code: self.code.clone(),
+            exports,
+            imports,
+        };
+        object::output_object(&object, filename);
     }
 
-    pub fn parse(&mut self, tokens: &Vec<lexer::Token>) -> bool {
-        self.index_functions(tokens);
-        match self.fn_data.get("main") {
-            Some(x) => {
-                self.code.push(CALL);
-                self.code.push(x.mem_location);
-                // main always has 0 arguments
-                self.code.push(0);
+    fn type_name(&self, var_type: u8) -> String {
+        match var_type {
+            INT => "int".to_string(),
+            FLOAT => "float".to_string(),
+            STRING => "string".to_string(),
+            BOOL => "bool".to_string(),
+            DECIMAL => "decimal".to_string(),
+            BIGINT => "bigint".to_string(),
+            VOID => "void".to_string(),
+            VEC_INT => "vec_int".to_string(),
+            VEC_FLOAT => "vec_float".to_string(),
+            VEC_STRING => "vec_string".to_string(),
+            FILE => "file".to_string(),
+            _ => {
+                if var_type >= FIRST_USER_TYPE && ((var_type - FIRST_USER_TYPE) as usize) < self.user_types.len() {
+                    return self.user_types[(var_type - FIRST_USER_TYPE) as usize].clone();
+                }
+                "unknown".to_string()
             },
-            None => {
-                println!("No main function found in program.");
-                self.error = true;
-            }
-        }
-        self.current_token_num = 0;
-        while self.is_last_token() == false {
-            self.fn_dec(tokens);
         }
-        self.error
     }
 
+    // User defined types are written as identifiers, unknown ones give ANY.
+    fn token_type(&self, token: &lexer::Token) -> u8 {
+        if token.token_num == IDENTIFIER {
+            if let Some(x) = self.type_params.get(&token.token_string) {
+                return *x;
+            }
+            if let Some(x) = self.struct_data.get(&token.token_string) {
+                return x.type_num;
+            }
+            if let Some(x) = self.enum_data.get(&token.token_string) {
+                return x.type_num;
+            }
+            return ANY;
+        }
+        token_to_type(token.token_num)
+    }
 
+    fn is_user_type(&self, identifier: &String) -> bool {
+        self.struct_data.contains_key(identifier) || self.enum_data.contains_key(identifier)
+    }
 
+    // Name of the enum with the given type, if it is an enum.
+    fn enum_name(&self, var_type: u8) -> Option<String> {
+        if var_type >= FIRST_USER_TYPE && ((var_type - FIRST_USER_TYPE) as usize) < self.user_types.len() {
+            let name = &self.user_types[(var_type - FIRST_USER_TYPE) as usize];
+            if self.enum_data.contains_key(name) {
+                return Some(name.clone());
+            }
+        }
+        None
+    }
 
+    // Option<T> and Result<T, E> are enums made for every set of types they are used with. The value inside
+    // is only as secret as the Option or Result holding it.
+    fn wrapper_type(&mut self, is_result: bool, value_type: u8, error_type: u8) -> u8 {
+        let name: String;
+        let variant_names: Vec<String>;
+        let payload_types: Vec<Vec<u8>>;
+        if is_result {
+            name = format!("Result<{}, {}>", self.type_name(value_type), self.type_name(error_type));
+            variant_names = vec!["Ok".to_string(), "Err".to_string()];
+            payload_types = vec![vec![value_type], vec![error_type]];
+        } else {
+            name = format!("Option<{}>", self.type_name(value_type));
+            variant_names = vec!["None".to_string(), "Some".to_string()];
+            payload_types = vec![Vec::new(), vec![value_type]];
+        }
+        if let Some(x) = self.enum_data.get(&name) {
+            return x.type_num;
+        }
+        if self.user_types.len() >= (ANY - FIRST_USER_TYPE) as usize {
+            return ANY;
+        }
+        let new_enum = Enum {
+            type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+            variant_names,
+            payload_securities: payload_types.iter().map(|x| vec![0; x.len()]).collect(),
+            payload_types,
+        };
+        let type_num = new_enum.type_num;
+        self.enum_data.insert(name.clone(), new_enum);
+        self.user_types.push(name);
+        type_num
+    }
 
+    // (is_result, value type, error type) of an Option or Result type, the error type of an Option is void.
+    fn wrapper_of(&self, var_type: u8) -> Option<(bool, u8, u8)> {
+        let name = self.enum_name(var_type)?;
+        let payload_types = &self.enum_data.get(&name)?.payload_types;
+        if name.starts_with("Option<") {
+            return Some((false, payload_types[1][0], VOID));
+        }
+        if name.starts_with("Result<") {
+            return Some((true, payload_types[0][0], payload_types[1][0]));
+        }
+        None
+    }
 
+    // Tuples are structs made for every set of element types and security levels, named like
+    // (int:0, string:2) with the fields 0, 1, ...
+    fn tuple_type(&mut self, element_types: Vec<u8>, element_securities: Vec<i64>) -> u8 {
+        let mut element_names: Vec<String> = Vec::new();
+        for i in 0..element_types.len() {
+            element_names.push(format!("{}:{}", self.type_name(element_types[i]), element_securities[i]));
+        }
+        let name = format!("({})", element_names.join(", "));
+        if let Some(x) = self.struct_data.get(&name) {
+            return x.type_num;
+        }
+        if self.user_types.len() >= (ANY - FIRST_USER_TYPE) as usize {
+            return ANY;
+        }
+        let new_struct = Struct {
+            type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+            field_names: (0..element_types.len()).map(|x| x.to_string()).collect(),
+            field_types: element_types,
+            field_securities: element_securities,
+        };
+        let type_num = new_struct.type_num;
+        self.struct_data.insert(name.clone(), new_struct);
+        self.user_types.push(name);
+        type_num
+    }
 
+    // (element types, element security levels) of a tuple type.
+    fn tuple_of(&self, var_type: u8) -> Option<(Vec<u8>, Vec<i64>)> {
+        if var_type < FIRST_USER_TYPE || ((var_type - FIRST_USER_TYPE) as usize) >= self.user_types.len() {
+            return None;
+        }
+        let name = &self.user_types[(var_type - FIRST_USER_TYPE) as usize];
+        if !name.starts_with("(") {
+            return None;
+        }
+        let x = self.struct_data.get(name)?;
+        Some((x.field_types.clone(), x.field_securities.clone()))
+    }
 
-
-
-
-
-    // Parsing code
-    //--------------------------------------------------------------------------------------------------------------------------
-
-    fn fn_dec(&mut self, tokens: &Vec<lexer::Token>) {
-        self.stack_size = 0;
-        self.fn_keyword(tokens);
-        let fn_type: u8;
-        if tokens[self.current_token_num].token_num != VOID {
-            fn_type = tokens[self.current_token_num].token_num;
-            self.fn_type(tokens);
-            self.colon(tokens);
-            self.integer(tokens);
-        } else {
-            fn_type = tokens[self.current_token_num].token_num;
-            self.fn_type(tokens);
+    // (type:security, type:security, ...)
+    fn parse_tuple_type(&mut self, tokens: &[lexer::Token], report_errors: bool) -> u8 {
+        let line_num = tokens[self.current_token_num].line_num;
+        let mut element_types: Vec<u8> = Vec::new();
+        let mut element_securities: Vec<i64> = Vec::new();
+        // (
+        self.consume_token();
+        let mut is_valid = true;
+        while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+            element_types.push(self.parse_type(tokens, report_errors));
+            is_valid = is_valid && tokens[self.current_token_num].token_num == COLON;
+            self.consume_token();
+            let mut element_security: i64 = 0;
+            if let Some(x) = self.security_level(tokens) {
+                element_security = x;
+            } else {
+                is_valid = false;
+            }
+            element_securities.push(element_security);
+            self.consume_token();
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.consume_token();
         }
-        self.current_fn_name = tokens[self.current_token_num].token_string.clone();
-        if self.current_fn_name == "main" {
-            if fn_type != VOID {
-                println!("Function 'main' must be of type void on line {}.", tokens[self.current_token_num].line_num);
+        is_valid = is_valid && tokens[self.current_token_num].token_num == RIGHT_PARENTHESIS;
+        self.consume_token();
+
+        if report_errors {
+            if !is_valid {
+                println!("Expected (type:security, type:security, ...) on line {}.", line_num);
+                self.error = true;
+            } else if element_types.len() < 2 {
+                println!("A tuple needs at least two elements on line {}.", line_num);
+                self.error = true;
+            } else if element_securities.iter().any(|x| *x < 0 || *x > 100) {
+                println!("Tuple security levels must be between 0 and 100 on line {}.", line_num);
                 self.error = true;
             }
         }
-        let identifier = tokens[self.current_token_num].token_string.clone();
-        match self.fn_data.get_mut(&identifier) {
-            Some(x) => {
-                x.times_parsed += 1;
-                if x.times_parsed > 1 {
-                    println!("Function with name '{}' already exists on line {}.", identifier, tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            },
-            None => {},
-        }
-        self.identifier(tokens);
-        if self.current_fn_name == "main" {
-            self.left_parenthesis(tokens);
-            self.right_parenthesis(tokens);
-        } else {
-            self.left_parenthesis(tokens);
-            if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-                let mut arg_location: i64 = 0;
-                if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-                    loop {
-                        let var_name = tokens[self.current_token_num].token_string.clone();
-                        let mut var_type = INT;
-                        let mut var_security: i64 = 0;
-
-                        // identifier
-                        self.identifier(tokens);
-                        if tokens[self.current_token_num].token_num == INT_TYPE {
-                            var_type = INT;
-                        } else if tokens[self.current_token_num].token_num == FLOAT_TYPE {
-                            var_type = FLOAT;
-                        } else if tokens[self.current_token_num].token_num == STRING_TYPE {
-                            var_type = STRING;
-                        } else if tokens[self.current_token_num].token_num == VEC_INT {
-                            var_type = VEC_INT;
-                        } else if tokens[self.current_token_num].token_num == VEC_FLOAT {
-                            var_type = VEC_FLOAT;
-                        } else if tokens[self.current_token_num].token_num == VEC_STRING {
-                            var_type = VEC_STRING;
-                        }
-                        // var type
-                        self.var_type(tokens);
-                        // colon
-                        self.colon(tokens);
-                        if tokens[self.current_token_num].token_num == INT {
-                            var_security = tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer.");
-                        }
-                        // security
-                        self.integer(tokens);
-
-                        let variable = Variable {
-                            mem_location: 0,
-                            var_type: var_type,
-                            security: var_security, 
-                            is_arg: true,
-                            arg_location: arg_location,
-                            scope: 0,
-                            function_name: identifier.clone(),
-                        };
+        self.tuple_type(element_types, element_securities)
+    }
 
-                        arg_location += 1;
-                        self.var_data.insert(var_name, variable);
+    // Option<type> or Result<type, type>
+    fn parse_wrapper_type(&mut self, tokens: &[lexer::Token], report_errors: bool) -> u8 {
+        let line_num = tokens[self.current_token_num].line_num;
+        let is_result = tokens[self.current_token_num].token_string == "Result";
+        // Option or Result
+        self.consume_token();
+        // less
+        self.consume_token();
+        let value_type = self.parse_type(tokens, report_errors);
+        let mut error_type = VOID;
+        let mut is_valid = true;
+        if is_result {
+            is_valid = tokens[self.current_token_num].token_num == COMMA;
+            self.consume_token();
+            error_type = self.parse_type(tokens, report_errors);
+        }
+        is_valid = is_valid && tokens[self.current_token_num].token_num == GREATER;
+        self.consume_token();
 
-                        if tokens[self.current_token_num].token_num != COMMA {
-                            break;
-                        } else {
-                            self.comma(tokens);
-                        }
-                    }
-                }
+        if report_errors {
+            if !is_valid && is_result {
+                println!("Expected Result<type, type> on line {}.", line_num);
+                self.error = true;
+            } else if !is_valid {
+                println!("Expected Option<type> on line {}.", line_num);
+                self.error = true;
             }
-            self.right_parenthesis(tokens);
-        }
-        self.return_num = 0;
-        let mut locals: Vec<i64> = Vec::new();
-        let mut breaks: Vec<i64> = Vec::new();
-        self.block(tokens, &mut locals, &mut breaks);
-        if self.return_num == 0 {
-            println!("Function '{}' has no return statement.", self.current_fn_name);
-            self.error = true;
         }
+        self.wrapper_type(is_result, value_type, error_type)
     }
 
-    fn block(&mut self, tokens: &Vec<lexer::Token>, locals: &mut Vec<i64>, breaks: &mut Vec<i64>) {
-        self.left_curley(tokens);
-        self.current_scope += 1;
-        while self.is_last_token() == false && tokens[self.current_token_num].token_num != RIGHT_CURLEY {
-            match tokens[self.current_token_num].token_num {
-                LET => {
-                    self.var_dec(tokens, locals);
-                    self.semi_colon(tokens);
-                },
-                IDENTIFIER => {
-                    if self.current_token_num + 1 < tokens.len() {
-                        if tokens[self.current_token_num + 1].token_num != LEFT_PARENTHESIS {
-                            self.var_def(tokens);
-                            self.semi_colon(tokens);
-                        } else if tokens[self.current_token_num + 1].token_num == LEFT_PARENTHESIS {
-                            match self.sl_data.get(&tokens[self.current_token_num].token_string) {
-                                Some(x) => {
-                                    if x.return_type != standard_library::VOID {
-                                        println!("Function not within an expression must be void on line {}.", tokens[self.current_token_num].line_num);
-                                        self.error = true;
-                                    }
-                                    self.sl_use(tokens, standard_library::VOID);
-                                    self.semi_colon(tokens);
-                                },
-                                None => {
-                                    self.fn_call(tokens);
-                                    self.semi_colon(tokens);
-                                },
-                            }
-                        }
-                    } else {
-                        println!("Program suddenly ended on line {}.", tokens[self.current_token_num].line_num);
-                        self.error = true;
-                    }
-                },
-                IF => {
-                    self.if_statement(tokens, locals, breaks);
-                },
-                WHILE => {
-                    self.while_statement(tokens, locals);
-                },
-                BREAK => {
-                    if self.loop_nest_num == 0 {
-                        println!("Cannot break out of a non loop on line {}.", tokens[self.current_token_num].line_num);
-                        self.error = true;
-                    }
-                    self.break_statement(tokens, breaks);
-                    self.semi_colon(tokens);
-                },
-                RETURN => {
-                    self.return_statement(tokens, locals);
-                    self.semi_colon(tokens);
-                },
-                _ => {
-                    println!("Beginning of unkown statement type on line {}.", tokens[self.current_token_num].line_num);
-                    self.consume_token();
-                    self.error = true;
-                },
-            }
+    // Parses a type, which is one token except for map<key_type:security, value_type:security>,
+    // fn(arg_type:security, ...) -> return_type:security, Option<type>, Result<type, type> and
+    // (type:security, type:security, ...).
+    fn parse_type(&mut self, tokens: &[lexer::Token], report_errors: bool) -> u8 {
+        if tokens[self.current_token_num].token_num == FN {
+            return self.parse_fn_type(tokens, report_errors);
         }
-        self.right_curley(tokens);
-        let mut keys: Vec<String> = Vec::new();
-        for (key, val) in self.var_data.iter() {
-            if val.function_name == self.current_fn_name && val.scope == self.current_scope {
-                keys.push(key.clone());
-            }
+        if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+            return self.parse_tuple_type(tokens, report_errors);
         }
-        for key in keys {
-            self.var_data.remove(&key);
+        let token = &tokens[self.current_token_num];
+        if token.token_num == IDENTIFIER && (token.token_string == "Option" || token.token_string == "Result")
+            && self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == LESS {
+            return self.parse_wrapper_type(tokens, report_errors);
         }
-        self.current_scope -= 1;
-    }
-
-    fn left_curley(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != LEFT_CURLEY {
-            println!("Expected '{}', got '{}' on line {}.", 123 as char, tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+        if tokens[self.current_token_num].token_num != MAP {
+            let var_type = self.token_type(&tokens[self.current_token_num]);
+            if report_errors {
+                self.var_type(tokens);
+            } else {
+                self.consume_token();
+            }
+            return var_type;
         }
-    }
-
-    fn right_curley(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != RIGHT_CURLEY {
-            println!("Expected '{}', got '{}' on line {}.", 125 as char, tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
+        let line_num = tokens[self.current_token_num].line_num;
+        // map
+        self.consume_token();
+        let mut is_valid = tokens[self.current_token_num].token_num == LESS;
+        self.consume_token();
+        let key_type = self.parse_type(tokens, report_errors);
+        is_valid = is_valid && tokens[self.current_token_num].token_num == COLON;
+        self.consume_token();
+        let mut key_security: i64 = 0;
+        if let Some(x) = self.security_level(tokens) {
+            key_security = x;
         } else {
-            self.consume_token();
+            is_valid = false;
         }
-    }
-
-    fn fn_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != FN {
-            println!("Expected 'fn', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
+        self.consume_token();
+        is_valid = is_valid && tokens[self.current_token_num].token_num == COMMA;
+        self.consume_token();
+        let value_type = self.parse_type(tokens, report_errors);
+        is_valid = is_valid && tokens[self.current_token_num].token_num == COLON;
+        self.consume_token();
+        let mut value_security: i64 = 0;
+        if let Some(x) = self.security_level(tokens) {
+            value_security = x;
         } else {
-            self.consume_token();
+            is_valid = false;
         }
-    }
+        self.consume_token();
+        is_valid = is_valid && tokens[self.current_token_num].token_num == GREATER;
+        self.consume_token();
 
-    fn fn_type(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != INT_TYPE &&
-            tokens[self.current_token_num].token_num != FLOAT_TYPE && 
-            tokens[self.current_token_num].token_num != STRING_TYPE &&
-            tokens[self.current_token_num].token_num != STRING_TYPE &&
-            tokens[self.current_token_num].token_num != VEC_INT && 
-            tokens[self.current_token_num].token_num != VEC_FLOAT && 
-            tokens[self.current_token_num].token_num != VEC_STRING &&
-            tokens[self.current_token_num].token_num != VOID {
-                println!("Expected type, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+        if report_errors {
+            if !is_valid {
+                println!("Expected map<key_type:security, value_type:security> on line {}.", line_num);
                 self.error = true;
-                self.consume_token();
-        } else {
-            self.consume_token();
+            } else if key_type != INT && key_type != STRING && key_type != ANY {
+                println!("Map keys must be of type int or string, got {} on line {}.", self.type_name(key_type), line_num);
+                self.error = true;
+            } else if !(0..=100).contains(&key_security) || !(0..=100).contains(&value_security) {
+                println!("Map security levels must be between 0 and 100 on line {}.", line_num);
+                self.error = true;
+            }
         }
+        self.map_type(key_type, key_security, value_type, value_security)
     }
 
-    fn semi_colon(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != SEMI_COLON {
-            println!("Expected ';', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-        } else {
-            self.consume_token();
+    // Every distinct map type gets one user defined type number.
+    fn map_type(&mut self, key_type: u8, key_security: i64, value_type: u8, value_security: i64) -> u8 {
+        let name = format!("map<{}:{}, {}:{}>", self.type_name(key_type), key_security, self.type_name(value_type), value_security);
+        if let Some(x) = self.map_data.get(&name) {
+            return x.type_num;
         }
+        if self.user_types.len() >= (ANY - FIRST_USER_TYPE) as usize {
+            return ANY;
+        }
+        let new_map = Map {
+            type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+            key_type,
+            key_security,
+            value_type,
+            value_security,
+        };
+        let type_num = new_map.type_num;
+        self.map_data.insert(name.clone(), new_map);
+        self.user_types.push(name);
+        type_num
     }
 
-    fn let_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != LET {
-            println!("Expected 'let', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
-        }
+    fn map_of_type(&self, var_type: u8) -> Option<&Map> {
+        self.map_data.values().find(|x| x.type_num == var_type)
     }
 
-    fn identifier(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != IDENTIFIER {
-            println!("Expected identifier, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
+    fn parse_fn_type(&mut self, tokens: &[lexer::Token], report_errors: bool) -> u8 {
+        let line_num = tokens[self.current_token_num].line_num;
+        let mut arg_types: Vec<u8> = Vec::new();
+        let mut arg_securities: Vec<i64> = Vec::new();
+        // fn
+        self.consume_token();
+        let mut is_valid = tokens[self.current_token_num].token_num == LEFT_PARENTHESIS;
+        self.consume_token();
+        while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+            arg_types.push(self.parse_type(tokens, report_errors));
+            is_valid = is_valid && tokens[self.current_token_num].token_num == COLON;
             self.consume_token();
-        } else {
+            let mut arg_security: i64 = 0;
+            if let Some(x) = self.security_level(tokens) {
+                arg_security = x;
+            } else {
+                is_valid = false;
+            }
+            arg_securities.push(arg_security);
             self.consume_token();
-        }
-    }
-
-    fn var_type(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != INT_TYPE &&
-            tokens[self.current_token_num].token_num != FLOAT_TYPE && 
-            tokens[self.current_token_num].token_num != STRING_TYPE && 
-            tokens[self.current_token_num].token_num != VEC_INT && 
-            tokens[self.current_token_num].token_num != VEC_FLOAT && 
-            tokens[self.current_token_num].token_num != VEC_STRING {
-                println!("Expected type, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-                self.error = true;
+            if tokens[self.current_token_num].token_num == COMMA {
                 self.consume_token();
-        } else {
-            self.consume_token();
+            } else if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                is_valid = false;
+                break;
+            }
         }
-    }
-
-    fn colon(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != COLON {
-            println!("Expected ':', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
+        // right parenthesis
+        self.consume_token();
+        let mut return_type = VOID;
+        let mut return_security: i64 = 0;
+        if tokens[self.current_token_num].token_num == THIN_ARROW {
             self.consume_token();
-        } else {
+            return_type = self.parse_type(tokens, report_errors);
+            is_valid = is_valid && tokens[self.current_token_num].token_num == COLON;
+            self.consume_token();
+            if let Some(x) = self.security_level(tokens) {
+                return_security = x;
+            } else {
+                is_valid = false;
+            }
             self.consume_token();
         }
-    }
-
-    fn integer(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != INT {
-            println!("Expected integer, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+        if report_errors && !is_valid {
+            println!("Expected fn(arg_type:security, ...) -> return_type:security on line {}.", line_num);
             self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
         }
+        self.function_type(&arg_types, &arg_securities, return_type, return_security)
     }
 
-    fn equal(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != EQUAL {
-            println!("Expected '=', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+    // Every distinct function signature gets one user defined type number.
+    fn function_type(&mut self, arg_types: &[u8], arg_securities: &[i64], return_type: u8, return_security: i64) -> u8 {
+        let mut args: Vec<String> = Vec::new();
+        for i in 0..arg_types.len() {
+            args.push(format!("{}:{}", self.type_name(arg_types[i]), arg_securities[i]));
+        }
+        let mut name = format!("fn({})", args.join(", "));
+        if return_type != VOID {
+            name = format!("{} -> {}:{}", name, self.type_name(return_type), return_security);
+        }
+        if let Some(x) = self.fn_type_data.get(&name) {
+            return x.type_num;
         }
+        if self.user_types.len() >= (ANY - FIRST_USER_TYPE) as usize {
+            return ANY;
+        }
+        let new_fn_type = FnType {
+            type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+            arg_types: arg_types.to_owned(),
+            arg_securities: arg_securities.to_owned(),
+            return_type,
+            return_security,
+        };
+        let type_num = new_fn_type.type_num;
+        self.fn_type_data.insert(name.clone(), new_fn_type);
+        self.user_types.push(name);
+        type_num
     }
 
-    fn left_parenthesis(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != LEFT_PARENTHESIS {
-            println!("Expected '(' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
-        }
+    fn fn_type_of(&self, var_type: u8) -> Option<&FnType> {
+        self.fn_type_data.values().find(|x| x.type_num == var_type)
     }
 
-    fn right_parenthesis(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-            println!("Expected ')' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+    fn index_user_types(&mut self, tokens: &[lexer::Token]) {
+        // every type name is known before the fields so that fields can have any user defined type
+        let mut i = 0;
+        while i + 1 < tokens.len() {
+            if (tokens[i].token_num == STRUCT || tokens[i].token_num == ENUM) && tokens[i + 1].token_num == IDENTIFIER {
+                let identifier = tokens[i + 1].token_string.clone();
+                if self.is_user_type(&identifier) {
+                    println!("Type with name '{}' already exists on line {}.", identifier, tokens[i + 1].line_num);
+                    self.error = true;
+                } else if self.user_types.len() >= (ANY - FIRST_USER_TYPE) as usize {
+                    println!("Too many user defined types on line {}.", tokens[i + 1].line_num);
+                    self.error = true;
+                } else if tokens[i].token_num == STRUCT {
+                    let new_struct = Struct {
+                        type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+                        field_names: Vec::new(),
+                        field_types: Vec::new(),
+                        field_securities: Vec::new(),
+                    };
+                    self.struct_data.insert(identifier.clone(), new_struct);
+                    self.user_types.push(identifier);
+                } else {
+                    let new_enum = Enum {
+                        type_num: FIRST_USER_TYPE + self.user_types.len() as u8,
+                        variant_names: Vec::new(),
+                        payload_types: Vec::new(),
+                        payload_securities: Vec::new(),
+                    };
+                    self.enum_data.insert(identifier.clone(), new_enum);
+                    self.user_types.push(identifier);
+                }
+            }
+            i += 1;
         }
-    }
-
-    fn comma(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != COMMA {
-            println!("Expected ',' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+        while !self.is_last_token() {
+            if tokens[self.current_token_num].token_num == STRUCT {
+                self.struct_dec(tokens);
+            } else if tokens[self.current_token_num].token_num == ENUM {
+                self.enum_dec(tokens);
+            } else {
+                self.consume_token();
+            }
         }
+        self.current_token_num = 0;
     }
 
-    fn return_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != RETURN {
-            println!("Expected 'return', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+    fn struct_dec(&mut self, tokens: &[lexer::Token]) {
+        self.struct_keyword(tokens);
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        self.identifier(tokens);
+        self.left_curley(tokens);
+        let mut field_names: Vec<String> = Vec::new();
+        let mut field_types: Vec<u8> = Vec::new();
+        let mut field_securities: Vec<i64> = Vec::new();
+        while tokens[self.current_token_num].token_num != RIGHT_CURLEY && !self.is_last_token() {
+            let field_name = tokens[self.current_token_num].token_string.clone();
+            if field_names.contains(&field_name) {
+                println!("Field '{}' already exists in struct '{}' on line {}.", field_name, identifier, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.identifier(tokens);
+            let field_type = self.parse_type(tokens, true);
+            field_types.push(field_type);
+            self.colon(tokens);
+            let mut security_level: i64 = 0;
+            if let Some(x) = self.security_level(tokens) {
+                security_level = x;
+            }
+            if security_level > 100 {
+                println!("'{}' exceeds the maximum security of 100 on line {}.", security_level, tokens[self.current_token_num].line_num);
+                self.error = true;
+            } else if security_level < 0 {
+                println!("'{}' is below the lowest security of 0 on line {}.", security_level, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.integer(tokens);
+            field_names.push(field_name);
+            field_securities.push(security_level);
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.comma(tokens);
+        }
+        self.right_curley(tokens);
+        if let Some(x) = self.struct_data.get_mut(&identifier) {
+            if x.field_names.is_empty() {
+                x.field_names = field_names;
+                x.field_types = field_types;
+                x.field_securities = field_securities;
+            }
         }
     }
 
-    fn if_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != IF {
-            println!("Expected 'if', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+    fn enum_dec(&mut self, tokens: &[lexer::Token]) {
+        self.enum_keyword(tokens);
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        self.identifier(tokens);
+        self.left_curley(tokens);
+        let mut variant_names: Vec<String> = Vec::new();
+        let mut payload_types: Vec<Vec<u8>> = Vec::new();
+        let mut payload_securities: Vec<Vec<i64>> = Vec::new();
+        while tokens[self.current_token_num].token_num != RIGHT_CURLEY && !self.is_last_token() {
+            let variant_name = tokens[self.current_token_num].token_string.clone();
+            if variant_names.contains(&variant_name) {
+                println!("Variant '{}' already exists in enum '{}' on line {}.", variant_name, identifier, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.identifier(tokens);
+            let mut types: Vec<u8> = Vec::new();
+            let mut securities: Vec<i64> = Vec::new();
+            // Variant(type:security, ...)
+            if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+                self.left_parenthesis(tokens);
+                loop {
+                    let payload_type = self.parse_type(tokens, true);
+                    types.push(payload_type);
+                    self.colon(tokens);
+                    let mut security_level: i64 = 0;
+                    if let Some(x) = self.security_level(tokens) {
+                        security_level = x;
+                    }
+                    if !(0..=100).contains(&security_level) {
+                        println!("'{}' is not a security level between 0 and 100 on line {}.", security_level, tokens[self.current_token_num].line_num);
+                        self.error = true;
+                    }
+                    self.integer(tokens);
+                    securities.push(security_level);
+                    if tokens[self.current_token_num].token_num != COMMA {
+                        break;
+                    }
+                    self.comma(tokens);
+                }
+                self.right_parenthesis(tokens);
+            }
+            variant_names.push(variant_name);
+            payload_types.push(types);
+            payload_securities.push(securities);
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.comma(tokens);
+        }
+        self.right_curley(tokens);
+        if let Some(x) = self.enum_data.get_mut(&identifier) {
+            if x.variant_names.is_empty() {
+                x.variant_names = variant_names;
+                x.payload_types = payload_types;
+                x.payload_securities = payload_securities;
+            }
         }
     }
 
-    fn else_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != ELSE {
-            println!("Expected 'else', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
+    fn index_functions(&mut self, tokens: &[lexer::Token]) {
+        let mut depth = 0;
+        while !self.is_last_token() {
+            // function types and function literals also start with fn
+            let token_num = tokens[self.current_token_num].token_num;
+            let is_declaration = token_num == FN && depth == 0 && (self.current_token_num == 0
+                || tokens[self.current_token_num - 1].token_num == RIGHT_CURLEY || tokens[self.current_token_num - 1].token_num == SEMI_COLON
+                || tokens[self.current_token_num - 1].token_num == CHECKED);
+            if !is_declaration {
+                if token_num == LEFT_CURLEY {
+                    depth += 1;
+                } else if token_num == RIGHT_CURLEY {
+                    depth -= 1;
+                }
+                self.consume_token();
+                continue;
+            }
+            self.index_function(tokens, String::new());
         }
     }
 
-    fn while_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != WHILE {
-            println!("Expected 'while', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
+    // Adds the signature of the function at the current token. A generic function is only recorded, its
+    // instances are added with their type parameters bound and the instance name.
+    fn index_function(&mut self, tokens: &[lexer::Token], instance_name: String) {
+        let start = self.current_token_num;
+        let fn_type: u8;
+        let mut identifier: String;
+        let mut security: i64 = 0;
+        let mut num_args: i64 = 0;
+        let mut arg_types = Vec::new();
+        let mut arg_securities = Vec::new();
+        let mut arg_pass_by = Vec::new();
+
+        // fn
+        self.consume_token();
+
+        if tokens[self.current_token_num].token_num != VOID {
+            fn_type = self.parse_type(tokens, false);
+            // colon
+            self.consume_token();
+            if let Some(x) = self.security_level(tokens) {
+                security = x;
+            }
+            // security
             self.consume_token();
         } else {
+            fn_type = VOID;
+            // type
             self.consume_token();
         }
-    }
-
-    fn break_keyword(&mut self, tokens: &Vec<lexer::Token>) {
-        if tokens[self.current_token_num].token_num != BREAK {
-            println!("Expected 'break', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+        identifier = tokens[self.current_token_num].token_string.clone();
+        if self.sl_data.contains_key(&identifier) {
+            println!("Function name '{}' cannot be the same as a standard library function on line {}.", identifier, tokens[self.current_token_num].line_num);
             self.error = true;
-            self.consume_token();
-        } else {
-            self.consume_token();
         }
-    }
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-    // Expression Code
-    // ---------------------------------------------------------------------------------------------------------------------------------------------------------------------
-
-    fn drain_expression_stack(&mut self, tokens: &Vec<lexer::Token>, expression_type: u8, expression_stack: &mut Vec<u8>) {
-        let or = 1;
-            let and = 2;
-            let equal_equal = 3;
-            let not_equal = 4;
-            let less = 5;
-            let greater = 6;
-            let less_equal = 7;
-            let greater_equal = 8;
-            let add = 9;
-            let sub = 10;
-            let mul = 11;
-            let div = 12;
-        while expression_stack.is_empty() == false {
-            if expression_stack[expression_stack.len() - 1] == div {
-                if expression_type == INT {
-                    self.code.push(I_DIV);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_DIV);
-                } else {
-                    println!("Type does not support division on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            } else if expression_stack[expression_stack.len() - 1] == mul {
-                if expression_type == INT {
-                    self.code.push(I_MUL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_MUL);
-                } else {
-                    println!("Type does not support multiplication on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            } else if expression_stack[expression_stack.len() - 1] == sub {
-                if expression_type == INT {
-                    self.code.push(I_SUB);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_SUB);
-                } else {
-                    println!("Type does not support subtraction on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            } else if expression_stack[expression_stack.len() - 1] == add {
-                if expression_type == INT {
-                    self.code.push(I_ADD);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_ADD);
-                } else if expression_type == STRING {
-                    self.code.push(S_ADD);
-                } else {
-                    println!("Type does not support addition on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            } else if expression_stack[expression_stack.len() - 1] == greater_equal {
-                if expression_type == INT {
-                    self.code.push(I_GREATER_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_GREATER_EQUAL);
-                } else {
-                    println!("Type does not support greater than or equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-            } else if expression_stack[expression_stack.len() - 1] == less_equal {
-                if expression_type == INT {
-                    self.code.push(I_LESS_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_LESS_EQUAL);
-                } else {
-                    println!("Type does not support less than or equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+        // identifier
+        self.consume_token();
+        if tokens[self.current_token_num].token_num == LESS {
+            // < type parameters >
+            let mut type_params: Vec<String> = Vec::new();
+            self.consume_token();
+            while tokens[self.current_token_num].token_num == IDENTIFIER {
+                type_params.push(tokens[self.current_token_num].token_string.clone());
+                self.consume_token();
+                if tokens[self.current_token_num].token_num == COMMA {
+                    self.consume_token();
                 }
-            } else if expression_stack[expression_stack.len() - 1] == greater {
-                if expression_type == INT {
-                    self.code.push(I_GREATER);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_GREATER);
-                } else {
-                    println!("Type does not support greater than comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+            }
+            // greater
+            self.consume_token();
+            if instance_name.is_empty() {
+                // the arguments of every instance are passed by value
+                let mut i = self.current_token_num;
+                while i < tokens.len() && tokens[i].token_num != RIGHT_PARENTHESIS {
+                    if tokens[i].token_num == REF {
+                        println!("Generic function '{}' cannot take arguments by reference on line {}.", identifier, tokens[i].line_num);
+                        self.error = true;
+                    }
+                    i += 1;
                 }
-            } else if expression_stack[expression_stack.len() - 1] == less {
-                if expression_type == INT {
-                    self.code.push(I_LESS);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_LESS);
-                } else {
-                    println!("Type does not support less than comparison on line {}.", tokens[self.current_token_num].line_num);
+                if self.generic_data.contains_key(&identifier) {
+                    println!("Function with name '{}' already exists on line {}.", identifier, tokens[start].line_num);
                     self.error = true;
                 }
-            } else if expression_stack[expression_stack.len() - 1] == equal_equal {
-                if expression_type == INT {
-                    self.code.push(I_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_EQUAL);
-                } else if expression_type == STRING {
-                    self.code.push(S_EQUAL);
-                } else {
-                    println!("Type does not support equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+                let generic = Generic {
+                    start,
+                    type_params,
+                };
+                self.generic_data.insert(identifier, generic);
+                return;
+            }
+            identifier = instance_name.clone();
+        }
+        // left parenthesis
+        self.consume_token();
+
+        if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+            loop {
+                let mut var_security: i64 = 0;
+                num_args += 1;
+
+                // identifier
+                self.consume_token();
+                arg_pass_by.push(self.pass_by(tokens, false));
+                let arg_type = self.parse_type(tokens, false);
+                arg_types.push(arg_type);
+                // colon
+                self.consume_token();
+                if let Some(x) = self.security_level(tokens) {
+                    var_security = x;
                 }
-            } else if expression_stack[expression_stack.len() - 1] == not_equal {
-                if expression_type == INT {
-                    self.code.push(I_NOT_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_NOT_EQUAL);
-                } else if expression_type == STRING {
-                    self.code.push(S_NOT_EQUAL);
+                arg_securities.push(var_security);
+                // security
+                self.consume_token();
+
+                if tokens[self.current_token_num].token_num != COMMA {
+                    break;
                 } else {
-                    println!("Type does not support not equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+                    self.consume_token();
                 }
-            } else if expression_stack[expression_stack.len() - 1] == and {
-                self.code.push(OP_AND);
-            } else if expression_stack[expression_stack.len() - 1] == or {
-                self.code.push(OP_OR);
             }
-            expression_stack.pop();
         }
-    }
 
-    fn expression(&mut self, tokens: &Vec<lexer::Token>, expression_type: u8, variable_security: i64) {
-        let mut expression_stack: Vec<u8> = Vec::new();
-        self.term(tokens, &mut expression_stack, expression_type, variable_security);
-        self.drain_expression_stack(tokens, expression_type, &mut expression_stack);
+        // right parenthesis
+        self.consume_token();
+
+        // the real location is set once the function body is reached in fn_dec
+        let new_fn = Function {
+            mem_location: 0,
+            fn_type,
+            security,
+            num_args,
+            arg_types,
+            arg_securities,
+            arg_pass_by,
+            times_parsed: 0,
+            is_extern: self.is_extern() && instance_name.is_empty(),
+        };
+
+        self.fn_data.insert(identifier, new_fn);
     }
 
-    fn get_precedence(&mut self, current_token_num: u8) -> u8 {
-        let or = 1;
-        let and = 2;
-        let equal_equal = 3;
-        let not_equal = 4;
-        let less = 5;
-        let greater = 6;
-        let less_equal = 7;
-        let greater_equal = 8;
-        let add = 9;
-        let sub = 10;
-        let mul = 11;
-        let div = 12;
-        let precedence: u8;
-        if current_token_num == or {
-            precedence = 1;
-        } else if current_token_num == and {
-            precedence = 2;
-        } else if current_token_num == equal_equal {
-            precedence = 3;
-        } else if current_token_num == not_equal {
-            precedence = 3;
-        } else if current_token_num == less {
-            precedence = 4;
-        } else if current_token_num == greater {
-            precedence = 4;
-        } else if current_token_num == less_equal {
-            precedence = 4;
-        } else if current_token_num == greater_equal {
-            precedence = 4;
-        } else if current_token_num == add {
-            precedence = 5;
-        } else if current_token_num == sub {
-            precedence = 5;
-        } else if current_token_num == mul {
-            precedence = 6;
-        } else if current_token_num == div {
-            precedence = 6;
+    pub fn parse(&mut self, tokens: &[lexer::Token]) -> bool {
+        self.index_user_types(tokens);
+        self.index_functions(tokens);
+        self.current_token_num = 0;
+        self.index_globals(tokens);
+        self.init_len = self.code.len();
+        if self.object_mode {
+            // the linker calls main after the statics of every object are initialized
+        } else if self.fn_data.contains_key("main") {
+            self.code.push(CALL);
+            self.fn_calls.push(("main".to_string(), self.code.len()));
+            self.code.push(0);
+            // main always has 0 arguments
+            self.code.push(0);
         } else {
-            precedence = 0;
-        }
-        precedence
-    }
-
-    fn shunting_yard(&mut self, tokens: &Vec<lexer::Token>, expression_stack: &mut Vec<u8>, expression_type: u8, value: u8) {
-        let or = 1;
-        let and = 2;
-        let equal_equal = 3;
-        let not_equal = 4;
-        let less = 5;
-        let greater = 6;
-        let less_equal = 7;
-        let greater_equal = 8;
-        let add = 9;
-        let sub = 10;
-        let mul = 11;
-        let div = 12;
-        while expression_stack.is_empty() == false && self.get_precedence(value) <= self.get_precedence(expression_stack[expression_stack.len() - 1]) {
-            if expression_stack[expression_stack.len() - 1] == div {
-                if expression_type == INT {
-                    self.code.push(I_DIV);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_DIV);
-                } else {
-                    println!("Type does not support division on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == mul {
-                if expression_type == INT {
-                    self.code.push(I_MUL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_MUL);
-                } else {
-                    println!("Type does not support multiplication on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == sub {
-                if expression_type == INT {
-                    self.code.push(I_SUB);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_SUB);
-                } else {
-                    println!("Type does not support subtraction on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == add {
-                if expression_type == INT {
-                    self.code.push(I_ADD);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_ADD);
-                } else if expression_type == STRING {
-                    self.code.push(S_ADD);
-                } else {
-                    println!("Type does not support addition on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == greater_equal {
-                if expression_type == INT {
-                    self.code.push(I_GREATER_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_GREATER_EQUAL);
-                } else {
-                    println!("Type does not support greater than or equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == less_equal {
-                if expression_type == INT {
-                    self.code.push(I_LESS_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_LESS_EQUAL);
-                } else if expression_type == STRING {
-                    println!("Type does not support less than or equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == greater {
-                if expression_type == INT {
-                    self.code.push(I_GREATER);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_GREATER);
-                } else if expression_type == STRING {
-                    println!("Type does not support greater than comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
-                }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == less {
-                if expression_type == INT {
-                    self.code.push(I_LESS);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_LESS);
-                } else if expression_type == STRING {
-                    println!("Type does not support less than comparison on line {}.", tokens[self.current_token_num].line_num);
+            println!("No main function found in program.");
+            self.error = true;
+        }
+        self.current_token_num = 0;
+        while !self.is_last_token() {
+            if tokens[self.current_token_num].token_num == CHECKED {
+                // read by fn_dec
+                self.consume_token();
+                if tokens[self.current_token_num].token_num != FN {
+                    println!("Expected function after 'checked' on line {}.", tokens[self.current_token_num].line_num);
                     self.error = true;
                 }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == equal_equal {
-                if expression_type == INT {
-                    self.code.push(I_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_EQUAL);
-                } else if expression_type == STRING {
-                    self.code.push(S_EQUAL);
-                } else {
-                    println!("Type does not support equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+            } else if tokens[self.current_token_num].token_num == STRUCT || tokens[self.current_token_num].token_num == ENUM {
+                // already parsed by index_user_types
+                while tokens[self.current_token_num].token_num != RIGHT_CURLEY && !self.is_last_token() {
+                    self.consume_token();
                 }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == not_equal {
-                if expression_type == INT {
-                    self.code.push(I_NOT_EQUAL);
-                } else if expression_type == FLOAT {
-                    self.code.push(F_NOT_EQUAL);
-                } else if expression_type == STRING {
-                    self.code.push(S_NOT_EQUAL);
-                } else {
-                    println!("Type does not support not equal comparison on line {}.", tokens[self.current_token_num].line_num);
-                    self.error = true;
+                self.consume_token();
+            } else if tokens[self.current_token_num].token_num == CONST || tokens[self.current_token_num].token_num == STATIC {
+                // already parsed by index_globals
+                while tokens[self.current_token_num].token_num != SEMI_COLON && !self.is_last_token() {
+                    self.consume_token();
                 }
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == and {
-                self.code.push(OP_AND);
-                expression_stack.pop();
-            } else if expression_stack[expression_stack.len() - 1] == or {
-                self.code.push(OP_OR);
-                expression_stack.pop();
+                self.consume_token();
+            } else if self.is_extern() {
+                // compiled into another object, only the signature is needed
+                self.skip_function(tokens);
+            } else if self.generic_data.values().any(|x| x.start == self.current_token_num) {
+                // compiled for each instance below
+                self.skip_function(tokens);
+            } else {
+                self.fn_dec(tokens);
             }
         }
-        expression_stack.push(value);
+        // instances can call generic functions with new types, which adds more instances
+        while let Some(instance) = self.instances.pop() {
+            self.current_token_num = instance.start;
+            self.type_params = instance.bindings;
+            self.level_params = instance.levels;
+            self.instance_name = instance.name;
+            let error = self.error;
+            self.error = false;
+            self.fn_dec(tokens);
+            if self.error {
+                println!("Errors in '{}' called on line {}.", self.instance_name, instance.line_num);
+            }
+            self.error = self.error || error;
+        }
+        self.type_params.clear();
+        self.level_params.clear();
+        self.link_fn_calls();
+        self.error
     }
 
-    fn change_expression_type(&mut self, tokens: &Vec<lexer::Token>) -> u8 {
-        let mut expression_type: u8 = INT;
-        let mut current_token_num = self.current_token_num;
-        while tokens[current_token_num].token_num == LEFT_PARENTHESIS && current_token_num < tokens.len() {
-            current_token_num += 1;
-        }
-        if tokens[current_token_num].token_num == INT {
-            expression_type = INT;
-        } else if tokens[current_token_num].token_num == FLOAT {
-            expression_type = FLOAT;
-        } else if tokens[current_token_num].token_num == IDENTIFIER {
-            if current_token_num + 1 < tokens.len() {
-                // identifier is a function
-                if tokens[current_token_num + 1].token_num == LEFT_PARENTHESIS {
-                    match self.sl_data.get(&tokens[self.current_token_num].token_string) {
-                        Some(x) => {
-                            if x.return_type == standard_library::INT {
-                                expression_type = INT;
-                            } else if x.return_type == standard_library::FLOAT {
-                                expression_type = FLOAT;
-                            } else if x.return_type == standard_library::STRING {
-                                expression_type = STRING;
-                            }
-                        },
-                        None => {},
-                    }
-                    match self.fn_data.get(&tokens[current_token_num].token_string) {
-                        Some(x) => {
-                            expression_type = x.fn_type;
-                        },
-                        None => {},
-                    }
-                    // identifier is a variable
-                } else if tokens[current_token_num + 1].token_num != LEFT_PARENTHESIS {
-                    match self.var_data.get(&tokens[current_token_num].token_string) {
-                        Some(x) => {
-                            expression_type = x.var_type;
-                        },
-                        None => {},
-                    }
+    fn skip_function(&mut self, tokens: &[lexer::Token]) {
+        let mut depth = 0;
+        while !self.is_last_token() {
+            if tokens[self.current_token_num].token_num == LEFT_CURLEY {
+                depth += 1;
+            } else if tokens[self.current_token_num].token_num == RIGHT_CURLEY {
+                depth -= 1;
+                if depth == 0 {
+                    break;
                 }
             }
+            self.consume_token();
         }
-        expression_type
+        self.consume_token();
     }
 
-    fn term(&mut self, tokens: &Vec<lexer::Token>, expression_stack: &mut Vec<u8>, mut expression_type: u8, variable_security: i64) {
-        let or = 1;
-        let and = 2;
-        let equal_equal = 3;
-        let not_equal = 4;
-        let less = 5;
-        let greater = 6;
-        let less_equal = 7;
-        let greater_equal = 8;
-        let add = 9;
-        let sub = 10;
-        let mul = 11;
-        let div = 12;
-        self.literal(tokens, expression_type, variable_security);
-        loop {
-            match tokens[self.current_token_num].token_num {
-                DIV => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(div);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, div);
-                    }
-                },
-                MUL => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(mul);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, mul);
-                    }
-                },
-                SUB => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(sub);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, sub);
-                    }
-                },
-                ADD => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(add);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, add);
-                    }
-                },
-                GREATER_EQUAL => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(greater_equal);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, greater_equal);
-                    }
-                },
-                LESS_EQUAL => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(less_equal);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, less_equal);
-                    }
-                },
-                GREATER => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(greater);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, greater);
-                    }
-                },
-                LESS => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(less);
+    // Globals are declared in order before any function is parsed, static initializers run before main.
+    fn index_globals(&mut self, tokens: &[lexer::Token]) {
+        self.stack_size = 0;
+        let mut alloc_location: usize = 0;
+        if tokens.iter().any(|x| x.token_num == STATIC) && !self.object_mode {
+            self.code.push(LOCAL_ALLOC);
+            alloc_location = self.code.len();
+            self.code.push(0);
+        }
+        // declarations inside a function body are reported when the body is parsed
+        let mut depth = 0;
+        while !self.is_last_token() {
+            let token_num = tokens[self.current_token_num].token_num;
+            if depth == 0 && (token_num == CONST || token_num == STATIC) {
+                self.global_dec(tokens);
+                continue;
+            }
+            if token_num == LEFT_CURLEY {
+                depth += 1;
+            } else if token_num == RIGHT_CURLEY {
+                depth -= 1;
+            }
+            self.consume_token();
+        }
+        if alloc_location != 0 {
+            // there is no frame yet so the hidden locals of the initializers start after three empty slots
+            self.code[alloc_location] = self.stack_size + 3;
+        }
+        self.init_locals = self.stack_size;
+        self.current_token_num = 0;
+    }
+
+    fn global_dec(&mut self, tokens: &[lexer::Token]) {
+        let is_const = tokens[self.current_token_num].token_num == CONST;
+        self.consume_token();
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        if self.global_data.contains_key(&identifier) || self.is_user_type(&identifier) {
+            println!("Identifier '{}' already declared on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        self.identifier(tokens);
+        let var_type = self.parse_type(tokens, true);
+        self.colon(tokens);
+        let mut security_level: i64 = 0;
+        if let Some(x) = self.security_level(tokens) {
+            security_level = x;
+        }
+        if !(0..=100).contains(&security_level) {
+            println!("'{}' is not a security level between 0 and 100 on line {}.", security_level, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        self.integer(tokens);
+        self.equal(tokens);
+
+        let is_extern = self.is_extern() && !is_const;
+        let start = self.code.len();
+        let mut value: Vec<i64> = Vec::new();
+        let mut mem_location: i64 = 0;
+        if is_extern {
+            // initialized by the object it belongs to
+            while tokens[self.current_token_num].token_num != SEMI_COLON && !self.is_last_token() {
+                self.consume_token();
+            }
+        } else {
+            self.expression(tokens, var_type, security_level);
+        }
+        if is_const {
+            if var_type != INT && var_type != FLOAT && var_type != STRING && var_type != BOOL && var_type != DECIMAL && var_type != ANY {
+                println!("Constant '{}' must be of type int, float, string, bool or decimal on line {}.", identifier, line_num);
+                self.error = true;
+            }
+            match fold_constant(&self.code, start) {
+                Ok(Constant::Number(x)) => {
+                    if var_type == FLOAT {
+                        value = vec![F_CONSTANT, x];
                     } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, less);
+                        value = vec![I_CONSTANT, x];
                     }
                 },
-                NOT_EQUAL => {
-                    self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(not_equal);
+                Ok(Constant::String(x)) => {
+                    value.push(S_CONSTANT);
+                    for c in x.chars() {
+                        value.push(c as u8 as i64);
+                    }
+                    value.push(0);
+                },
+                Err(FoldError::NotConstant) => {
+                    println!("Value of constant '{}' is not known at compile time on line {}.", identifier, line_num);
+                    self.error = true;
+                },
+                Err(FoldError::Overflow) => {
+                    println!("Value of constant '{}' overflows on line {}.", identifier, line_num);
+                    self.error = true;
+                },
+                Err(FoldError::DivisionByZero) => {
+                    println!("Value of constant '{}' divides by zero on line {}.", identifier, line_num);
+                    self.error = true;
+                },
+            }
+            self.code.truncate(start);
+            self.fn_calls.retain(|x| x.1 < start);
+        } else {
+            mem_location = self.num_globals;
+            self.num_globals += 1;
+            if !is_extern {
+                self.code.push(GLOBAL_STORE);
+                self.code.push(mem_location);
+            }
+        }
+        self.semi_colon(tokens);
+
+        let global = Global {
+            mem_location,
+            var_type,
+            security: security_level,
+            is_const,
+            value,
+            is_extern,
+        };
+        self.global_data.insert(identifier, global);
+    }
+
+    // Function locations are only known once every function has been parsed, so each CALL
+    // records where its address goes and is patched here.
+    fn link_fn_calls(&mut self) {
+        for (identifier, code_location) in self.fn_calls.iter() {
+            if let Some(x) = self.fn_data.get(identifier) {
+                self.code[*code_location] = x.mem_location;
+            }
+        }
+    }
+
+
+
+
+
+
+
+
+
+    // Parsing code
+    //--------------------------------------------------------------------------------------------------------------------------
+
+    fn fn_dec(&mut self, tokens: &[lexer::Token]) {
+        self.stack_size = 0;
+        self.is_checked = self.checked_mode || (self.current_token_num > 0 && tokens[self.current_token_num - 1].token_num == CHECKED);
+        self.fn_keyword(tokens);
+        let fn_type: u8;
+        if tokens[self.current_token_num].token_num != VOID {
+            fn_type = self.parse_type(tokens, true);
+            self.colon(tokens);
+            self.integer(tokens);
+        } else {
+            fn_type = VOID;
+            self.consume_token();
+        }
+        let mut identifier = tokens[self.current_token_num].token_string.clone();
+        if self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == LESS {
+            identifier = self.instance_name.clone();
+        }
+        self.current_fn_name = identifier.clone();
+        if self.current_fn_name == "main" {
+            // the exit code can be seen outside of the program
+            let security = match self.fn_data.get("main") {
+                Some(x) => x.security,
+                None => 0,
+            };
+            if fn_type != VOID && (fn_type != INT || security != 0) {
+                println!("Function 'main' must be of type void or int:0 on line {}.", tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+        }
+        if let Some(x) = self.fn_data.get_mut(&identifier) {
+            x.times_parsed += 1;
+            if x.times_parsed > 1 {
+                println!("Function with name '{}' already exists on line {}.", identifier, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+        }
+        self.identifier(tokens);
+        if tokens[self.current_token_num].token_num == LESS {
+            // the type parameters are already bound for this instance
+            while tokens[self.current_token_num].token_num != GREATER && !self.is_last_token() {
+                self.consume_token();
+            }
+            self.consume_token();
+        }
+        let mut main_args = false;
+        if self.current_fn_name == "main" {
+            // main() or main(args vec_string:security), the arguments are a local loaded from the VM
+            self.left_parenthesis(tokens);
+            if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                let var_name = tokens[self.current_token_num].token_string.clone();
+                let line_num = tokens[self.current_token_num].line_num;
+                self.identifier(tokens);
+                let var_type = self.parse_type(tokens, true);
+                self.colon(tokens);
+                let mut var_security: i64 = 0;
+                if let Some(x) = self.security_level(tokens) {
+                    var_security = x;
+                }
+                self.integer(tokens);
+                if var_type != VEC_STRING || tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                    println!("Function 'main' can only take the arguments of the program as a vec_string on line {}.", line_num);
+                    self.error = true;
+                    while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+                        self.consume_token();
+                    }
+                }
+                let variable = Variable {
+                    mem_location: 0,
+                    var_type: VEC_STRING,
+                    security: var_security,
+                    is_arg: false,
+                    arg_location: 0,
+                    scope: 0,
+                    function_name: identifier.clone(),
+                    pass_by: BY_VALUE,
+                };
+                self.var_data.insert(var_name, variable);
+                self.stack_size = 1;
+                main_args = true;
+            }
+            self.right_parenthesis(tokens);
+        } else {
+            self.left_parenthesis(tokens);
+            if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                let mut arg_location: i64 = 0;
+                if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                    loop {
+                        let var_name = tokens[self.current_token_num].token_string.clone();
+                        let mut var_security: i64 = 0;
+
+                        // identifier
+                        self.identifier(tokens);
+                        let pass_by = self.pass_by(tokens, true);
+                        let var_type = self.parse_type(tokens, true);
+                        // colon
+                        self.colon(tokens);
+                        if let Some(x) = self.security_level(tokens) {
+                            var_security = x;
+                        }
+                        // security
+                        self.integer(tokens);
+
+                        let variable = Variable {
+                            mem_location: 0,
+                            var_type,
+                            security: var_security, 
+                            is_arg: true,
+                            arg_location,
+                            scope: 0,
+                            function_name: identifier.clone(),
+                            pass_by,
+                        };
+
+                        arg_location += 1;
+                        self.var_data.insert(var_name, variable);
+
+                        if tokens[self.current_token_num].token_num != COMMA {
+                            break;
+                        } else {
+                            self.comma(tokens);
+                        }
+                    }
+                }
+            }
+            self.right_parenthesis(tokens);
+        }
+        if let Some(x) = self.fn_data.get_mut(&identifier) {
+            if x.times_parsed == 1 {
+                x.mem_location = self.code.len() as i64;
+            }
+        }
+        // every local of the function gets a slot up front, the count is known once the body is parsed
+        self.code.push(LOCAL_ALLOC);
+        let alloc_location = self.code.len();
+        self.code.push(0);
+        if main_args {
+            self.code.push(ARGS_LOAD);
+            self.code.push(LOCAL_STORE);
+            self.code.push(0);
+        }
+        self.block(tokens);
+        self.code[alloc_location] = self.stack_size;
+        let name = format!("Function '{}'", self.current_fn_name);
+        self.end_of_function(name);
+    }
+
+    // A void function returns at the end of its body, any other function must have returned on every path.
+    fn end_of_function(&mut self, name: String) {
+        if self.terminates {
+            return;
+        }
+        let fn_type = match self.fn_data.get(&self.current_fn_name) {
+            Some(x) => x.fn_type,
+            None => ANY,
+        };
+        if fn_type == VOID && self.current_fn_name == "main" {
+            self.code.push(HALT);
+        } else if fn_type == VOID {
+            self.code.push(RETURN_NON_VAL);
+        } else {
+            println!("{} is missing a return on the path through line {}.", name, self.open_line);
+            self.error = true;
+        }
+    }
+
+    fn block(&mut self, tokens: &[lexer::Token]) {
+        let mut open_line = tokens[self.current_token_num].line_num;
+        self.left_curley(tokens);
+        self.current_scope += 1;
+        let mut terminates = false;
+        let mut is_reported = false;
+        while !self.is_last_token() && tokens[self.current_token_num].token_num != RIGHT_CURLEY {
+            let line_num = tokens[self.current_token_num].line_num;
+            if terminates && !is_reported {
+                println!("Warning: unreachable code on line {}.", line_num);
+                is_reported = true;
+            }
+            self.terminates = false;
+            self.open_line = line_num;
+            match tokens[self.current_token_num].token_num {
+                LET => {
+                    self.var_dec(tokens);
+                    self.semi_colon(tokens);
+                },
+                IDENTIFIER => {
+                    if self.current_token_num + 1 < tokens.len() {
+                        if tokens[self.current_token_num + 1].token_num != LEFT_PARENTHESIS {
+                            self.var_def(tokens);
+                            self.semi_colon(tokens);
+                        } else if tokens[self.current_token_num + 1].token_num == LEFT_PARENTHESIS {
+                            match self.sl_data.get(&tokens[self.current_token_num].token_string) {
+                                Some(x) => {
+                                    if x.return_type != standard_library::VOID {
+                                        println!("Function not within an expression must be void on line {}.", tokens[self.current_token_num].line_num);
+                                        self.error = true;
+                                    }
+                                    self.sl_use(tokens, VOID, 100);
+                                    self.semi_colon(tokens);
+                                },
+                                None => {
+                                    if self.is_variable(&tokens[self.current_token_num].token_string) {
+                                        let line_num = tokens[self.current_token_num].line_num;
+                                        let (return_type, _) = self.indirect_call(tokens, 100);
+                                        if return_type != VOID && return_type != ANY {
+                                            println!("Function not within an expression must be void on line {}.", line_num);
+                                            self.error = true;
+                                        }
+                                    } else {
+                                        self.fn_call(tokens);
+                                    }
+                                    self.semi_colon(tokens);
+                                },
+                            }
+                        }
                     } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, not_equal);
+                        println!("Program suddenly ended on line {}.", tokens[self.current_token_num].line_num);
+                        self.error = true;
                     }
                 },
-                EQUAL_EQUAL => {
+                IF => {
+                    self.if_statement(tokens);
+                },
+                MATCH => {
+                    self.match_statement(tokens);
+                },
+                TRY => {
+                    self.try_statement(tokens);
+                },
+                THROW => {
+                    self.throw_statement(tokens);
+                    self.semi_colon(tokens);
+                },
+                WHILE => {
+                    self.while_statement(tokens, String::new());
+                },
+                FOR => {
+                    self.for_statement(tokens, String::new());
+                },
+                LABEL => {
+                    let label = tokens[self.current_token_num].token_string.clone();
                     self.consume_token();
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(equal_equal);
+                    self.colon(tokens);
+                    if tokens[self.current_token_num].token_num == WHILE {
+                        self.while_statement(tokens, label);
+                    } else if tokens[self.current_token_num].token_num == FOR {
+                        self.for_statement(tokens, label);
                     } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, equal_equal);
+                        println!("Expected loop after label {}, got '{}' on line {}.", label, tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                        self.error = true;
+                    }
+                },
+                BREAK => {
+                    self.break_statement(tokens);
+                    self.semi_colon(tokens);
+                },
+                CONTINUE => {
+                    self.continue_statement(tokens);
+                    self.semi_colon(tokens);
+                },
+                RETURN => {
+                    self.return_statement(tokens);
+                    self.semi_colon(tokens);
+                },
+                CONST | STATIC => {
+                    println!("Constants and statics must be declared outside of functions on line {}.", line_num);
+                    self.error = true;
+                    while tokens[self.current_token_num].token_num != SEMI_COLON && !self.is_last_token() {
+                        self.consume_token();
                     }
+                    self.semi_colon(tokens);
                 },
-                AND => {
+                _ => {
+                    println!("Beginning of unkown statement type on line {}.", tokens[self.current_token_num].line_num);
                     self.consume_token();
-                    self.drain_expression_stack(tokens, expression_type, expression_stack);
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(and);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, and);
+                    self.error = true;
+                },
+            }
+            if !terminates {
+                terminates = self.terminates;
+                open_line = self.open_line;
+            }
+        }
+        self.right_curley(tokens);
+        let mut keys: Vec<String> = Vec::new();
+        for (key, val) in self.var_data.iter() {
+            if val.function_name == self.current_fn_name && val.scope == self.current_scope {
+                keys.push(key.clone());
+            }
+        }
+        for key in keys {
+            self.var_data.remove(&key);
+        }
+        self.current_scope -= 1;
+        self.terminates = terminates;
+        self.open_line = open_line;
+    }
+
+    fn left_curley(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != LEFT_CURLEY {
+            println!("Expected '{}', got '{}' on line {}.", 123 as char, tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn right_curley(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != RIGHT_CURLEY {
+            println!("Expected '{}', got '{}' on line {}.", 125 as char, tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn fn_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != FN {
+            println!("Expected 'fn', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn semi_colon(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != SEMI_COLON {
+            println!("Expected ';', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn let_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != LET {
+            println!("Expected 'let', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn identifier(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != IDENTIFIER {
+            println!("Expected identifier, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn var_type(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != INT_TYPE &&
+            tokens[self.current_token_num].token_num != FLOAT_TYPE && 
+            tokens[self.current_token_num].token_num != STRING_TYPE && 
+            tokens[self.current_token_num].token_num != DECIMAL_TYPE &&
+            tokens[self.current_token_num].token_num != BIGINT_TYPE &&
+            tokens[self.current_token_num].token_num != VEC_INT && 
+            tokens[self.current_token_num].token_num != VEC_FLOAT && 
+            tokens[self.current_token_num].token_num != VEC_STRING &&
+            tokens[self.current_token_num].token_num != BOOL &&
+            tokens[self.current_token_num].token_num != FILE &&
+            !self.is_user_type(&tokens[self.current_token_num].token_string) &&
+            !self.type_params.contains_key(&tokens[self.current_token_num].token_string) {
+                println!("Expected type, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                self.error = true;
+                self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn colon(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != COLON {
+            println!("Expected ':', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    // The security level written at the current token. In an instance of a generic function a security level
+    // parameter gives the level it is bound to.
+    fn security_level(&self, tokens: &[lexer::Token]) -> Option<i64> {
+        let token = &tokens[self.current_token_num];
+        if token.token_num == INT {
+            return Some(token.token_string.parse::<i64>().expect("Failed to parse integer."));
+        }
+        if token.token_num == IDENTIFIER {
+            return self.level_params.get(&token.token_string).copied();
+        }
+        None
+    }
+
+    fn integer(&mut self, tokens: &[lexer::Token]) {
+        if self.security_level(tokens).is_none() {
+            println!("Expected integer, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn equal(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != EQUAL {
+            println!("Expected '=', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn left_parenthesis(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != LEFT_PARENTHESIS {
+            println!("Expected '(' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn right_parenthesis(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+            println!("Expected ')' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn comma(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != COMMA {
+            println!("Expected ',' got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn return_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != RETURN {
+            println!("Expected 'return', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn if_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != IF {
+            println!("Expected 'if', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn else_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != ELSE {
+            println!("Expected 'else', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn while_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != WHILE {
+            println!("Expected 'while', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn for_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != FOR {
+            println!("Expected 'for', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn in_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != IN {
+            println!("Expected 'in', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn continue_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != CONTINUE {
+            println!("Expected 'continue', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn struct_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != STRUCT {
+            println!("Expected 'struct', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn enum_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != ENUM {
+            println!("Expected 'enum', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn match_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != MATCH {
+            println!("Expected 'match', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn double_colon(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != DOUBLE_COLON {
+            println!("Expected '::', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn arrow(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != ARROW {
+            println!("Expected '=>', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+    fn break_keyword(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num != BREAK {
+            println!("Expected 'break', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+            self.consume_token();
+        } else {
+            self.consume_token();
+        }
+    }
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+    // Expression Code
+    // ---------------------------------------------------------------------------------------------------------------------------------------------------------------------
+
+    fn drain_expression_stack(&mut self, tokens: &[lexer::Token], expression_stack: &mut Vec<u8>, type_stack: &mut Vec<u8>) {
+        while !expression_stack.is_empty() {
+            let operator = expression_stack[expression_stack.len() - 1];
+            self.operator(tokens, operator, type_stack);
+            expression_stack.pop();
+        }
+    }
+
+    fn expression(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let line_num = tokens[self.current_token_num].line_num;
+        let (mut result_type, security) = self.or_expression(tokens, expression_type, variable_security);
+        if result_type == INT && (expression_type == FLOAT || expression_type == DECIMAL || expression_type == BIGINT) {
+            // ints are widened where a float, decimal or bigint is expected, other conversions can lose digits and need 'as'
+            self.code.push(USE);
+            self.code.push(int_conversion(expression_type));
+            result_type = expression_type;
+        } else if is_number(expression_type) && is_number(result_type) && expression_type != result_type {
+            println!("Cannot implicitly convert {} to {} on line {}, use 'as {}'.", self.type_name(result_type), self.type_name(expression_type), line_num, self.type_name(expression_type));
+            self.error = true;
+        } else if expression_type != ANY && result_type != ANY && result_type != expression_type {
+            println!("Type mismatch: expected {}, got {} on line {}.", self.type_name(expression_type), self.type_name(result_type), line_num);
+            self.error = true;
+        }
+        (result_type, security)
+    }
+
+    fn or_expression(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let (mut result_type, mut security) = self.and_expression(tokens, expression_type, variable_security);
+        if tokens[self.current_token_num].token_num != OR {
+            return (result_type, security);
+        }
+        // short circuit: the first operand that is true skips the rest
+        let mut true_jump_locations: Vec<usize> = Vec::new();
+        while tokens[self.current_token_num].token_num == OR {
+            self.logical_operand(tokens, result_type, "or");
+            self.code.push(JUMP_IF_TRUE);
+            true_jump_locations.push(self.code.len());
+            self.code.push(0);
+            self.consume_token();
+            let (operand_type, operand_security) = self.and_expression(tokens, BOOL, variable_security);
+            result_type = operand_type;
+            security = std::cmp::max(security, operand_security);
+        }
+        self.logical_operand(tokens, result_type, "or");
+        self.code.push(JUMP);
+        let end_location = self.code.len();
+        self.code.push(0);
+        for location in true_jump_locations {
+            self.code[location] = self.code.len() as i64;
+        }
+        self.code.push(I_CONSTANT);
+        self.code.push(1);
+        self.code[end_location] = self.code.len() as i64;
+        (BOOL, security)
+    }
+
+    fn and_expression(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let (mut result_type, mut security) = self.not_expression(tokens, expression_type, variable_security);
+        if tokens[self.current_token_num].token_num != AND {
+            return (result_type, security);
+        }
+        // short circuit: the first operand that is false skips the rest
+        let mut false_jump_locations: Vec<usize> = Vec::new();
+        while tokens[self.current_token_num].token_num == AND {
+            self.logical_operand(tokens, result_type, "and");
+            self.code.push(JUMP_IF_FALSE);
+            false_jump_locations.push(self.code.len());
+            self.code.push(0);
+            self.consume_token();
+            let (operand_type, operand_security) = self.not_expression(tokens, BOOL, variable_security);
+            result_type = operand_type;
+            security = std::cmp::max(security, operand_security);
+        }
+        self.logical_operand(tokens, result_type, "and");
+        self.code.push(JUMP);
+        let end_location = self.code.len();
+        self.code.push(0);
+        for location in false_jump_locations {
+            self.code[location] = self.code.len() as i64;
+        }
+        self.code.push(I_CONSTANT);
+        self.code.push(0);
+        self.code[end_location] = self.code.len() as i64;
+        (BOOL, security)
+    }
+
+    fn not_expression(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        if tokens[self.current_token_num].token_num != NOT {
+            return self.term(tokens, expression_type, variable_security);
+        }
+        self.consume_token();
+        let (operand_type, security) = self.not_expression(tokens, BOOL, variable_security);
+        self.logical_operand(tokens, operand_type, "not");
+        self.code.push(OP_NOT);
+        (BOOL, security)
+    }
+
+    fn logical_operand(&mut self, tokens: &[lexer::Token], operand_type: u8, operator_name: &str) {
+        if operand_type != BOOL && operand_type != ANY {
+            println!("Operator '{}' expects bool operands, got {} on line {}.", operator_name, self.type_name(operand_type), tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+    }
+
+    fn get_precedence(&mut self, current_token_num: u8) -> u8 {
+        match current_token_num {
+            EQUAL_EQUAL | NOT_EQUAL => 3,
+            LESS | GREATER | LESS_EQUAL | GREATER_EQUAL => 4,
+            BIT_OR => 5,
+            BIT_XOR => 6,
+            BIT_AND => 7,
+            SHIFT_LEFT | SHIFT_RIGHT => 8,
+            ADD | SUB => 9,
+            MUL | DIV | MOD => 10,
+            POW => 11,
+            _ => 0,
+        }
+    }
+
+    fn shunting_yard(&mut self, tokens: &[lexer::Token], expression_stack: &mut Vec<u8>, type_stack: &mut Vec<u8>, value: u8) {
+        while !expression_stack.is_empty() {
+            let precedence = self.get_precedence(value);
+            let top_precedence = self.get_precedence(expression_stack[expression_stack.len() - 1]);
+            // '**' is right associative
+            if precedence > top_precedence || (value == POW && precedence == top_precedence) {
+                break;
+            }
+            let operator = expression_stack[expression_stack.len() - 1];
+            self.operator(tokens, operator, type_stack);
+            expression_stack.pop();
+        }
+        expression_stack.push(value);
+    }
+
+    // Integer opcodes that can overflow trap instead in checked functions.
+    fn checked_opcode(&self, opcode: i64) -> i64 {
+        if !self.is_checked {
+            return opcode;
+        }
+        match opcode {
+            I_ADD => I_ADD_CHECKED,
+            I_SUB => I_SUB_CHECKED,
+            I_MUL => I_MUL_CHECKED,
+            I_DIV => I_DIV_CHECKED,
+            I_POW => I_POW_CHECKED,
+            I_NEG => I_NEG_CHECKED,
+            _ => opcode,
+        }
+    }
+
+    // Pops the operand types of a binary operator, emits the opcode for them and pushes the result type.
+    fn operator(&mut self, tokens: &[lexer::Token], operator: u8, type_stack: &mut Vec<u8>) {
+        let right_type = type_stack.pop().unwrap_or(ANY);
+        let left_type = type_stack.pop().unwrap_or(ANY);
+        if left_type == ANY || right_type == ANY {
+            type_stack.push(ANY);
+            return;
+        }
+        let operator_name = operator_name(operator);
+        let is_arithmetic = operator == ADD || operator == SUB || operator == MUL || operator == DIV || operator == MOD || operator == POW;
+        let is_comparison = operator == EQUAL_EQUAL || operator == NOT_EQUAL || operator == LESS || operator == GREATER
+            || operator == LESS_EQUAL || operator == GREATER_EQUAL;
+        if (is_arithmetic || is_comparison) && (left_type == FLOAT || left_type == DECIMAL || left_type == BIGINT) && right_type == INT {
+            self.code.push(USE);
+            self.code.push(int_conversion(left_type));
+            type_stack.push(left_type);
+            type_stack.push(left_type);
+            return self.operator(tokens, operator, type_stack);
+        }
+        if (is_arithmetic || is_comparison) && left_type == INT && (right_type == FLOAT || right_type == DECIMAL || right_type == BIGINT) {
+            // the int is below the right operand, which waits in a hidden local while it is converted
+            let location = self.stack_size;
+            self.stack_size += 1;
+            self.code.push(LOCAL_STORE);
+            self.code.push(location);
+            self.code.push(USE);
+            self.code.push(int_conversion(right_type));
+            self.code.push(LOCAL_LOAD);
+            self.code.push(location);
+            type_stack.push(right_type);
+            type_stack.push(right_type);
+            return self.operator(tokens, operator, type_stack);
+        }
+        if left_type != right_type {
+            println!("Type mismatch: cannot apply '{}' to {} and {} on line {}.", operator_name, self.type_name(left_type), self.type_name(right_type), tokens[self.current_token_num].line_num);
+            self.error = true;
+            type_stack.push(ANY);
+            return;
+        }
+        let opcode: i64 = match (operator, left_type) {
+            (DIV, INT) => I_DIV,
+            (DIV, FLOAT) => F_DIV,
+            (MUL, INT) => I_MUL,
+            (MUL, FLOAT) => F_MUL,
+            (SUB, INT) => I_SUB,
+            (SUB, FLOAT) => F_SUB,
+            (ADD, INT) => I_ADD,
+            (ADD, FLOAT) => F_ADD,
+            (ADD, STRING) => S_ADD,
+            (ADD, DECIMAL) => D_ADD,
+            (SUB, DECIMAL) => D_SUB,
+            (MUL, DECIMAL) => D_MUL,
+            (DIV, DECIMAL) => D_DIV,
+            (ADD, BIGINT) => B_ADD,
+            (SUB, BIGINT) => B_SUB,
+            (MUL, BIGINT) => B_MUL,
+            (DIV, BIGINT) => B_DIV,
+            (MOD, BIGINT) => B_MOD,
+            (POW, BIGINT) => B_POW,
+            (MOD, INT) => I_MOD,
+            (MOD, FLOAT) => F_MOD,
+            (POW, INT) => I_POW,
+            (POW, FLOAT) => F_POW,
+            (BIT_AND, INT) | (BIT_AND, BOOL) => I_BIT_AND,
+            (BIT_OR, INT) | (BIT_OR, BOOL) => I_BIT_OR,
+            (BIT_XOR, INT) | (BIT_XOR, BOOL) => I_BIT_XOR,
+            (SHIFT_LEFT, INT) => I_SHIFT_LEFT,
+            (SHIFT_RIGHT, INT) => I_SHIFT_RIGHT,
+            (GREATER_EQUAL, INT) | (GREATER_EQUAL, DECIMAL) | (GREATER_EQUAL, BIGINT) => I_GREATER_EQUAL,
+            (GREATER_EQUAL, FLOAT) => F_GREATER_EQUAL,
+            (LESS_EQUAL, INT) | (LESS_EQUAL, DECIMAL) | (LESS_EQUAL, BIGINT) => I_LESS_EQUAL,
+            (LESS_EQUAL, FLOAT) => F_LESS_EQUAL,
+            (GREATER, INT) | (GREATER, DECIMAL) | (GREATER, BIGINT) => I_GREATER,
+            (GREATER, FLOAT) => F_GREATER,
+            (LESS, INT) | (LESS, DECIMAL) | (LESS, BIGINT) => I_LESS,
+            (LESS, FLOAT) => F_LESS,
+            (EQUAL_EQUAL, INT) | (EQUAL_EQUAL, BOOL) | (EQUAL_EQUAL, DECIMAL) | (EQUAL_EQUAL, BIGINT) => I_EQUAL,
+            (EQUAL_EQUAL, FLOAT) => F_EQUAL,
+            (EQUAL_EQUAL, STRING) => S_EQUAL,
+            (NOT_EQUAL, INT) | (NOT_EQUAL, BOOL) | (NOT_EQUAL, DECIMAL) | (NOT_EQUAL, BIGINT) => I_NOT_EQUAL,
+            (NOT_EQUAL, FLOAT) => F_NOT_EQUAL,
+            (NOT_EQUAL, STRING) => S_NOT_EQUAL,
+            _ => 0,
+        };
+        if opcode == 0 {
+            println!("Type {} does not support '{}' on line {}.", self.type_name(left_type), operator_name, tokens[self.current_token_num].line_num);
+            self.error = true;
+            type_stack.push(ANY);
+            return;
+        }
+        if is_comparison && left_type == BIGINT {
+            // bigints are compared in constant time to -1, 0 or 1 which is then compared to zero
+            self.code.push(B_COMPARE);
+            self.code.push(I_CONSTANT);
+            self.code.push(0);
+        }
+        self.code.push(self.checked_opcode(opcode));
+        if is_comparison {
+            type_stack.push(BOOL);
+        } else {
+            type_stack.push(left_type);
+        }
+    }
+
+    fn term(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let mut expression_stack: Vec<u8> = Vec::new();
+        let mut type_stack: Vec<u8> = Vec::new();
+        let (operand_type, mut security) = self.literal(tokens, expression_type, variable_security);
+        let operand_type = self.propagate(tokens, operand_type, security);
+        let operand_type = self.cast(tokens, operand_type);
+        type_stack.push(operand_type);
+        while self.get_precedence(tokens[self.current_token_num].token_num) != 0 {
+            let operator = tokens[self.current_token_num].token_num;
+            self.consume_token();
+            self.shunting_yard(tokens, &mut expression_stack, &mut type_stack, operator);
+            // the right hand side is expected to have the same type as the left hand side
+            let operand_hint = type_stack[type_stack.len() - 1];
+            let (operand_type, operand_security) = self.literal(tokens, operand_hint, variable_security);
+            let operand_type = self.propagate(tokens, operand_type, operand_security);
+            let operand_type = self.cast(tokens, operand_type);
+            type_stack.push(operand_type);
+            security = std::cmp::max(security, operand_security);
+        }
+        self.drain_expression_stack(tokens, &mut expression_stack, &mut type_stack);
+        (type_stack.pop().unwrap_or(ANY), security)
+    }
+
+    // operand as int, operand as float: converts between numbers, a cast to the same type does nothing.
+    fn cast(&mut self, tokens: &[lexer::Token], operand_type: u8) -> u8 {
+        let mut result_type = operand_type;
+        while tokens[self.current_token_num].token_num == AS {
+            let line_num = tokens[self.current_token_num].line_num;
+            self.consume_token();
+            let cast_type = self.parse_type(tokens, true);
+            if result_type == ANY || cast_type == ANY {
+                result_type = ANY;
+                continue;
+            }
+            match (result_type, cast_type) {
+                (INT, FLOAT) => {
+                    self.code.push(USE);
+                    self.code.push(standard_library::INT_TO_FLOAT);
+                },
+                (FLOAT, INT) => {
+                    // truncates toward zero
+                    self.code.push(USE);
+                    self.code.push(standard_library::FLOAT_TO_INT);
+                },
+                (INT, DECIMAL) => {
+                    self.code.push(USE);
+                    self.code.push(standard_library::INT_TO_DECIMAL);
+                },
+                (DECIMAL, INT) => {
+                    // truncates toward zero
+                    self.code.push(USE);
+                    self.code.push(standard_library::DECIMAL_TO_INT);
+                },
+                (FLOAT, DECIMAL) => {
+                    // rounds half to even
+                    self.code.push(USE);
+                    self.code.push(standard_library::FLOAT_TO_DECIMAL);
+                },
+                (DECIMAL, FLOAT) => {
+                    self.code.push(USE);
+                    self.code.push(standard_library::DECIMAL_TO_FLOAT);
+                },
+                (INT, BIGINT) => {
+                    self.code.push(USE);
+                    self.code.push(standard_library::INT_TO_BIGINT);
+                },
+                (BIGINT, INT) => {
+                    // stops the program if the value does not fit
+                    self.code.push(USE);
+                    self.code.push(standard_library::BIGINT_TO_INT);
+                },
+                // bools are already 0 or 1
+                (BOOL, INT) => {},
+                _ => {
+                    if result_type != cast_type {
+                        println!("Cannot cast {} to {} on line {}.", self.type_name(result_type), self.type_name(cast_type), line_num);
+                        self.error = true;
+                    }
+                },
+            }
+            result_type = cast_type;
+        }
+        result_type
+    }
+
+    fn literal(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        match tokens[self.current_token_num].token_num {
+            INT => {
+                self.code.push(I_CONSTANT);
+                self.code.push(tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer."));
+                self.consume_token();
+                (INT, 0)
+            },
+            FLOAT => {
+                self.code.push(F_CONSTANT);
+                self.code.push(i64::from_be_bytes(f64::to_be_bytes(tokens[self.current_token_num].token_string.parse::<f64>().expect("Failed to parse float."))));
+                self.consume_token();
+                (FLOAT, 0)
+            },
+            STRING => {
+                self.string_constant(tokens);
+                (STRING, 0)
+            },
+            DECIMAL => {
+                self.code.push(I_CONSTANT);
+                match decimal_literal(&tokens[self.current_token_num].token_string) {
+                    Some(x) => self.code.push(x),
+                    None => {
+                        println!("Decimal '{}' must fit in an int with at most 4 decimal places on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                        self.error = true;
+                        self.code.push(0);
+                    },
+                }
+                self.consume_token();
+                (DECIMAL, 0)
+            },
+            BIGINT => {
+                // the digits are kept as a string and parsed when the literal is reached
+                let token = &tokens[self.current_token_num];
+                self.push_string(&lexer::Token::new(&token.token_string.trim_end_matches('n').to_string(), token.line_num));
+                self.code.push(USE);
+                self.code.push(standard_library::STRING_TO_BIGINT);
+                self.consume_token();
+                (BIGINT, 0)
+            },
+            TRUE => {
+                self.code.push(I_CONSTANT);
+                self.code.push(1);
+                self.consume_token();
+                (BOOL, 0)
+            },
+            FALSE => {
+                self.code.push(I_CONSTANT);
+                self.code.push(0);
+                self.consume_token();
+                (BOOL, 0)
+            },
+            SUB => {
+                let line_num = tokens[self.current_token_num].line_num;
+                self.consume_token();
+                let (operand_type, security) = self.literal(tokens, expression_type, variable_security);
+                if operand_type == INT {
+                    self.code.push(self.checked_opcode(I_NEG));
+                } else if operand_type == FLOAT {
+                    self.code.push(F_NEG);
+                } else if operand_type == DECIMAL {
+                    self.code.push(D_NEG);
+                } else if operand_type == BIGINT {
+                    self.code.push(B_NEG);
+                } else if operand_type != ANY {
+                    println!("Type {} does not support unary '-' on line {}.", self.type_name(operand_type), line_num);
+                    self.error = true;
+                }
+                (operand_type, security)
+            },
+            IDENTIFIER => {
+                let (operand_type, security);
+                if self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == LEFT_PARENTHESIS {
+                    // identifier is a function
+                    if self.sl_data.contains_key(&tokens[self.current_token_num].token_string) {
+                        (operand_type, security) = self.sl_use(tokens, expression_type, variable_security);
+                    } else if self.is_variable(&tokens[self.current_token_num].token_string) {
+                        (operand_type, security) = self.indirect_call(tokens, variable_security);
+                    } else {
+                        (operand_type, security) = self.identifier_function(tokens, variable_security);
+                    }
+                } else if self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == LEFT_CURLEY
+                    && self.struct_data.contains_key(&tokens[self.current_token_num].token_string) {
+                    // identifier is a struct literal
+                    (operand_type, security) = self.struct_literal(tokens);
+                } else if self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == DOUBLE_COLON {
+                    // identifier is an enum
+                    let name = &tokens[self.current_token_num].token_string;
+                    if name == "Option" || name == "Result" {
+                        (operand_type, security) = self.wrapper_value(tokens, expression_type, variable_security);
+                    } else {
+                        (operand_type, security) = self.enum_value(tokens, variable_security);
+                    }
+                } else {
+                    // identifier is a variable
+                    (operand_type, security) = self.identifier_variable(tokens, variable_security);
+                }
+                self.field_access(tokens, operand_type, security, variable_security)
+            },
+            FN => {
+                self.closure(tokens, variable_security)
+            },
+            LEFT_PARENTHESIS => {
+                if self.is_tuple_literal(tokens) {
+                    return self.tuple_literal(tokens, expression_type, variable_security);
+                }
+                self.consume_token();
+                let result = self.or_expression(tokens, expression_type, variable_security);
+                if tokens[self.current_token_num].token_num == RIGHT_PARENTHESIS {
+                    self.consume_token();
+                } else {
+                    println!("No closing parenthesis on line {}", tokens[self.current_token_num].line_num);
+                    self.error = true;
+                    self.consume_token();
+                }
+                result
+            },
+            _ => {
+                println!("Invalid Token Type: Expected either literal or grouped expression, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                self.error = true;
+                self.consume_token();
+                (ANY, 0)
+            },
+        }
+    }
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+    // Code Generation... Kinda (It's mixed with the parsing code for statements)
+    // ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
+
+    fn if_statement(&mut self, tokens: &[lexer::Token]) {
+        let mut end_of_if_jump_locations: Vec<i64> = Vec::new();
+        // the if falls through when a branch does or when there is no else
+        let mut open_line = tokens[self.current_token_num].line_num;
+        let mut terminates = true;
+        let mut has_else = false;
+        loop {
+            self.if_keyword(tokens);
+
+            self.condition(tokens);
+
+            self.code.push(JUMP_IF_FALSE);
+            let code_location = self.code.len();
+            self.code.push(0);
+            self.block(tokens);
+            if terminates && !self.terminates {
+                terminates = false;
+                open_line = self.open_line;
+            }
+            self.code.push(JUMP);
+            end_of_if_jump_locations.push(self.code.len() as i64);
+            self.code.push(0);
+            let jump_location = self.code.len();
+            self.code[code_location] = jump_location as i64;
+
+            if tokens[self.current_token_num].token_num != ELSE {
+                break;
+            } else {
+                self.else_keyword(tokens);
+                if tokens[self.current_token_num].token_num == LEFT_CURLEY {
+                    self.block(tokens);
+                    if terminates && !self.terminates {
+                        terminates = false;
+                        open_line = self.open_line;
+                    }
+                    has_else = true;
+                    break;
+                }
+            }
+        }
+
+        for location in end_of_if_jump_locations {
+            self.code[location as usize] = self.code.len() as i64;
+        }
+        self.terminates = terminates && has_else;
+        self.open_line = open_line;
+    }
+
+    fn match_statement(&mut self, tokens: &[lexer::Token]) {
+        self.match_keyword(tokens);
+        let line_num = tokens[self.current_token_num].line_num;
+        let (value_type, value_security) = self.expression(tokens, ANY, 100);
+        let enum_name = self.enum_name(value_type);
+        let mut variant_names: Vec<String> = Vec::new();
+        let mut payload_types: Vec<Vec<u8>> = Vec::new();
+        let mut payload_securities: Vec<Vec<i64>> = Vec::new();
+        match &enum_name {
+            Some(name) => {
+                if let Some(x) = self.enum_data.get(name) {
+                    variant_names = x.variant_names.clone();
+                    payload_types = x.payload_types.clone();
+                    payload_securities = x.payload_securities.clone();
+                }
+            },
+            None => {
+                if value_type != INT && value_type != BOOL && value_type != STRING && value_type != ANY {
+                    println!("Cannot match on type {} on line {}.", self.type_name(value_type), line_num);
+                    self.error = true;
+                }
+            },
+        }
+        let value_location = self.stack_size;
+        self.stack_size += 1;
+        self.code.push(LOCAL_STORE);
+        self.code.push(value_location);
+
+        // the arms are emitted first, the code choosing between them follows once every pattern is known
+        self.code.push(JUMP);
+        let dispatch_jump_location = self.code.len();
+        self.code.push(0);
+
+        // (pattern value, arm location), enum patterns use the variant number and bool patterns 0 or 1
+        let mut cases: Vec<(i64, i64)> = Vec::new();
+        let mut string_cases: Vec<(usize, i64)> = Vec::new();
+        let mut default_location: i64 = -1;
+        let mut end_jump_locations: Vec<usize> = Vec::new();
+        // matches are exhaustive, so the match falls through only when an arm does
+        let mut terminates = true;
+        let mut open_line = line_num;
+        let mut arm_count = 0;
+        self.left_curley(tokens);
+        while tokens[self.current_token_num].token_num != RIGHT_CURLEY && !self.is_last_token() {
+            let arm_line_num = tokens[self.current_token_num].line_num;
+            if default_location != -1 {
+                println!("Unreachable match arm after '_' on line {}.", arm_line_num);
+                self.error = true;
+            }
+            let arm_location = self.code.len() as i64;
+            arm_count += 1;
+            let mut bindings: Vec<(String, u8, i64, i64)> = Vec::new();
+            let mut num_patterns = 0;
+            loop {
+                num_patterns += 1;
+                let pattern_value: i64;
+                let pattern_name: String;
+                let token = &tokens[self.current_token_num];
+                if token.token_num == IDENTIFIER && token.token_string == "_" {
+                    default_location = arm_location;
+                    self.consume_token();
+                    if tokens[self.current_token_num].token_num != BIT_OR {
+                        break;
+                    }
+                    self.consume_token();
+                    continue;
+                } else if enum_name.is_some() {
+                    // Enum::Variant or Enum::Variant(binding, ...), Option<T> and Result<T, E> are written without types
+                    if Some(token.token_string.as_str()) != enum_name.as_ref().and_then(|x| x.split('<').next()) {
+                        println!("Expected pattern of enum {}, got '{}' on line {}.", self.type_name(value_type), token.token_string, token.line_num);
+                        self.error = true;
+                    }
+                    self.identifier(tokens);
+                    self.double_colon(tokens);
+                    let variant_name = tokens[self.current_token_num].token_string.clone();
+                    let variant_num = variant_names.iter().position(|name| *name == variant_name);
+                    if variant_num.is_none() {
+                        println!("Enum {} has no variant '{}' on line {}.", self.type_name(value_type), variant_name, tokens[self.current_token_num].line_num);
+                        self.error = true;
+                        self.identifier(tokens);
+                        if tokens[self.current_token_num].token_num != BIT_OR {
+                            break;
+                        }
+                        self.consume_token();
+                        continue;
+                    }
+                    self.identifier(tokens);
+                    let variant_num = variant_num.unwrap_or(0);
+                    if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+                        self.left_parenthesis(tokens);
+                        let mut payload_num = 0;
+                        loop {
+                            let binding = tokens[self.current_token_num].token_string.clone();
+                            if payload_num < payload_types[variant_num].len() && binding != "_" {
+                                // a binding is as secret as its payload and the value matched on
+                                let security = std::cmp::max(value_security, payload_securities[variant_num][payload_num]);
+                                bindings.push((binding, payload_types[variant_num][payload_num], security, payload_num as i64 + 1));
+                            }
+                            payload_num += 1;
+                            self.identifier(tokens);
+                            if tokens[self.current_token_num].token_num != COMMA {
+                                break;
+                            }
+                            self.comma(tokens);
+                        }
+                        self.right_parenthesis(tokens);
+                        if payload_num != payload_types[variant_num].len() {
+                            println!("Variant '{}' has {} values but the pattern has {} on line {}.", variant_name, payload_types[variant_num].len(), payload_num, arm_line_num);
+                            self.error = true;
+                        }
+                    } else if !payload_types[variant_num].is_empty() {
+                        println!("Pattern for variant '{}' must name its {} values on line {}.", variant_name, payload_types[variant_num].len(), arm_line_num);
+                        self.error = true;
+                    }
+                    pattern_value = variant_num as i64;
+                    pattern_name = variant_name;
+                } else {
+                    // literal patterns
+                    let pattern_type = match token.token_num {
+                        INT => INT,
+                        TRUE | FALSE => BOOL,
+                        STRING => STRING,
+                        _ => ANY,
+                    };
+                    if pattern_type == ANY {
+                        println!("Expected a literal pattern, got '{}' on line {}.", token.token_string, token.line_num);
+                        self.error = true;
+                    } else if pattern_type != value_type && value_type != ANY {
+                        println!("Type mismatch: pattern '{}' is {}, expected {} on line {}.", token.token_string, self.type_name(pattern_type), self.type_name(value_type), token.line_num);
+                        self.error = true;
+                    }
+                    if token.token_num == STRING {
+                        let is_duplicate = string_cases.iter().any(|(x, _)| tokens[*x].token_string == token.token_string);
+                        if is_duplicate {
+                            println!("Pattern {} already matched on line {}.", token.token_string, token.line_num);
+                            self.error = true;
+                        }
+                        string_cases.push((self.current_token_num, arm_location));
+                        self.consume_token();
+                        if tokens[self.current_token_num].token_num != BIT_OR {
+                            break;
+                        }
+                        self.consume_token();
+                        continue;
+                    }
+                    pattern_name = token.token_string.clone();
+                    pattern_value = match token.token_num {
+                        INT => token.token_string.parse::<i64>().expect("Failed to parse integer."),
+                        TRUE => 1,
+                        _ => 0,
+                    };
+                    self.consume_token();
+                }
+                if cases.iter().any(|(x, _)| *x == pattern_value) {
+                    println!("Pattern {} already matched on line {}.", pattern_name, arm_line_num);
+                    self.error = true;
+                }
+                cases.push((pattern_value, arm_location));
+                if tokens[self.current_token_num].token_num != BIT_OR {
+                    break;
+                }
+                self.consume_token();
+            }
+            if !bindings.is_empty() && num_patterns > 1 {
+                println!("Patterns joined with '|' cannot bind values on line {}.", arm_line_num);
+                self.error = true;
+            }
+            self.arrow(tokens);
+
+            // bindings belong to the scope of the arm
+            for (binding, binding_type, security, field_num) in bindings {
+                if self.var_data.contains_key(&binding) || self.is_user_type(&binding) {
+                    println!("Identifier '{}' already declared on line {}.", binding, arm_line_num);
+                    self.error = true;
+                    continue;
+                }
+                let variable = Variable {
+                    mem_location: self.stack_size,
+                    var_type: binding_type,
+                    security,
+                    is_arg: false,
+                    arg_location: 0,
+                    scope: self.current_scope + 1,
+                    function_name: self.current_fn_name.clone(),
+                    pass_by: BY_VALUE,
+                };
+                self.code.push(LOCAL_LOAD);
+                self.code.push(value_location);
+                self.code.push(FIELD_LOAD);
+                self.code.push(field_num);
+                self.code.push(security);
+                self.code.push(LOCAL_STORE);
+                self.code.push(self.stack_size);
+                self.stack_size += 1;
+                self.var_data.insert(binding, variable);
+            }
+            self.block(tokens);
+            if terminates && !self.terminates {
+                terminates = false;
+                open_line = self.open_line;
+            }
+            self.code.push(JUMP);
+            end_jump_locations.push(self.code.len());
+            self.code.push(0);
+            if tokens[self.current_token_num].token_num == COMMA {
+                self.comma(tokens);
+            }
+        }
+        self.right_curley(tokens);
+
+        let dispatch_location = self.code.len() as i64;
+        self.code[dispatch_jump_location] = dispatch_location;
+        if default_location == -1 {
+            // the match must be exhaustive
+            if enum_name.is_some() {
+                for (i, variant_name) in variant_names.iter().enumerate() {
+                    if !cases.iter().any(|(x, _)| *x == i as i64) {
+                        println!("Match on {} is missing variant '{}' on line {}.", self.type_name(value_type), variant_name, line_num);
+                        self.error = true;
+                    }
+                }
+            } else if value_type == BOOL {
+                if cases.len() < 2 {
+                    println!("Match on bool must cover true and false or have a '_' arm on line {}.", line_num);
+                    self.error = true;
+                }
+            } else if value_type != ANY {
+                println!("Match on {} must have a '_' arm on line {}.", self.type_name(value_type), line_num);
+                self.error = true;
+            }
+        }
+
+        let mut min: i64 = 0;
+        let mut max: i64 = -1;
+        for (value, _) in cases.iter() {
+            if max < min {
+                min = *value;
+                max = *value;
+            }
+            min = std::cmp::min(min, *value);
+            max = std::cmp::max(max, *value);
+        }
+        if enum_name.is_some() {
+            min = 0;
+            max = variant_names.len() as i64 - 1;
+        }
+        // dense patterns use a jump table, sparse ones are compared one at a time
+        let is_dense = enum_name.is_some() || value_type == BOOL
+            || (cases.len() >= 3 && (max as i128 - min as i128) < 2 * cases.len() as i128);
+        if is_dense && !cases.is_empty() {
+            self.code.push(LOCAL_LOAD);
+            self.code.push(value_location);
+            if enum_name.is_some() {
+                self.code.push(FIELD_LOAD);
+                self.code.push(0);
+                self.code.push(100);
+            }
+            self.code.push(JUMP_TABLE);
+            self.code.push(min);
+            self.code.push(max - min + 1);
+            let default_jump_location = self.code.len();
+            self.code.push(default_location);
+            for value in min..max + 1 {
+                match cases.iter().find(|(x, _)| *x == value) {
+                    Some((_, location)) => self.code.push(*location),
+                    None => self.code.push(default_location),
+                }
+            }
+            // without a '_' arm the default location is -1 and unmatched values leave the match
+            for i in default_jump_location..self.code.len() {
+                if self.code[i] == -1 {
+                    end_jump_locations.push(i);
+                }
+            }
+        } else {
+            for (value, location) in cases.iter() {
+                self.code.push(LOCAL_LOAD);
+                self.code.push(value_location);
+                self.code.push(I_CONSTANT);
+                self.code.push(*value);
+                self.code.push(I_EQUAL);
+                self.code.push(JUMP_IF_TRUE);
+                self.code.push(*location);
+            }
+        }
+        for (token_location, location) in string_cases.iter() {
+            self.code.push(LOCAL_LOAD);
+            self.code.push(value_location);
+            self.push_string(&tokens[*token_location]);
+            self.code.push(S_EQUAL);
+            self.code.push(JUMP_IF_TRUE);
+            self.code.push(*location);
+        }
+        if default_location != -1 {
+            self.code.push(JUMP);
+            self.code.push(default_location);
+        }
+
+        let end_location = self.code.len() as i64;
+        for location in end_jump_locations {
+            self.code[location] = end_location;
+        }
+        self.terminates = terminates && arm_count > 0;
+        self.open_line = open_line;
+    }
+
+    fn condition(&mut self, tokens: &[lexer::Token]) {
+        let line_num = tokens[self.current_token_num].line_num;
+        let (condition_type, _) = self.or_expression(tokens, BOOL, 100);
+        if condition_type != BOOL && condition_type != ANY {
+            println!("Condition must be of type bool, got {} on line {}.", self.type_name(condition_type), line_num);
+            self.error = true;
+        }
+    }
+
+    // try { ... } catch (message string:N) { ... }, the handler only catches errors with a security level up to N so
+    // a secret message cannot reach a less secret variable.
+    fn try_statement(&mut self, tokens: &[lexer::Token]) {
+        self.consume_token();
+        self.code.push(TRY_BEGIN);
+        let handler_location = self.code.len();
+        self.code.push(0);
+        self.code.push(0);
+        self.try_depth += 1;
+        self.block(tokens);
+        self.try_depth -= 1;
+        let (try_terminates, try_open_line) = (self.terminates, self.open_line);
+        self.code.push(TRY_END);
+        self.code.push(JUMP);
+        let end_jump_location = self.code.len();
+        self.code.push(0);
+        self.code[handler_location] = self.code.len() as i64;
+
+        if tokens[self.current_token_num].token_num != CATCH {
+            println!("Expected 'catch', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        self.consume_token();
+        self.left_parenthesis(tokens);
+        let line_num = tokens[self.current_token_num].line_num;
+        let var_name = tokens[self.current_token_num].token_string.clone();
+        self.identifier(tokens);
+        let var_type = self.parse_type(tokens, true);
+        self.colon(tokens);
+        let mut security: i64 = 0;
+        if let Some(x) = self.security_level(tokens) {
+            security = x;
+        }
+        self.integer(tokens);
+        self.right_parenthesis(tokens);
+        if var_type != STRING {
+            println!("Caught error must be a string, got {} on line {}.", self.type_name(var_type), line_num);
+            self.error = true;
+        }
+        self.code[handler_location + 1] = security;
+
+        // the message pushed by the throw is stored in a variable of the catch block's scope
+        if self.var_data.contains_key(&var_name) || self.is_user_type(&var_name) {
+            println!("Identifier '{}' already declared on line {}.", var_name, line_num);
+            self.error = true;
+        } else {
+            let variable = Variable {
+                mem_location: self.stack_size,
+                var_type: STRING,
+                security,
+                is_arg: false,
+                arg_location: 0,
+                scope: self.current_scope + 1,
+                function_name: self.current_fn_name.clone(),
+                pass_by: BY_VALUE,
+            };
+            self.var_data.insert(var_name, variable);
+        }
+        self.code.push(LOCAL_STORE);
+        self.code.push(self.stack_size);
+        self.stack_size += 1;
+        self.block(tokens);
+        self.code[end_jump_location] = self.code.len() as i64;
+        if !try_terminates {
+            self.terminates = false;
+            self.open_line = try_open_line;
+        }
+    }
+
+    // throw expression, the message keeps the security level of the data it was built from.
+    fn throw_statement(&mut self, tokens: &[lexer::Token]) {
+        self.consume_token();
+        let (_, security) = self.expression(tokens, STRING, 100);
+        self.code.push(THROW_ERROR);
+        self.code.push(security);
+        self.terminates = true;
+    }
+
+    // Removes the handlers of try blocks left by a return, break or continue.
+    fn end_try_blocks(&mut self, num_blocks: usize) {
+        for _ in 0..num_blocks {
+            self.code.push(TRY_END);
+        }
+    }
+
+    fn while_statement(&mut self, tokens: &[lexer::Token], label: String) {
+        let line_num = tokens[self.current_token_num].line_num;
+        self.while_keyword(tokens);
+        let begin_location = self.code.len() as i64;
+        // only a 'while true' loop without a break never continues after the loop
+        let is_endless = tokens[self.current_token_num].token_num == TRUE
+            && self.current_token_num + 1 < tokens.len() && tokens[self.current_token_num + 1].token_num == LEFT_CURLEY;
+
+        self.condition(tokens);
+        self.code.push(JUMP_IF_FALSE);
+        let code_location = self.code.len();
+        self.code.push(0);
+        let has_breaks = self.loop_block(tokens, label, String::new(), begin_location, code_location);
+        self.terminates = is_endless && !has_breaks;
+        self.open_line = line_num;
+    }
+
+    // Parses the body of a loop, jumps back to continue_location and patches every exit of the loop. Returns whether a
+    // break leaves the loop.
+    fn loop_block(&mut self, tokens: &[lexer::Token], label: String, variable: String, continue_location: i64, exit_location: usize) -> bool {
+        self.loops.push(Loop {
+            label,
+            variable,
+            breaks: Vec::new(),
+            continues: Vec::new(),
+            try_depth: self.try_depth,
+        });
+        self.block(tokens);
+        let current_loop = self.loops.pop().expect("Loop stack is empty.");
+        self.code.push(JUMP);
+        self.code.push(continue_location);
+        let jump_location = self.code.len() as i64;
+        self.code[exit_location] = jump_location;
+        let has_breaks = !current_loop.breaks.is_empty();
+        for break_statement in current_loop.breaks {
+            self.code[break_statement] = jump_location;
+        }
+        for continue_statement in current_loop.continues {
+            self.code[continue_statement] = continue_location;
+        }
+        has_breaks
+    }
+
+    fn for_statement(&mut self, tokens: &[lexer::Token], label: String) {
+        self.for_keyword(tokens);
+        let identifier: String = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        if self.var_data.contains_key(&identifier) {
+            println!("Identifier '{}' already declared on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        self.identifier(tokens);
+        self.in_keyword(tokens);
+
+        let (first_type, first_security) = self.expression(tokens, ANY, 100);
+        let var_type: u8;
+        let security: i64;
+        let mem_location: i64 = self.stack_size;
+        let begin_location: i64;
+        let exit_location: usize;
+        let increment_code: Vec<i64>;
+        if tokens[self.current_token_num].token_num == RANGE {
+            // for i in start..end
+            self.consume_token();
+            if first_type != INT && first_type != ANY {
+                println!("Range bounds must be of type int, got {} on line {}.", self.type_name(first_type), line_num);
+                self.error = true;
+            }
+            let (_, end_security) = self.expression(tokens, INT, 100);
+            let end_location = mem_location + 1;
+            self.stack_size += 2;
+            var_type = INT;
+            security = std::cmp::max(first_security, end_security);
+
+            self.code.push(LOCAL_STORE);
+            self.code.push(end_location);
+            self.code.push(LOCAL_STORE);
+            self.code.push(mem_location);
+
+            begin_location = self.code.len() as i64;
+            self.code.push(LOCAL_LOAD);
+            self.code.push(mem_location);
+            self.code.push(LOCAL_LOAD);
+            self.code.push(end_location);
+            self.code.push(I_LESS);
+            self.code.push(JUMP_IF_FALSE);
+            exit_location = self.code.len();
+            self.code.push(0);
+
+            increment_code = vec![LOCAL_LOAD, mem_location, I_CONSTANT, 1, I_ADD, LOCAL_STORE, mem_location];
+        } else {
+            // for x in vec
+            let (len_fn, get_index_fn) = match first_type {
+                VEC_FLOAT => (standard_library::VEC_FLOAT_LEN, standard_library::VEC_FLOAT_GET_INDEX),
+                VEC_STRING => (standard_library::VEC_STRING_LEN, standard_library::VEC_STRING_GET_INDEX),
+                _ => {
+                    if first_type != VEC_INT && first_type != ANY {
+                        println!("Cannot iterate over type {} on line {}.", self.type_name(first_type), line_num);
+                        self.error = true;
+                    }
+                    (standard_library::VEC_INT_LEN, standard_library::VEC_INT_GET_INDEX)
+                },
+            };
+            let vec_location = mem_location + 1;
+            let index_location = mem_location + 2;
+            self.stack_size += 3;
+            var_type = match first_type {
+                VEC_FLOAT => FLOAT,
+                VEC_STRING => STRING,
+                _ => INT,
+            };
+            security = first_security;
+
+            self.code.push(LOCAL_STORE);
+            self.code.push(vec_location);
+            self.code.push(I_CONSTANT);
+            self.code.push(0);
+            self.code.push(LOCAL_STORE);
+            self.code.push(index_location);
+
+            begin_location = self.code.len() as i64;
+            self.code.push(LOCAL_LOAD);
+            self.code.push(index_location);
+            self.code.push(LOCAL_LOAD);
+            self.code.push(vec_location);
+            self.code.push(USE);
+            self.code.push(len_fn);
+            self.code.push(I_LESS);
+            self.code.push(JUMP_IF_FALSE);
+            exit_location = self.code.len();
+            self.code.push(0);
+
+            self.code.push(LOCAL_LOAD);
+            self.code.push(vec_location);
+            self.code.push(LOCAL_LOAD);
+            self.code.push(index_location);
+            self.code.push(USE);
+            self.code.push(get_index_fn);
+            self.code.push(LOCAL_STORE);
+            self.code.push(mem_location);
+
+            increment_code = vec![LOCAL_LOAD, index_location, I_CONSTANT, 1, I_ADD, LOCAL_STORE, index_location];
+        }
+
+        // the loop variable belongs to the scope of the loop body
+        let variable = Variable {
+            mem_location,
+            var_type,
+            security,
+            is_arg: false,
+            arg_location: 0,
+            scope: self.current_scope + 1,
+            function_name: self.current_fn_name.clone(),
+            pass_by: BY_VALUE,
+        };
+        self.var_data.insert(identifier.clone(), variable);
+
+        // the increment sits before the body so that continue can jump to it
+        self.code.push(JUMP);
+        let body_jump_location = self.code.len();
+        self.code.push(0);
+        let continue_location = self.code.len() as i64;
+        for chunk in increment_code {
+            self.code.push(chunk);
+        }
+        self.code.push(JUMP);
+        self.code.push(begin_location);
+        self.code[body_jump_location] = self.code.len() as i64;
+
+        self.loop_block(tokens, label, identifier, continue_location, exit_location);
+        self.terminates = false;
+        self.open_line = line_num;
+    }
+
+    fn is_loop_variable(&self, identifier: &str) -> bool {
+        self.loops.iter().any(|x| x.variable == identifier)
+    }
+
+    // Finds the loop targeted by a break or continue, either the innermost one or the one with the given label.
+    fn target_loop(&mut self, tokens: &[lexer::Token], statement_name: &str) -> Option<usize> {
+        let line_num = tokens[self.current_token_num].line_num;
+        if tokens[self.current_token_num].token_num == LABEL {
+            let label = tokens[self.current_token_num].token_string.clone();
+            self.consume_token();
+            match self.loops.iter().rposition(|x| x.label == label) {
+                Some(x) => return Some(x),
+                None => {
+                    println!("Unknown loop label {} on line {}.", label, line_num);
+                    self.error = true;
+                    return None;
+                },
+            }
+        }
+        if self.loops.is_empty() {
+            println!("Cannot {} out of a non loop on line {}.", statement_name, line_num);
+            self.error = true;
+            return None;
+        }
+        Some(self.loops.len() - 1)
+    }
+
+    fn break_statement(&mut self, tokens: &[lexer::Token]) {
+        self.break_keyword(tokens);
+        let target = self.target_loop(tokens, "break");
+        if let Some(x) = target {
+            self.end_try_blocks(self.try_depth - self.loops[x].try_depth);
+        }
+        self.code.push(JUMP);
+        if let Some(x) = target {
+            self.loops[x].breaks.push(self.code.len());
+        }
+        self.code.push(0);
+        self.terminates = true;
+    }
+
+    fn continue_statement(&mut self, tokens: &[lexer::Token]) {
+        self.continue_keyword(tokens);
+        let target = self.target_loop(tokens, "continue");
+        if let Some(x) = target {
+            self.end_try_blocks(self.try_depth - self.loops[x].try_depth);
+        }
+        self.code.push(JUMP);
+        if let Some(x) = target {
+            self.loops[x].continues.push(self.code.len());
+        }
+        self.code.push(0);
+        self.terminates = true;
+    }
+
+    fn var_def(&mut self, tokens: &[lexer::Token]) {
+        let identifier: String = tokens[self.current_token_num].token_string.clone();
+        let mut expression_type = INT;
+        let mut security_level = 0;
+        let mut mem_location = 0;
+        let mut is_arg: bool = false;
+        let mut is_global: bool = false;
+        let mut arg_location: i64 = 0;
+        let mut scope = 0;
+        let mut pass_by = BY_VALUE;
+        match self.var_data.get(&identifier) {
+            Some(x) => {
+                expression_type = x.var_type;
+                security_level = x.security;
+                mem_location = x.mem_location;
+                is_arg = x.is_arg;
+                arg_location = x.arg_location;
+                scope = x.scope;
+                pass_by = x.pass_by;
+            },
+            None => {
+                match self.global_data.get(&identifier) {
+                    Some(x) => {
+                        expression_type = x.var_type;
+                        security_level = x.security;
+                        mem_location = x.mem_location;
+                        is_global = true;
+                        if x.is_const {
+                            println!("Cannot assign to constant '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+                            self.error = true;
+                        }
+                    },
+                    None => {
+                        if self.is_variable(&identifier) {
+                            println!("Cannot assign to captured variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+                        } else {
+                            println!("Undeclared variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+                        }
+                        self.error = true;
+                    },
+                }
+            },
+        }
+        if self.closures.last().is_some_and(|x| x.captures.contains(&identifier)) {
+            println!("Cannot assign to captured variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        if scope > self.current_scope {
+            println!("Variable '{}' is not found within this scope on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        if self.is_loop_variable(&identifier) {
+            println!("Cannot assign to loop variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        if pass_by == BY_REF && tokens[self.current_token_num + 1].token_num != DOT {
+            println!("Cannot assign to '{}', it is passed by ref and not mut ref on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        let is_ref = pass_by != BY_VALUE;
+        self.identifier(tokens);
+        // x.a.b = expr assigns to the field b, which is as secret as everything holding it
+        let mut fields: Vec<i64> = Vec::new();
+        let mut target = identifier.clone();
+        while tokens[self.current_token_num].token_num == DOT {
+            self.consume_token();
+            target = format!("{}.{}", target, tokens[self.current_token_num].token_string);
+            let (field_num, field_type, field_security) = self.struct_field(tokens, expression_type);
+            self.field_name(tokens);
+            fields.push(field_num);
+            expression_type = field_type;
+            security_level = std::cmp::max(security_level, field_security);
+        }
+        // x = expr, x op= expr, x++ or x--
+        let operator = tokens[self.current_token_num].token_num;
+        let line_num = tokens[self.current_token_num].line_num;
+        let mut is_increment = false;
+        if operator == EQUAL {
+            self.equal(tokens);
+        } else if (operator == ADD || operator == SUB) && self.current_token_num + 1 < tokens.len()
+            && tokens[self.current_token_num + 1].token_num == operator {
+            is_increment = true;
+            self.consume_token();
+            self.consume_token();
+        } else if operator == ADD || operator == SUB || operator == MUL || operator == DIV || operator == MOD
+            || operator == BIT_AND || operator == BIT_OR || operator == BIT_XOR {
+            self.consume_token();
+            self.equal(tokens);
+        } else {
+            self.equal(tokens);
+        }
+        if !fields.is_empty() {
+            // the struct holding the field stays on the stack for FIELD_STORE
+            self.variable_load(is_arg, is_global, is_ref, mem_location, arg_location);
+            for field_num in fields[..fields.len() - 1].iter() {
+                self.code.push(FIELD_LOAD);
+                self.code.push(*field_num);
+                self.code.push(100);
+            }
+        }
+        if operator != EQUAL {
+            self.variable_load(is_arg, is_global, is_ref, mem_location, arg_location);
+            for field_num in fields.iter() {
+                self.code.push(FIELD_LOAD);
+                self.code.push(*field_num);
+                self.code.push(100);
+            }
+        }
+        if is_increment {
+            if expression_type != INT {
+                println!("Operator '{}{}' requires an int variable, '{}' is {} on line {}.", operator_name(operator), operator_name(operator), identifier, self.type_name(expression_type), line_num);
+                self.error = true;
+            }
+            self.code.push(I_CONSTANT);
+            self.code.push(1);
+            if operator == ADD {
+                self.code.push(self.checked_opcode(I_ADD));
+            } else {
+                self.code.push(self.checked_opcode(I_SUB));
+            }
+        } else if operator != EQUAL {
+            // the right hand side must have the type of the variable and respect its security level
+            let error = self.error;
+            self.error = false;
+            let (mut right_type, right_security) = self.expression(tokens, expression_type, security_level);
+            if right_type != expression_type {
+                // already reported by expression
+                right_type = ANY;
+            }
+            if right_security > security_level && !self.error {
+                println!("Max security level exceeded assigning to '{}' on line {}.", target, line_num);
+                self.error = true;
+            }
+            self.error = self.error || error;
+            let mut type_stack: Vec<u8> = vec![expression_type, right_type];
+            self.operator(tokens, operator, &mut type_stack);
+            let result_type = type_stack[0];
+            if result_type != ANY && result_type != expression_type {
+                println!("Type mismatch: '{}=' on '{}' gives {} on line {}.", operator_name(operator), identifier, self.type_name(result_type), line_num);
+                self.error = true;
+            }
+        } else {
+            // a more secret value the expression did not already report, such as the result of a function
+            let error = self.error;
+            self.error = false;
+            let (_, expression_security) = self.expression(tokens, expression_type, security_level);
+            if expression_security > security_level && !self.error {
+                println!("Max security level exceeded assigning to '{}' on line {}.", target, line_num);
+                self.error = true;
+            }
+            self.error = self.error || error;
+        }
+
+        if !fields.is_empty() {
+            self.code.push(FIELD_STORE);
+            self.code.push(fields[fields.len() - 1]);
+        } else if is_global {
+            self.code.push(GLOBAL_STORE);
+            self.code.push(mem_location);
+        } else if is_ref {
+            self.code.push(REF_STORE);
+            self.code.push(arg_location);
+        } else if !is_arg {
+            self.code.push(LOCAL_STORE);
+            self.code.push(mem_location);
+        } else {
+            self.code.push(ARG_STORE);
+            self.code.push(arg_location);
+        }
+    }
+
+    // 'ref' or 'mut ref' before the type of an argument.
+    fn pass_by(&mut self, tokens: &[lexer::Token], report_errors: bool) -> u8 {
+        if tokens[self.current_token_num].token_num == REF {
+            self.consume_token();
+            return BY_REF;
+        }
+        if tokens[self.current_token_num].token_num != MUT {
+            return BY_VALUE;
+        }
+        self.consume_token();
+        if tokens[self.current_token_num].token_num == REF {
+            self.consume_token();
+        } else if report_errors {
+            println!("Expected 'ref' after 'mut', got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        BY_MUT_REF
+    }
+
+    // Passes the location of a variable to an argument taken by reference. A function can read a mut ref at the
+    // argument's security level and write to it, so the variable must have exactly that level.
+    fn ref_arg(&mut self, tokens: &[lexer::Token], arg_type: u8, arg_security: i64, pass_by: u8) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let next_token = tokens.get(self.current_token_num + 1).map_or(SEMI_COLON, |x| x.token_num);
+        let variable = match self.var_data.get(&identifier) {
+            Some(x) if next_token == COMMA || next_token == RIGHT_PARENTHESIS => x,
+            _ => {
+                println!("Argument passed by reference must be a local variable or argument on line {}.", line_num);
+                self.error = true;
+                self.expression(tokens, arg_type, arg_security);
+                return;
+            },
+        };
+        let (var_type, security, is_arg, var_pass_by, mem_location, arg_location, scope) = (variable.var_type, variable.security,
+            variable.is_arg, variable.pass_by, variable.mem_location, variable.arg_location, variable.scope);
+        if scope > self.current_scope {
+            println!("Variable '{}' is not found within this scope on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        if var_type != arg_type && var_type != ANY && arg_type != ANY {
+            println!("Type mismatch: expected {}, got {} on line {}.", self.type_name(arg_type), self.type_name(var_type), line_num);
+            self.error = true;
+        }
+        if pass_by == BY_MUT_REF && security != arg_security {
+            println!("'{}' with security level {} cannot be passed as a mut ref with security level {} on line {}.", identifier, security, arg_security, line_num);
+            self.error = true;
+        } else if security > arg_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        if pass_by == BY_MUT_REF && var_pass_by == BY_REF {
+            println!("Cannot pass '{}' as a mut ref, it is passed by ref on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        if pass_by == BY_MUT_REF && self.is_loop_variable(&identifier) {
+            println!("Cannot pass loop variable '{}' as a mut ref on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        if var_pass_by != BY_VALUE {
+            // already a location
+            self.code.push(ARG_LOAD);
+            self.code.push(arg_location);
+        } else if is_arg {
+            self.code.push(ARG_ADDRESS);
+            self.code.push(arg_location);
+        } else {
+            self.code.push(LOCAL_ADDRESS);
+            self.code.push(mem_location);
+        }
+        self.consume_token();
+    }
+
+    fn variable_load(&mut self, is_arg: bool, is_global: bool, is_ref: bool, mem_location: i64, arg_location: i64) {
+        if is_global {
+            self.code.push(GLOBAL_LOAD);
+            self.code.push(mem_location);
+        } else if is_ref {
+            self.code.push(REF_LOAD);
+            self.code.push(arg_location);
+        } else if !is_arg {
+            self.code.push(LOCAL_LOAD);
+            self.code.push(mem_location);
+        } else {
+            self.code.push(ARG_LOAD);
+            self.code.push(arg_location);
+        }
+    }
+
+    // Looks up the field named by the current token, giving its number, type and security level.
+    fn struct_field(&mut self, tokens: &[lexer::Token], struct_type: u8) -> (i64, u8, i64) {
+        let field_name = tokens[self.current_token_num].token_string.clone();
+        if struct_type == ANY {
+            return (0, ANY, 0);
+        }
+        if struct_type >= FIRST_USER_TYPE && ((struct_type - FIRST_USER_TYPE) as usize) < self.user_types.len() {
+            let struct_name = self.user_types[(struct_type - FIRST_USER_TYPE) as usize].clone();
+            if let Some(x) = self.struct_data.get(&struct_name) {
+                if let Some(i) = x.field_names.iter().position(|name| *name == field_name) {
+                    return (i as i64, x.field_types[i], x.field_securities[i]);
+                }
+            }
+        }
+        println!("Type {} has no field '{}' on line {}.", self.type_name(struct_type), field_name, tokens[self.current_token_num].line_num);
+        self.error = true;
+        (0, ANY, 0)
+    }
+
+    // Field names are identifiers, or numbers for the elements of a tuple.
+    fn field_name(&mut self, tokens: &[lexer::Token]) {
+        if tokens[self.current_token_num].token_num == INT {
+            self.consume_token();
+        } else {
+            self.identifier(tokens);
+        }
+    }
+
+    // Loads the fields accessed after a value, e.g. customer.address.city
+    fn field_access(&mut self, tokens: &[lexer::Token], value_type: u8, value_security: i64, variable_security: i64) -> (u8, i64) {
+        let mut var_type = value_type;
+        let mut security = value_security;
+        while tokens[self.current_token_num].token_num == DOT {
+            self.consume_token();
+            let field_name = tokens[self.current_token_num].token_string.clone();
+            let (field_num, field_type, field_security) = self.struct_field(tokens, var_type);
+            if field_security > variable_security {
+                println!("Max security level exceeded with field '{}' on line {}.", field_name, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.field_name(tokens);
+            self.code.push(FIELD_LOAD);
+            self.code.push(field_num);
+            self.code.push(variable_security);
+            var_type = field_type;
+            security = std::cmp::max(security, field_security);
+        }
+        (var_type, security)
+    }
+
+    fn enum_value(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let mut type_num = ANY;
+        let mut variant_names: Vec<String> = Vec::new();
+        let mut payload_types: Vec<Vec<u8>> = Vec::new();
+        let mut payload_securities: Vec<Vec<i64>> = Vec::new();
+        match self.enum_data.get(&identifier) {
+            Some(x) => {
+                type_num = x.type_num;
+                variant_names = x.variant_names.clone();
+                payload_types = x.payload_types.clone();
+                payload_securities = x.payload_securities.clone();
+            },
+            None => {
+                println!("Unknown enum '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+                self.error = true;
+            },
+        }
+        self.identifier(tokens);
+        self.double_colon(tokens);
+        let variant_name = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let variant_num = match variant_names.iter().position(|name| *name == variant_name) {
+            Some(x) => x,
+            None => {
+                if type_num != ANY {
+                    println!("Enum {} has no variant '{}' on line {}.", identifier, variant_name, line_num);
+                    self.error = true;
+                }
+                self.identifier(tokens);
+                return (type_num, 0);
+            },
+        };
+        self.identifier(tokens);
+        self.code.push(I_CONSTANT);
+        self.code.push(variant_num as i64);
+
+        // a payload holding a value above its own level makes the whole enum that secret
+        let mut security: i64 = 0;
+        let num_payloads = payload_types[variant_num].len();
+        if num_payloads != 0 {
+            self.left_parenthesis(tokens);
+            for i in 0..num_payloads {
+                let payload_security = payload_securities[variant_num][i];
+                let (_, value_security) = self.expression(tokens, payload_types[variant_num][i], std::cmp::max(payload_security, variable_security));
+                if value_security > payload_security {
+                    security = std::cmp::max(security, value_security);
+                }
+                if i != num_payloads - 1 {
+                    self.comma(tokens);
+                }
+            }
+            self.right_parenthesis(tokens);
+        } else if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+            println!("Variant '{}' has no values on line {}.", variant_name, line_num);
+            self.error = true;
+            while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+                self.consume_token();
+            }
+            self.consume_token();
+        }
+        self.code.push(STRUCT_NEW);
+        self.code.push(num_payloads as i64 + 1);
+        self.code.push(0);
+        for payload_security in payload_securities[variant_num].iter() {
+            self.code.push(*payload_security);
+        }
+        (type_num, security)
+    }
+
+    // Option::None, Option::Some(value), Result::Ok(value) or Result::Err(error). The types are those expected
+    // where the value is used, or those of the value given to Option::Some.
+    fn wrapper_value(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let is_result = identifier == "Result";
+        self.identifier(tokens);
+        self.double_colon(tokens);
+        let variant_name = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let (variant_num, has_payload) = match (is_result, variant_name.as_str()) {
+            (false, "None") => (0, false),
+            (false, "Some") => (1, true),
+            (true, "Ok") => (0, true),
+            (true, "Err") => (1, true),
+            _ => {
+                println!("{} has no variant '{}' on line {}.", identifier, variant_name, line_num);
+                self.error = true;
+                self.identifier(tokens);
+                if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+                    while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+                        self.consume_token();
+                    }
+                    self.consume_token();
+                }
+                return (ANY, 0);
+            },
+        };
+        self.identifier(tokens);
+        let mut var_type = match self.wrapper_of(expression_type) {
+            Some((x, _, _)) if x == is_result => expression_type,
+            _ => ANY,
+        };
+        self.code.push(I_CONSTANT);
+        self.code.push(variant_num);
+
+        let mut security: i64 = 0;
+        if has_payload {
+            let payload_type = match self.wrapper_of(var_type) {
+                Some((_, _, error_type)) if variant_num == 1 && is_result => error_type,
+                Some((_, value_type, _)) => value_type,
+                None => ANY,
+            };
+            self.left_parenthesis(tokens);
+            let (value_type, value_security) = self.expression(tokens, payload_type, variable_security);
+            self.right_parenthesis(tokens);
+            security = value_security;
+            if var_type == ANY && !is_result && value_type != ANY && value_type != VOID {
+                var_type = self.wrapper_type(false, value_type, VOID);
+            }
+        }
+        if var_type == ANY {
+            println!("Cannot infer the type of '{}::{}' on line {}.", identifier, variant_name, line_num);
+            self.error = true;
+            return (ANY, security);
+        }
+        self.code.push(STRUCT_NEW);
+        if has_payload {
+            self.code.push(2);
+            self.code.push(0);
+            self.code.push(0);
+        } else {
+            self.code.push(1);
+            self.code.push(0);
+        }
+        (var_type, security)
+    }
+
+    // value? gives the value inside Some or Ok. For None or Err the function returns None or the same Err at once.
+    fn propagate(&mut self, tokens: &[lexer::Token], operand_type: u8, security: i64) -> u8 {
+        if tokens[self.current_token_num].token_num != QUESTION {
+            return operand_type;
+        }
+        let line_num = tokens[self.current_token_num].line_num;
+        self.consume_token();
+        if operand_type == ANY {
+            return ANY;
+        }
+        let (is_result, value_type, error_type) = match self.wrapper_of(operand_type) {
+            Some(x) => x,
+            None => {
+                println!("'?' expects an Option or Result, got {} on line {}.", self.type_name(operand_type), line_num);
+                self.error = true;
+                return ANY;
+            },
+        };
+        let (fn_type, fn_security) = match self.fn_data.get(&self.current_fn_name) {
+            Some(x) => (x.fn_type, x.security),
+            None => (ANY, 100),
+        };
+        let is_compatible = match self.wrapper_of(fn_type) {
+            Some((x, _, y)) => x == is_result && y == error_type,
+            None => fn_type == ANY,
+        };
+        if !is_compatible {
+            if is_result {
+                println!("'?' on {} can only be used in a function returning a Result with error type {} on line {}.", self.type_name(operand_type), self.type_name(error_type), line_num);
+            } else {
+                println!("'?' on {} can only be used in a function returning an Option on line {}.", self.type_name(operand_type), line_num);
+            }
+            self.error = true;
+        }
+        if security > fn_security {
+            println!("Max security level exceeded by '?' returning from function with security level {} on line {}.", fn_security, line_num);
+            self.error = true;
+        }
+        let location = self.stack_size;
+        self.stack_size += 1;
+        self.code.push(LOCAL_STORE);
+        self.code.push(location);
+        self.code.push(LOCAL_LOAD);
+        self.code.push(location);
+        self.code.push(FIELD_LOAD);
+        self.code.push(0);
+        self.code.push(100);
+        // Some is variant 1 and Ok is variant 0
+        self.code.push(I_CONSTANT);
+        self.code.push(if is_result { 0 } else { 1 });
+        self.code.push(I_EQUAL);
+        self.code.push(JUMP_IF_TRUE);
+        let value_jump_location = self.code.len();
+        self.code.push(0);
+        if is_result && fn_type == operand_type {
+            self.code.push(LOCAL_LOAD);
+            self.code.push(location);
+        } else if is_result {
+            // the function's Result holds another value type
+            self.code.push(I_CONSTANT);
+            self.code.push(1);
+            self.code.push(LOCAL_LOAD);
+            self.code.push(location);
+            self.code.push(FIELD_LOAD);
+            self.code.push(1);
+            self.code.push(100);
+            self.code.push(STRUCT_NEW);
+            self.code.push(2);
+            self.code.push(0);
+            self.code.push(0);
+        } else {
+            self.code.push(I_CONSTANT);
+            self.code.push(0);
+            self.code.push(STRUCT_NEW);
+            self.code.push(1);
+            self.code.push(0);
+        }
+        self.end_try_blocks(self.try_depth);
+        self.code.push(RETURN_VAL);
+        self.code[value_jump_location] = self.code.len() as i64;
+        self.code.push(LOCAL_LOAD);
+        self.code.push(location);
+        self.code.push(FIELD_LOAD);
+        self.code.push(1);
+        self.code.push(100);
+        value_type
+    }
+
+    fn struct_literal(&mut self, tokens: &[lexer::Token]) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let mut type_num = ANY;
+        let mut field_names: Vec<String> = Vec::new();
+        let mut field_types: Vec<u8> = Vec::new();
+        let mut field_securities: Vec<i64> = Vec::new();
+        if let Some(x) = self.struct_data.get(&identifier) {
+            type_num = x.type_num;
+            field_names = x.field_names.clone();
+            field_types = x.field_types.clone();
+            field_securities = x.field_securities.clone();
+        }
+        self.identifier(tokens);
+        self.left_curley(tokens);
+
+        // fields can be given in any order so each one goes to a hidden local first
+        let first_location = self.stack_size;
+        self.stack_size += field_names.len() as i64;
+        let mut is_given: Vec<bool> = vec![false; field_names.len()];
+        while tokens[self.current_token_num].token_num != RIGHT_CURLEY && !self.is_last_token() {
+            let field_name = tokens[self.current_token_num].token_string.clone();
+            let field_num = field_names.iter().position(|name| *name == field_name);
+            match field_num {
+                Some(i) => {
+                    if is_given[i] {
+                        println!("Field '{}' given more than once on line {}.", field_name, tokens[self.current_token_num].line_num);
+                        self.error = true;
                     }
-                    expression_type = self.change_expression_type(tokens);
+                    is_given[i] = true;
                 },
-                OR => {
-                    self.consume_token();
-                    self.drain_expression_stack(tokens, expression_type, expression_stack);
-                    if expression_stack.is_empty() == true {
-                        expression_stack.push(or);
-                    } else {
-                        self.shunting_yard(tokens, expression_stack, expression_type, or);
+                None => {
+                    println!("Struct '{}' has no field '{}' on line {}.", identifier, field_name, tokens[self.current_token_num].line_num);
+                    self.error = true;
+                },
+            }
+            self.identifier(tokens);
+            self.colon(tokens);
+            match field_num {
+                Some(i) => {
+                    // every field is read at its own level, so it can not hold a more secret value
+                    let error = self.error;
+                    self.error = false;
+                    let (_, field_security) = self.expression(tokens, field_types[i], field_securities[i]);
+                    if field_security > field_securities[i] && !self.error {
+                        println!("Max security level exceeded with field '{}' on line {}.", field_name, line_num);
+                        self.error = true;
                     }
-                    expression_type = self.change_expression_type(tokens);
+                    self.error = self.error || error;
+                    self.code.push(LOCAL_STORE);
+                    self.code.push(first_location + i as i64);
+                },
+                None => {
+                    self.expression(tokens, ANY, 100);
                 },
-                _ => break,
             }
-            self.literal(tokens, expression_type, variable_security);
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.comma(tokens);
         }
-    }
-
-    fn literal(&mut self, tokens: &Vec<lexer::Token>, expression_type: u8, variable_security: i64) {
-        if tokens[self.current_token_num].token_num == INT && expression_type == INT {
-            self.code.push(I_CONSTANT);
-            self.code.push(tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer."));
-            self.consume_token();
-            return;
-        } else if tokens[self.current_token_num].token_num == FLOAT && expression_type == FLOAT {
-            self.code.push(F_CONSTANT);
-            self.code.push(i64::from_be_bytes(f64::to_be_bytes(tokens[self.current_token_num].token_string.parse::<f64>().expect("Failed to parse float."))));
-            self.consume_token();
-            return;
-        } else if tokens[self.current_token_num].token_num == STRING && expression_type == STRING {
-            self.string_constant(tokens);
-            return;
-        } else if tokens[self.current_token_num].token_num == IDENTIFIER {
-            if self.current_token_num + 1 < tokens.len() {
-                // identifier is a function
-                if tokens[self.current_token_num + 1].token_num == LEFT_PARENTHESIS {
-                    match self.sl_data.get(&tokens[self.current_token_num].token_string) {
-                        Some(x) => {
-                            if x.return_type == standard_library::INT && expression_type != INT {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            } else if x.return_type == standard_library::FLOAT && expression_type != FLOAT {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            } else if x.return_type == standard_library::STRING && expression_type != STRING {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            } else if x.return_type == standard_library::VEC_INT && expression_type != VEC_INT {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            } else if x.return_type == standard_library::VEC_FLOAT && expression_type != VEC_FLOAT {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            } else if x.return_type == standard_library::VEC_STRING && expression_type != VEC_STRING {
-                                println!("Type mismatch on line {}.", tokens[self.current_token_num].line_num);
-                                self.error = true;
-                            }
+        self.right_curley(tokens);
 
-                            let return_type: i64;
-                            if expression_type == INT {
-                                return_type = standard_library::INT;
-                            } else if expression_type == FLOAT {
-                                return_type = standard_library::FLOAT;
-                            } else if expression_type == STRING {
-                                return_type = standard_library::STRING;
-                            } else if expression_type == VEC_INT {
-                                return_type = standard_library::VEC_INT;
-                            } else if expression_type == VEC_FLOAT {
-                                return_type = standard_library::VEC_FLOAT;
-                            } else if expression_type == VEC_INT {
-                                return_type = standard_library::VEC_STRING;
-                            } else {
-                                return_type = standard_library::VOID;
-                            }
-                            self.sl_use(tokens, return_type);
-                        },
-                        None => {
-                            self.identifier_function(tokens, expression_type, variable_security);
-                        }
-                    }
-                    return;
-                    // identifier is a variable
-                } else if tokens[self.current_token_num + 1].token_num != LEFT_PARENTHESIS {
-                    self.identifier_variable(tokens, expression_type, variable_security);
-                    return;
-                }
-            }
-        } else if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
-            self.consume_token();
-            self.expression(tokens, expression_type, variable_security);
-            if tokens[self.current_token_num].token_num == RIGHT_PARENTHESIS {
-                self.consume_token();
-                return;
-            } else if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-                println!("No closing parenthesis on line {}", tokens[self.current_token_num].line_num);
+        for i in 0..field_names.len() {
+            if !is_given[i] {
+                println!("Missing field '{}' in struct '{}' on line {}.", field_names[i], identifier, line_num);
                 self.error = true;
-                self.consume_token();
-                return;
             }
-        } else if expression_type == INT && tokens[self.current_token_num].token_num != INT {
-            println!("Expected integer, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-            return;
-        } else if expression_type == FLOAT && tokens[self.current_token_num].token_num != FLOAT {
-            println!("Expected float, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-            self.error = true;
-            self.consume_token();
-            return;
+            self.code.push(LOCAL_LOAD);
+            self.code.push(first_location + i as i64);
         }
-        println!("Invalid Token Type: Expected either literal or grouped expression, got '{}' on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
-        self.error = true;
-        self.consume_token();
+        self.code.push(STRUCT_NEW);
+        self.code.push(field_names.len() as i64);
+        for field_security in field_securities {
+            self.code.push(field_security);
+        }
+        (type_num, 0)
     }
 
-
-
-
-
-
-
-
-
-
-
-
-
-
-
-    // Code Generation... Kinda (It's mixed with the parsing code for statements)
-    // ---------------------------------------------------------------------------------------------------------------------------------------------------------------------------------------
-
-    fn if_statement(&mut self, tokens: &Vec<lexer::Token>, locals: &mut Vec<i64>, breaks: &mut Vec<i64>) {
-        let mut end_of_if_jump_locations: Vec<i64> = Vec::new();
-        loop {
-            self.if_keyword(tokens);
-
-            let expression_type = self.change_expression_type(tokens);
-            self.expression(tokens, expression_type, 100);
-
-            self.code.push(JUMP_IF_FALSE);
-            let code_location = self.code.len();
-            self.code.push(0);
-            self.block(tokens, locals, breaks);
-            self.code.push(JUMP);
-            end_of_if_jump_locations.push(self.code.len() as i64);
-            self.code.push(0);
-            let jump_location = self.code.len();
-            self.code[code_location] = jump_location as i64;
-
-            if tokens[self.current_token_num].token_num != ELSE {
-                break;
-            } else {
-                self.else_keyword(tokens);
-                if tokens[self.current_token_num].token_num == LEFT_CURLEY {
-                    self.block(tokens, locals, breaks);
-                    break;
+    // A '(' holds a tuple when a ',' follows its first element.
+    fn is_tuple_literal(&self, tokens: &[lexer::Token]) -> bool {
+        let mut depth = 0;
+        for token in tokens[self.current_token_num..].iter() {
+            if token.token_num == LEFT_PARENTHESIS || token.token_num == LEFT_CURLEY {
+                depth += 1;
+            } else if token.token_num == RIGHT_PARENTHESIS || token.token_num == RIGHT_CURLEY {
+                depth -= 1;
+                if depth == 0 {
+                    return false;
                 }
+            } else if token.token_num == COMMA && depth == 1 {
+                return true;
+            } else if token.token_num == SEMI_COLON {
+                return false;
             }
         }
-
-        for location in end_of_if_jump_locations {
-            self.code[location as usize] = self.code.len() as i64;
-        }
+        false
     }
 
-    fn while_statement(&mut self, tokens: &Vec<lexer::Token>, locals: &mut Vec<i64>) {
-        self.while_keyword(tokens);
-        let begin_location = self.code.len() as i64;
-
-        let expression_type = self.change_expression_type(tokens);
-        self.expression(tokens, expression_type, 100);
-        self.code.push(JUMP_IF_FALSE);
-        let code_location = self.code.len();
-        self.code.push(0);
-        let mut breaks: Vec<i64> = Vec::new();
-        self.loop_nest_num += 1;
-        self.block(tokens, locals, &mut breaks);
-        self.loop_nest_num -= 1;
-        self.code.push(JUMP);
-        self.code.push(begin_location);
-        let jump_location = self.code.len() as i64;
-        self.code[code_location] = jump_location;
-        for break_statement in breaks {
-            self.code[break_statement as usize] = jump_location;
+    // (expression, expression, ...) builds a tuple. Without an expected tuple type each element keeps the
+    // type and security level of its expression.
+    fn tuple_literal(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let line_num = tokens[self.current_token_num].line_num;
+        let expected = self.tuple_of(expression_type);
+        self.left_parenthesis(tokens);
+        let mut element_types: Vec<u8> = Vec::new();
+        let mut element_securities: Vec<i64> = Vec::new();
+        // an element holding a value above its own level makes the whole tuple that secret
+        let mut security: i64 = 0;
+        while !self.is_last_token() {
+            let i = element_types.len();
+            match &expected {
+                Some((types, securities)) if i < types.len() => {
+                    let (element_type, element_security) = (types[i], securities[i]);
+                    let (_, value_security) = self.expression(tokens, element_type, std::cmp::max(element_security, variable_security));
+                    if value_security > element_security {
+                        security = std::cmp::max(security, value_security);
+                    }
+                    element_types.push(element_type);
+                    element_securities.push(element_security);
+                },
+                _ => {
+                    let (value_type, value_security) = self.expression(tokens, ANY, variable_security);
+                    if value_type == VOID {
+                        println!("Tuple elements cannot be void on line {}.", line_num);
+                        self.error = true;
+                    }
+                    element_types.push(value_type);
+                    element_securities.push(value_security);
+                },
+            }
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.comma(tokens);
+        }
+        self.right_parenthesis(tokens);
+        match &expected {
+            Some((types, _)) if types.len() != element_types.len() => {
+                println!("Expected a tuple of {} elements, got {} on line {}.", types.len(), element_types.len(), line_num);
+                self.error = true;
+            },
+            _ => {},
         }
-    }
 
-    fn break_statement(&mut self, tokens: &Vec<lexer::Token>, breaks: &mut Vec<i64>) {
-        self.break_keyword(tokens);
-        self.code.push(JUMP);
-        breaks.push(self.code.len() as i64);
-        self.code.push(0);
+        self.code.push(STRUCT_NEW);
+        self.code.push(element_types.len() as i64);
+        for element_security in element_securities.iter() {
+            self.code.push(*element_security);
+        }
+        (self.tuple_type(element_types, element_securities), security)
     }
 
-    fn var_def(&mut self, tokens: &Vec<lexer::Token>) {
-        let identifier: String = tokens[self.current_token_num].token_string.clone();
-        self.identifier(tokens);
+    // let (a, b) = expression; gives every element of a tuple its own variable, as secret as the element and
+    // the tuple together. '_' skips an element.
+    fn tuple_dec(&mut self, tokens: &[lexer::Token]) {
+        let line_num = tokens[self.current_token_num].line_num;
+        self.left_parenthesis(tokens);
+        let mut names: Vec<String> = Vec::new();
+        while !self.is_last_token() {
+            let identifier = tokens[self.current_token_num].token_string.clone();
+            if identifier != "_" {
+                if self.var_data.contains_key(&identifier) || names.contains(&identifier) {
+                    println!("Identifier '{}' already declared on line {}.", identifier, tokens[self.current_token_num].line_num);
+                    self.error = true;
+                } else if self.global_data.contains_key(&identifier) {
+                    println!("Identifier '{}' already declared as a global on line {}.", identifier, tokens[self.current_token_num].line_num);
+                    self.error = true;
+                } else if self.is_user_type(&identifier) {
+                    println!("Identifier '{}' is already the name of a type on line {}.", identifier, tokens[self.current_token_num].line_num);
+                    self.error = true;
+                }
+            }
+            self.identifier(tokens);
+            names.push(identifier);
+            if tokens[self.current_token_num].token_num != COMMA {
+                break;
+            }
+            self.comma(tokens);
+        }
+        self.right_parenthesis(tokens);
         self.equal(tokens);
-        let mut expression_type = INT;
-        let mut security_level = 0;
-        let mut mem_location = 0;
-        let mut is_arg: bool = false;
-        let mut arg_location: i64 = 0;
-        let mut scope = 0;
-        match self.var_data.get(&identifier) {
-            Some(x) => {
-                expression_type = x.var_type;
-                security_level = x.security;
-                mem_location = x.mem_location;
-                is_arg = x.is_arg;
-                arg_location = x.arg_location;
-                scope = x.scope;
-            },
-            None => {
-                println!("Undeclared variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
-                self.error = true;
+
+        let (tuple_type, tuple_security) = self.expression(tokens, ANY, 100);
+        let (element_types, element_securities) = match self.tuple_of(tuple_type) {
+            Some(x) if x.0.len() == names.len() => x,
+            _ => {
+                if tuple_type != ANY {
+                    println!("Cannot destructure {} into {} variables on line {}.", self.type_name(tuple_type), names.len(), line_num);
+                    self.error = true;
+                }
+                (vec![ANY; names.len()], vec![0; names.len()])
             },
-        }
-        if scope > self.current_scope {
-            println!("Variable '{}' is not found within this scope on line {}.", identifier, tokens[self.current_token_num].line_num);
-            self.error = true;
-        }
-        self.expression(tokens, expression_type, security_level);
+        };
 
-        if is_arg == false {
+        let tuple_location = self.stack_size;
+        self.stack_size += 1;
+        self.code.push(LOCAL_STORE);
+        self.code.push(tuple_location);
+        for (i, identifier) in names.into_iter().enumerate() {
+            if identifier == "_" {
+                continue;
+            }
+            let security = std::cmp::max(tuple_security, element_securities[i]);
+            let mem_location = self.stack_size;
+            self.stack_size += 1;
+            self.code.push(LOCAL_LOAD);
+            self.code.push(tuple_location);
+            self.code.push(FIELD_LOAD);
+            self.code.push(i as i64);
+            self.code.push(security);
             self.code.push(LOCAL_STORE);
             self.code.push(mem_location);
-        } else {
-            self.code.push(ARG_STORE);
-            self.code.push(arg_location);
+            let variable = Variable {
+                mem_location,
+                var_type: element_types[i],
+                security,
+                is_arg: false,
+                arg_location: 0,
+                scope: self.current_scope,
+                function_name: self.current_fn_name.clone(),
+                pass_by: BY_VALUE,
+            };
+            self.var_data.insert(identifier, variable);
         }
     }
 
-    fn var_dec(&mut self, tokens: &Vec<lexer::Token>, locals: &mut Vec<i64>) {
+    fn var_dec(&mut self, tokens: &[lexer::Token]) {
         self.let_keyword(tokens);
+        if tokens[self.current_token_num].token_num == LEFT_PARENTHESIS {
+            self.tuple_dec(tokens);
+            return;
+        }
         let identifier: String = tokens[self.current_token_num].token_string.clone();
-        if self.var_data.contains_key(&identifier) == true {
+        if self.var_data.contains_key(&identifier) {
             println!("Identifier '{}' already declared on line {}.", identifier, tokens[self.current_token_num].line_num);
             self.error = true;
+        } else if self.global_data.contains_key(&identifier) {
+            println!("Identifier '{}' already declared as a global on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        } else if self.is_user_type(&identifier) {
+            println!("Identifier '{}' is already the name of a type on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
         }
+        let line_num = tokens[self.current_token_num].line_num;
         self.identifier(tokens);
-        let mut var_type: u8 = INT_TYPE;
-        let mut expression_type = INT;
         self.stack_size += 1;
-        if tokens[self.current_token_num].token_num == INT_TYPE {
-            var_type = INT;
-            expression_type = INT;
-        } else if tokens[self.current_token_num].token_num == FLOAT_TYPE {
-            var_type = FLOAT;
-            expression_type = FLOAT
-        } else if tokens[self.current_token_num].token_num == STRING_TYPE {
-            var_type = STRING;
-            expression_type = STRING;
-        } else if tokens[self.current_token_num].token_num == VEC_INT {
-            var_type = VEC_INT;
-            expression_type = VEC_INT;
-        } else if tokens[self.current_token_num].token_num == VEC_FLOAT {
-            var_type = VEC_FLOAT;
-            expression_type = VEC_FLOAT;
-        } else if tokens[self.current_token_num].token_num == VEC_STRING {
-            var_type = VEC_STRING;
-            expression_type = VEC_STRING;
-        }
-        self.var_type(tokens);
-        self.colon(tokens);
-        let mut security_level: i64 = 0;
-        if tokens[self.current_token_num].token_num == INT {
-            security_level = tokens[self.current_token_num].token_string.parse::<i64>().expect("Failed to parse integer.");
+        // 'let x = ...' and 'let x :2 = ...' take their type from the expression, without a security level
+        // the variable is as secret as the most secret value the expression uses
+        let is_type_inferred = tokens[self.current_token_num].token_num == COLON || tokens[self.current_token_num].token_num == EQUAL;
+        let is_security_inferred = tokens[self.current_token_num].token_num == EQUAL;
+        let mut var_type: u8 = ANY;
+        if !is_type_inferred {
+            var_type = self.parse_type(tokens, true);
         }
-        if security_level > 100 {
-            println!("'{}' exceeds the maximum security of 100 on line {}.", security_level, tokens[self.current_token_num].line_num);
-            self.error = true;
-        } else if security_level < 0 {
-            println!("'{}' is below the lowest security of 0 on line {}.", security_level, tokens[self.current_token_num].line_num);
-            self.error = true;
+        let mut security_level: i64 = 0;
+        if !is_security_inferred {
+            self.colon(tokens);
+            if let Some(x) = self.security_level(tokens) {
+                security_level = x;
+            }
+            if security_level > 100 {
+                println!("'{}' exceeds the maximum security of 100 on line {}.", security_level, tokens[self.current_token_num].line_num);
+                self.error = true;
+            } else if security_level < 0 {
+                println!("'{}' is below the lowest security of 0 on line {}.", security_level, tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.integer(tokens);
         }
-        self.integer(tokens);
 
         self.equal(tokens);
 
-        self.expression(tokens, expression_type, security_level);
+        let error = self.error;
+        self.error = false;
+        let (expression_type, expression_security) = self.expression(tokens, var_type, if is_security_inferred { 100 } else { security_level });
+        if is_type_inferred {
+            var_type = expression_type;
+            if var_type == VOID {
+                println!("Cannot declare '{}' from a void expression on line {}.", identifier, line_num);
+                self.error = true;
+                var_type = ANY;
+            } else if var_type == ANY && !self.error {
+                // otherwise the expression already reported why
+                println!("Cannot infer the type of '{}' on line {}.", identifier, line_num);
+                self.error = true;
+            }
+        }
+        if is_security_inferred {
+            security_level = expression_security;
+        } else if expression_security > security_level && !self.error {
+            // a more secret value the expression did not already report, such as the result of a function
+            println!("Max security level exceeded declaring '{}' on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        self.error = self.error || error;
 
         let mem_location: i64 = self.stack_size - 1;
-        locals.push(mem_location);
 
         let variable = Variable {
-            mem_location: mem_location,
-            var_type: var_type,
+            mem_location,
+            var_type,
             security: security_level,
             is_arg: false,
             arg_location: 0,
             scope: self.current_scope,
             function_name: self.current_fn_name.clone(),
+            pass_by: BY_VALUE,
         };
 
         self.var_data.insert(identifier.clone(), variable);
@@ -1633,18 +4387,27 @@ impl Parser {
         self.code.push(mem_location);
     }
 
-    fn fn_call(&mut self, tokens: &Vec<lexer::Token>) {
+    fn fn_call(&mut self, tokens: &[lexer::Token]) {
         let identifier = tokens[self.current_token_num].token_string.clone();
-        let mut mem_location: i64 = 0;
+        if self.generic_data.contains_key(&identifier) {
+            let line_num = tokens[self.current_token_num].line_num;
+            let (fn_type, _) = self.generic_call(tokens, 100);
+            if fn_type != VOID && fn_type != ANY {
+                println!("Function not within an expression must be void on line {}.", line_num);
+                self.error = true;
+            }
+            return;
+        }
         let mut num_args = 0;
         let mut arg_types: Vec<u8> = Vec::new();
         let mut arg_securities: Vec<i64> = Vec::new();
+        let mut arg_pass_by: Vec<u8> = Vec::new();
         match self.fn_data.get(&identifier) {
             Some(x) => {
                 num_args = x.num_args;
                 arg_types = x.arg_types.clone();
                 arg_securities = x.arg_securities.clone();
-                mem_location = x.mem_location;
+                arg_pass_by = x.arg_pass_by.clone();
                 if x.fn_type != VOID {
                     println!("Function not within an expression must be void on line {}.", tokens[self.current_token_num].line_num);
                     self.error = true;
@@ -1658,9 +4421,13 @@ impl Parser {
         self.identifier(tokens);
         self.left_parenthesis(tokens);
         if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-            let mut i = 0 as usize;
+            let mut i = 0_usize;
             while i < num_args as usize {
-                self.expression(tokens, arg_types[i], arg_securities[i]);
+                if arg_pass_by[i] == BY_VALUE {
+                    self.expression(tokens, arg_types[i], arg_securities[i]);
+                } else {
+                    self.ref_arg(tokens, arg_types[i], arg_securities[i], arg_pass_by[i]);
+                }
                 if i != num_args as usize - 1 {
                     self.comma(tokens);
                 }
@@ -1669,109 +4436,80 @@ impl Parser {
         }
         self.right_parenthesis(tokens);
         self.code.push(CALL);
-        self.code.push(mem_location);
+        self.fn_calls.push((identifier, self.code.len()));
+        self.code.push(0);
         self.code.push(num_args);
     }
 
-    fn return_statement(&mut self, tokens: &Vec<lexer::Token>, locals: &mut Vec<i64>) {
+    fn return_statement(&mut self, tokens: &[lexer::Token]) {
         self.return_keyword(tokens);
-        if self.current_scope == 0 {
-            self.return_num += 1;
-        }
         let mut fn_type = INT;
         let mut security = 0;
         let mut error = false;
         if tokens[self.current_token_num].token_num != SEMI_COLON {
-            match self.fn_data.get(&self.current_fn_name) {
-                Some(x) => {
-                    if x.fn_type != VOID {
-                        fn_type = x.fn_type;
-                        security = x.security;
-                    } else {
-                        error = true;
-                        println!("Cannot return value from void function on line {}.", tokens[self.current_token_num].line_num);
-                        self.error = true;
-                    }
-                },
-                None => {},
+            if let Some(x) = self.fn_data.get(&self.current_fn_name) {
+                if x.fn_type != VOID {
+                    fn_type = x.fn_type;
+                    security = x.security;
+                } else {
+                    error = true;
+                    println!("Cannot return value from void function on line {}.", tokens[self.current_token_num].line_num);
+                    self.error = true;
+                }
             }
-            if error == false {
+            if !error {
                 self.expression(tokens, fn_type, security);
             }
-            locals.sort_by(|a, b| b.cmp(a));
-            for local in locals {
-                self.code.push(POP);
-                self.code.push(*local);
+            if self.current_fn_name == "main" {
+                // the value is the exit code of the program
+                self.code.push(EXIT);
+            } else {
+                self.end_try_blocks(self.try_depth);
+                self.code.push(RETURN_VAL);
             }
-            self.code.push(RETURN_VAL);
         } else {
-            match self.fn_data.get(&self.current_fn_name) {
-                Some(x) => {
-                    if x.fn_type != VOID {
-                        println!("Must return value from non-void function on line {}.", tokens[self.current_token_num].line_num);
-                        self.error = true;
-                    }
-                },
-                None => {},
+            if let Some(x) = self.fn_data.get(&self.current_fn_name) {
+                if x.fn_type != VOID {
+                    println!("Must return value from non-void function on line {}.", tokens[self.current_token_num].line_num);
+                    self.error = true;
+                }
             }
             if self.current_fn_name == "main" {
-                locals.sort_by(|a, b| b.cmp(a));
-                for local in locals {
-                    self.code.push(POP);
-                    self.code.push(*local);
-                }
                 self.code.push(HALT);
             } else {
-                locals.sort_by(|a, b| b.cmp(a));
-                for local in locals {
-                    self.code.push(POP);
-                    self.code.push(*local);
-                }
+                self.end_try_blocks(self.try_depth);
                 self.code.push(RETURN_NON_VAL);
             }
         }
+        self.terminates = true;
     }
 
-    fn sl_use(&mut self, tokens: &Vec<lexer::Token>, expression_type: i64) {
+    fn sl_use(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        // the conversions that give 0 for a bad string are deprecated in favour of the parse functions
+        let replacement = match tokens[self.current_token_num].token_string.as_str() {
+            "string_to_int" => Some("parse_int"),
+            "string_to_float" => Some("parse_float"),
+            "string_to_decimal" => Some("parse_decimal"),
+            "string_to_bigint" => Some("parse_bigint"),
+            _ => None,
+        };
+        if let Some(x) = replacement {
+            println!("Warning: '{}' is deprecated and returns 0 when the conversion fails, use '{}' instead on line {}.",
+                tokens[self.current_token_num].token_string, x, tokens[self.current_token_num].line_num);
+        }
         if tokens[self.current_token_num].token_string == "print" {
             self.consume_token();
             self.left_parenthesis(tokens);
 
             loop {
-                if tokens[self.current_token_num].token_num == INT {
-                    self.expression(tokens, INT, 100);
-                    self.code.push(USE);
-                    self.code.push(standard_library::PRINT);
-                    self.code.push(standard_library::INT);
-                } else if tokens[self.current_token_num].token_num == FLOAT {
-                    self.expression(tokens, FLOAT, 100);
-                    self.code.push(USE);
-                    self.code.push(standard_library::PRINT);
-                    self.code.push(standard_library::FLOAT);
-                } else if tokens[self.current_token_num].token_num == STRING {
-                    self.expression(tokens, STRING, 100);
+                let line_num = tokens[self.current_token_num].line_num;
+                let (print_type, _) = self.expression(tokens, ANY, 100);
+                if print_type == INT || print_type == FLOAT || print_type == STRING || print_type == BOOL || print_type == DECIMAL || print_type == BIGINT {
                     self.code.push(USE);
                     self.code.push(standard_library::PRINT);
-                    self.code.push(standard_library::STRING);
-                } else if tokens[self.current_token_num].token_num == IDENTIFIER {
-                    let expression_type: u8;
-                    expression_type = self.change_expression_type(tokens);
-                    self.expression(tokens, expression_type, 100);
-                    if expression_type == INT {
-                        self.code.push(USE);
-                        self.code.push(standard_library::PRINT);
-                        self.code.push(standard_library::INT);
-                    } else if expression_type == FLOAT {
-                        self.code.push(USE);
-                        self.code.push(standard_library::PRINT);
-                        self.code.push(standard_library::FLOAT);
-                    } else if expression_type == STRING {
-                        self.code.push(USE);
-                        self.code.push(standard_library::PRINT);
-                        self.code.push(standard_library::STRING);
-                    }
-                } else {
-                    println!("Unknown token '{}', expected beginning of expression on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                    self.code.push(type_to_sl_type(print_type));
+                } else if print_type != ANY {
+                    println!("Cannot print value of type {} on line {}.", self.type_name(print_type), line_num);
                     self.error = true;
                 }
                 if tokens[self.current_token_num].token_num != COMMA {
@@ -1782,76 +4520,360 @@ impl Parser {
             }
     
             self.right_parenthesis(tokens);
+            (VOID, 0)
         } else if tokens[self.current_token_num].token_string == "read" {
+            // reading into an Option gives None at the end of the input or when the line is not of the type
+            let read_type = match self.wrapper_of(expression_type) {
+                Some((false, x, _)) => x,
+                _ => expression_type,
+            };
+            if read_type != INT && read_type != FLOAT && read_type != STRING && read_type != BOOL && read_type != DECIMAL && read_type != BIGINT {
+                println!("Cannot determine the type returned by 'read' on line {}.", tokens[self.current_token_num].line_num);
+                self.error = true;
+            }
+            self.consume_token();
+            self.left_parenthesis(tokens);
+            self.right_parenthesis(tokens);
+            self.code.push(USE);
+            if read_type != expression_type {
+                self.code.push(standard_library::READ_OPTION);
+            } else {
+                self.code.push(standard_library::READ);
+            }
+            self.code.push(type_to_sl_type(read_type));
+            (expression_type, 0)
+        } else if tokens[self.current_token_num].token_string.starts_with("parse_") {
+            // Ok(value) or Err(message) rather than 0 when the string is not a number
+            let identifier = tokens[self.current_token_num].token_string.clone();
+            let value_type = match identifier.as_str() {
+                "parse_int" => INT,
+                "parse_float" => FLOAT,
+                "parse_decimal" => DECIMAL,
+                _ => BIGINT,
+            };
+            let sl_num = self.sl_data.get(&identifier).map_or(0, |x| x.sl_num);
+            let line_num = tokens[self.current_token_num].line_num;
+            self.consume_token();
+            self.left_parenthesis(tokens);
+            let (_, security) = self.expression(tokens, STRING, 100);
+            self.right_parenthesis(tokens);
+            self.code.push(USE);
+            self.code.push(sl_num);
+            if security > variable_security {
+                println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+                self.error = true;
+            }
+            (self.wrapper_type(true, value_type, STRING), security)
+        } else if tokens[self.current_token_num].token_string == "env_get" {
+            // Some(value) or None when the variable is not set. The value is as secret as the policy says, or as
+            // the most secret variable in the policy when the name is only known at run time.
+            let line_num = tokens[self.current_token_num].line_num;
             self.consume_token();
             self.left_parenthesis(tokens);
+            let name = self.string_literal(tokens);
+            let (_, name_security) = self.expression(tokens, STRING, 100);
             self.right_parenthesis(tokens);
+            let level = match name {
+                Some(x) => self.policy.level("env", &x),
+                None => self.policy.max_level("env"),
+            };
             self.code.push(USE);
-            self.code.push(standard_library::READ);
-            self.code.push(expression_type);
+            self.code.push(standard_library::ENV_GET);
+            self.code.push(level);
+            let security = std::cmp::max(level, name_security);
+            if security > variable_security {
+                println!("Max security level exceeded with 'env_get' on line {}.", line_num);
+                self.error = true;
+            }
+            (self.wrapper_type(false, STRING, VOID), security)
+        } else if tokens[self.current_token_num].token_string.starts_with("map_") {
+            self.map_use(tokens, expression_type, variable_security)
+        } else if tokens[self.current_token_num].token_string.starts_with("file_") || tokens[self.current_token_num].token_string.starts_with("dir_") {
+            self.file_use(tokens, variable_security)
         } else {
             let identifier = tokens[self.current_token_num].token_string.clone();
+            let line_num = tokens[self.current_token_num].line_num;
             let types: Vec<i64>;
             let num_types: i64;
+            let return_type: i64;
             match self.sl_data.get_mut(&identifier) {
                 Some(x) => {
                     types = x.types.clone();
                     num_types = x.num_types;
+                    return_type = x.return_type;
                 },
                 None => {
                     types = Vec::new();
                     num_types = 0;
+                    return_type = standard_library::VOID;
                 },
             }
             self.consume_token();
             self.left_parenthesis(tokens);
             
+            let mut security: i64 = 0;
             let mut current_arg_type = 0;
             for arg_type in types {
-                if arg_type == standard_library::INT {
-                    self.expression(tokens, INT, 100);
-                } else if arg_type == standard_library::FLOAT {
-                    self.expression(tokens, FLOAT, 100);
-                } else if arg_type == standard_library::STRING {
-                    self.expression(tokens, STRING, 100);
-                } else if arg_type == standard_library::VEC_INT {
-                    self.expression(tokens, VEC_INT, 100);
-                } else if arg_type == standard_library::VEC_FLOAT {
-                    self.expression(tokens, VEC_FLOAT, 100);
-                } else if arg_type == standard_library::VEC_STRING {
-                    self.expression(tokens, VEC_STRING, 100);
+                let (_, arg_security) = self.expression(tokens, sl_type_to_type(arg_type), 100);
+                security = std::cmp::max(security, arg_security);
+                current_arg_type += 1;
+                if current_arg_type != num_types {
+                    self.comma(tokens);
+                }
+            }
+            self.right_parenthesis(tokens);
+
+            if let Some(x) = self.sl_data.get_mut(&identifier) {
+                self.code.push(USE);
+                self.code.push(x.sl_num);
+            }
+            // the result is as secret as the most secret argument
+            if security > variable_security {
+                println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+                self.error = true;
+            }
+            (sl_type_to_type(return_type), security)
+        }
+    }
+
+    // The string of an argument that is only a string literal, which can be looked up in the policy.
+    fn string_literal(&self, tokens: &[lexer::Token]) -> Option<String> {
+        let is_literal = tokens[self.current_token_num].token_num == STRING && self.current_token_num + 1 < tokens.len()
+            && (tokens[self.current_token_num + 1].token_num == COMMA || tokens[self.current_token_num + 1].token_num == RIGHT_PARENTHESIS);
+        if !is_literal {
+            return None;
+        }
+        Some(tokens[self.current_token_num].token_string.trim_matches('"').to_string())
+    }
+
+    // Every open file has the security level of its handle. What is read from a file is as secret as the file
+    // and only data up to its level can be written to it, which the VM checks again when the program runs.
+    fn file_use(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        self.consume_token();
+        self.left_parenthesis(tokens);
+        let return_type: u8;
+        let security: i64;
+        match identifier.as_str() {
+            "file_open" => {
+                // file_open(path, mode) takes the level of the file from the policy, file_open(path, mode, level)
+                // gives it when the policy does not
+                let path = self.string_literal(tokens);
+                let (_, path_security) = self.expression(tokens, STRING, 100);
+                self.comma(tokens);
+                let mode = self.string_literal(tokens).unwrap_or_default();
+                if mode != "r" && mode != "w" && mode != "a" {
+                    println!("The mode of 'file_open' must be \"r\", \"w\" or \"a\" on line {}.", line_num);
+                    self.error = true;
                 }
-                current_arg_type += 1;
-                if current_arg_type != num_types {
+                self.expression(tokens, STRING, 100);
+                let policy_level = match &path {
+                    Some(x) => self.policy.find("file", x),
+                    None => Some(self.policy.max_level("file")),
+                };
+                let mut level = policy_level.unwrap_or(0);
+                if tokens[self.current_token_num].token_num == COMMA {
                     self.comma(tokens);
+                    if let Some(x) = self.security_level(tokens) {
+                        level = x;
+                    }
+                    self.integer(tokens);
+                    if !(0..=100).contains(&level) {
+                        println!("File security levels must be between 0 and 100 on line {}.", line_num);
+                        self.error = true;
+                    }
+                    // a file can be read at a higher level and written at a lower one than the policy gives it
+                    match (&path, policy_level) {
+                        (Some(x), Some(y)) if mode == "r" && level < y => {
+                            println!("Cannot read '{}' with security level {} below its level of {} in the policy on line {}.", x, level, y, line_num);
+                            self.error = true;
+                        },
+                        (Some(x), Some(y)) if mode != "r" && level > y => {
+                            println!("Cannot write data with security level {} to '{}' with its level of {} in the policy on line {}.", level, x, y, line_num);
+                            self.error = true;
+                        },
+                        _ => {},
+                    }
                 }
-            }
-            self.right_parenthesis(tokens);
+                self.code.push(USE);
+                self.code.push(standard_library::FILE_OPEN);
+                self.code.push(level);
+                return_type = FILE;
+                security = std::cmp::max(level, path_security);
+            },
+            "file_read_line" => {
+                // Some(line) or None at the end of the file
+                let (_, file_security) = self.expression(tokens, FILE, 100);
+                self.code.push(USE);
+                self.code.push(standard_library::FILE_READ_LINE);
+                return_type = self.wrapper_type(false, STRING, VOID);
+                security = file_security;
+            },
+            "file_write" => {
+                let (_, file_security) = self.expression(tokens, FILE, 100);
+                self.comma(tokens);
+                let (_, data_security) = self.expression(tokens, STRING, 100);
+                if data_security > file_security {
+                    println!("Cannot write data with security level {} to a file with security level {} on line {}.", data_security, file_security, line_num);
+                    self.error = true;
+                }
+                self.code.push(USE);
+                self.code.push(standard_library::FILE_WRITE);
+                self.code.push(data_security);
+                return_type = VOID;
+                security = 0;
+            },
+            "file_close" => {
+                self.expression(tokens, FILE, 100);
+                self.code.push(USE);
+                self.code.push(standard_library::FILE_CLOSE);
+                return_type = VOID;
+                security = 0;
+            },
+            "file_exists" | "dir_exists" => {
+                let (_, path_security) = self.expression(tokens, STRING, 100);
+                self.code.push(USE);
+                self.code.push(if identifier == "file_exists" { standard_library::FILE_EXISTS } else { standard_library::DIR_EXISTS });
+                return_type = BOOL;
+                security = path_security;
+            },
+            _ => {
+                // the names in a directory are as secret as the policy says the directory is
+                let path = self.string_literal(tokens);
+                let (_, path_security) = self.expression(tokens, STRING, 100);
+                let level = match &path {
+                    Some(x) => self.policy.level("file", x),
+                    None => self.policy.max_level("file"),
+                };
+                self.code.push(USE);
+                self.code.push(standard_library::DIR_LIST);
+                self.code.push(level);
+                return_type = VEC_STRING;
+                security = std::cmp::max(level, path_security);
+            },
+        }
+        self.right_parenthesis(tokens);
+        if security > variable_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        (return_type, security)
+    }
 
-            match self.sl_data.get_mut(&identifier) {
-                Some(x) => {
-                    self.code.push(USE);
-                    self.code.push(x.sl_num);
+    // Map functions work on every key and value type so they are checked here rather than through sl_data.
+    fn map_use(&mut self, tokens: &[lexer::Token], expression_type: u8, variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        self.consume_token();
+        self.left_parenthesis(tokens);
+        if identifier == "map_new" {
+            self.right_parenthesis(tokens);
+            let key_type = match self.map_of_type(expression_type) {
+                Some(x) => x.key_type,
+                None => {
+                    println!("Cannot determine the type returned by 'map_new' on line {}.", line_num);
+                    self.error = true;
+                    return (ANY, 0);
                 },
-                None => {},
-            }
+            };
+            self.code.push(USE);
+            self.code.push(standard_library::MAP_NEW);
+            self.code.push(type_to_sl_type(key_type));
+            return (expression_type, 0);
+        }
+
+        let (map_type, map_security) = self.expression(tokens, ANY, 100);
+        let (key_type, key_security, value_type, value_security) = match self.map_of_type(map_type) {
+            Some(x) => (x.key_type, x.key_security, x.value_type, x.value_security),
+            None => {
+                if map_type != ANY {
+                    println!("'{}' expects a map, got {} on line {}.", identifier, self.type_name(map_type), line_num);
+                    self.error = true;
+                }
+                (ANY, 0, ANY, 0)
+            },
+        };
+        // keys and values may be as secret as the map holding them
+        let key_limit = std::cmp::max(key_security, map_security);
+        let value_limit = std::cmp::max(value_security, map_security);
+        let mut security = map_security;
+        let return_type: u8;
+        let sl_num: i64;
+        match identifier.as_str() {
+            "map_insert" => {
+                self.comma(tokens);
+                self.expression(tokens, key_type, key_limit);
+                self.comma(tokens);
+                self.expression(tokens, value_type, value_limit);
+                return_type = VOID;
+                sl_num = standard_library::MAP_INSERT;
+            },
+            "map_remove" => {
+                self.comma(tokens);
+                self.expression(tokens, key_type, key_limit);
+                return_type = VOID;
+                sl_num = standard_library::MAP_REMOVE;
+            },
+            "map_get" => {
+                self.comma(tokens);
+                let (_, argument_security) = self.expression(tokens, key_type, 100);
+                // which value is found depends on every key in the map
+                security = std::cmp::max(security, std::cmp::max(argument_security, std::cmp::max(key_security, value_security)));
+                return_type = value_type;
+                sl_num = standard_library::MAP_GET;
+            },
+            "map_contains" => {
+                self.comma(tokens);
+                let (_, argument_security) = self.expression(tokens, key_type, 100);
+                // membership depends on every key in the map
+                security = std::cmp::max(security, std::cmp::max(argument_security, key_security));
+                return_type = BOOL;
+                sl_num = standard_library::MAP_CONTAINS;
+            },
+            "map_len" => {
+                // the number of entries tells whether secret keys were equal
+                security = std::cmp::max(security, key_security);
+                return_type = INT;
+                sl_num = standard_library::MAP_LEN;
+            },
+            _ => {
+                security = std::cmp::max(security, key_security);
+                if key_type == STRING {
+                    return_type = VEC_STRING;
+                } else {
+                    return_type = VEC_INT;
+                }
+                sl_num = standard_library::MAP_KEYS;
+            },
+        }
+        self.right_parenthesis(tokens);
+        self.code.push(USE);
+        self.code.push(sl_num);
+        if security > variable_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+            self.error = true;
         }
+        (return_type, security)
     }
 
-    fn identifier_function(&mut self, tokens: &Vec<lexer::Token>, expression_type: u8, variable_security: i64) {
+    fn identifier_function(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
         let identifier = tokens[self.current_token_num].token_string.clone();
-        let mut mem_location: i64 = 0;
+        if self.generic_data.contains_key(&identifier) {
+            return self.generic_call(tokens, variable_security);
+        }
         let mut num_args: i64 = 0;
         let mut security: i64 = 0;
-        let mut fn_type: u8 = INT;
+        let mut fn_type: u8 = ANY;
         let mut arg_types: Vec<u8> = Vec::new();
         let mut arg_securities: Vec<i64> = Vec::new();
+        let mut arg_pass_by: Vec<u8> = Vec::new();
         match self.fn_data.get(&identifier) {
             Some(x) => {
                 num_args = x.num_args;
                 arg_types = x.arg_types.clone();
                 arg_securities = x.arg_securities.clone();
-                mem_location = x.mem_location;
+                arg_pass_by = x.arg_pass_by.clone();
                 security = x.security;  
                 fn_type = x.fn_type;
             },
@@ -1860,10 +4882,6 @@ impl Parser {
                 self.error = true;
             },
         }
-        if fn_type != expression_type {
-            println!("Type mismatch: function '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
-            self.error = true;
-        }
         if security > variable_security {
             println!("Max security level exceeded with '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
             self.error = true;
@@ -1871,9 +4889,13 @@ impl Parser {
         self.identifier(tokens);
         self.left_parenthesis(tokens);
         if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
-            let mut i = 0 as usize;
+            let mut i = 0_usize;
             while i < num_args as usize {
-                self.expression(tokens, arg_types[i], arg_securities[i]);
+                if arg_pass_by[i] == BY_VALUE {
+                    self.expression(tokens, arg_types[i], arg_securities[i]);
+                } else {
+                    self.ref_arg(tokens, arg_types[i], arg_securities[i], arg_pass_by[i]);
+                }
                 if i != num_args as usize - 1 {
                     self.comma(tokens);
                 }
@@ -1882,15 +4904,22 @@ impl Parser {
         }
         self.right_parenthesis(tokens);
         self.code.push(CALL);
-        self.code.push(mem_location);
+        self.fn_calls.push((identifier, self.code.len()));
+        self.code.push(0);
         self.code.push(num_args);
+        (fn_type, security)
+    }
+
+    fn string_constant(&mut self, tokens: &[lexer::Token]) {
+        self.push_string(&tokens[self.current_token_num]);
+        self.consume_token();
     }
 
-    fn string_constant(&mut self, tokens: &Vec<lexer::Token>) {
+    fn push_string(&mut self, token: &lexer::Token) {
         self.code.push(S_CONSTANT);
         let mut backslash: bool = false;
-        for c in tokens[self.current_token_num].token_string.clone().chars() {
-            if backslash == false {
+        for c in token.token_string.chars() {
+            if !backslash {
                 if c != '"' && c != '\\' {
                     self.code.push(c as u8 as i64);
                 } else if c == '\\' {
@@ -1898,27 +4927,30 @@ impl Parser {
                 }
             } else {
                 if c == 'n' {
-                    self.code.push(10 as i64);
+                    self.code.push(10_i64);
                     backslash = false;
                 } else {
-                    println!("Expect 'n' after backslash in token {} on line {}.", tokens[self.current_token_num].token_string, tokens[self.current_token_num].line_num);
+                    println!("Expect 'n' after backslash in token {} on line {}.", token.token_string, token.line_num);
                     self.error = true;
                     backslash = false;
                 }
             }
         }
         self.code.push(0);
-        self.consume_token();
     }
 
-    fn identifier_variable(&mut self, tokens: &Vec<lexer::Token>, expression_type: u8, variable_security: i64) {
+    fn identifier_variable(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
         let identifier: String = tokens[self.current_token_num].token_string.clone();
-        let mut var_type = INT;
+        let mut var_type = ANY;
         let mut security_level = 0;
         let mut mem_location = 0;
         let mut is_arg = false;
         let mut arg_location = 0;
         let mut scope = 0;
+        let mut pass_by = BY_VALUE;
+        if !self.var_data.contains_key(&identifier) && !self.closures.is_empty() {
+            self.capture(&identifier, self.closures.len() - 1);
+        }
         match self.var_data.get(&identifier) {
             Some(x) => {
                 var_type = x.var_type;
@@ -1927,14 +4959,24 @@ impl Parser {
                 is_arg = x.is_arg;
                 arg_location = x.arg_location;
                 scope = x.scope;
+                pass_by = x.pass_by;
+            },
+            None => {
+                if self.global_data.contains_key(&identifier) {
+                    return self.identifier_global(tokens, variable_security);
+                }
+                if self.fn_data.contains_key(&identifier) {
+                    return self.function_value(tokens);
+                }
+                if self.generic_data.contains_key(&identifier) {
+                    println!("Generic function '{}' cannot be used as a value on line {}.", identifier, tokens[self.current_token_num].line_num);
+                } else {
+                    println!("Undeclared variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+                }
+                self.error = true;
             },
-            None => println!("Undeclared variable '{}' on line {}.", identifier, tokens[self.current_token_num].line_num),
         }
 
-        if var_type != expression_type {
-            println!("Type mismatch: identifier '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
-            self.error = true;
-        }
         if security_level > variable_security {
             println!("Max security level exceeded with '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
             self.error = true;
@@ -1943,13 +4985,529 @@ impl Parser {
             println!("Variable '{}' is not found within this scope on line {}.", identifier, tokens[self.current_token_num].line_num);
             self.error = true;
         }
-        if is_arg == false {
-            self.code.push(LOCAL_LOAD);
-            self.code.push(mem_location);
+        self.variable_load(is_arg, false, pass_by != BY_VALUE, mem_location, arg_location);
+        self.consume_token();
+        (var_type, security_level)
+    }
+
+    fn identifier_global(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let identifier: String = tokens[self.current_token_num].token_string.clone();
+        let mut var_type = ANY;
+        let mut security_level = 0;
+        if let Some(x) = self.global_data.get(&identifier) {
+            var_type = x.var_type;
+            security_level = x.security;
+            if x.is_const {
+                self.code.extend(x.value.iter());
+            } else {
+                self.code.push(GLOBAL_LOAD);
+                self.code.push(x.mem_location);
+            }
+        }
+        if security_level > variable_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        self.consume_token();
+        (var_type, security_level)
+    }
+
+    fn is_variable(&self, identifier: &String) -> bool {
+        self.var_data.contains_key(identifier) || self.closures.iter().any(|x| x.outer_vars.contains_key(identifier))
+    }
+
+    // Makes a variable of an enclosing function an argument of the closure at the given level,
+    // capturing it in every closure in between.
+    fn capture(&mut self, identifier: &String, level: usize) -> bool {
+        if !self.closures[level].outer_vars.contains_key(identifier) && (level == 0 || !self.capture(identifier, level - 1)) {
+            return false;
+        }
+        let (var_type, security) = match self.closures[level].outer_vars.get(identifier) {
+            Some(x) => (x.var_type, x.security),
+            None => return false,
+        };
+        let closure = &mut self.closures[level];
+        let variable = Variable {
+            mem_location: 0,
+            var_type,
+            security,
+            is_arg: true,
+            arg_location: closure.num_args + closure.captures.len() as i64,
+            scope: 0,
+            function_name: closure.name.clone(),
+            pass_by: BY_VALUE,
+        };
+        closure.captures.push(identifier.clone());
+        closure.security = std::cmp::max(closure.security, security);
+        if level + 1 == self.closures.len() {
+            self.var_data.insert(identifier.clone(), variable);
         } else {
-            self.code.push(ARG_LOAD);
-            self.code.push(arg_location);
+            self.closures[level + 1].outer_vars.insert(identifier.clone(), variable);
+        }
+        true
+    }
+
+    // fn(name type:security, ...) -> return_type:security { ... }, the body is jumped over and the value is a closure
+    // as secret as the variables it captures.
+    fn closure(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let line_num = tokens[self.current_token_num].line_num;
+        let name = format!("fn#{}", self.num_closures);
+        self.num_closures += 1;
+        self.fn_keyword(tokens);
+        self.left_parenthesis(tokens);
+        let mut arg_names: Vec<String> = Vec::new();
+        let mut arg_types: Vec<u8> = Vec::new();
+        let mut arg_securities: Vec<i64> = Vec::new();
+        while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+            arg_names.push(tokens[self.current_token_num].token_string.clone());
+            self.identifier(tokens);
+            arg_types.push(self.parse_type(tokens, true));
+            self.colon(tokens);
+            let mut arg_security: i64 = 0;
+            if let Some(x) = self.security_level(tokens) {
+                arg_security = x;
+            }
+            arg_securities.push(arg_security);
+            self.integer(tokens);
+            if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                self.comma(tokens);
+            }
+        }
+        self.right_parenthesis(tokens);
+        let mut return_type = VOID;
+        let mut return_security: i64 = 0;
+        if tokens[self.current_token_num].token_num == THIN_ARROW {
+            self.consume_token();
+            return_type = self.parse_type(tokens, true);
+            self.colon(tokens);
+            if let Some(x) = self.security_level(tokens) {
+                return_security = x;
+            }
+            self.integer(tokens);
+        }
+        let fn_type = self.function_type(&arg_types, &arg_securities, return_type, return_security);
+
+        self.code.push(JUMP);
+        let jump_location = self.code.len();
+        self.code.push(0);
+        let fn_location = self.code.len() as i64;
+        let new_fn = Function {
+            mem_location: fn_location,
+            fn_type: return_type,
+            security: return_security,
+            num_args: arg_types.len() as i64,
+            arg_types: arg_types.clone(),
+            arg_securities: arg_securities.clone(),
+            arg_pass_by: vec![BY_VALUE; arg_types.len()],
+            times_parsed: 1,
+            is_extern: false,
+        };
+        self.fn_data.insert(name.clone(), new_fn);
+
+        // the closure is parsed like a function of its own
+        let stack_size = self.stack_size;
+        let current_fn_name = std::mem::replace(&mut self.current_fn_name, name.clone());
+        let current_scope = self.current_scope;
+        let loops = std::mem::take(&mut self.loops);
+        let try_depth = std::mem::replace(&mut self.try_depth, 0);
+        let outer_vars = std::mem::take(&mut self.var_data);
+        for i in 0..arg_names.len() {
+            let variable = Variable {
+                mem_location: 0,
+                var_type: arg_types[i],
+                security: arg_securities[i],
+                is_arg: true,
+                arg_location: i as i64,
+                scope: 0,
+                function_name: name.clone(),
+                pass_by: BY_VALUE,
+            };
+            self.var_data.insert(arg_names[i].clone(), variable);
+        }
+        self.closures.push(Closure {
+            name,
+            outer_vars,
+            num_args: arg_names.len() as i64,
+            captures: Vec::new(),
+            security: 0,
+        });
+        self.stack_size = 0;
+        self.current_scope = -1;
+        self.code.push(LOCAL_ALLOC);
+        let alloc_location = self.code.len();
+        self.code.push(0);
+        self.block(tokens);
+        self.code[alloc_location] = self.stack_size;
+        self.end_of_function(format!("Function literal on line {}", line_num));
+        // the literal is part of an expression statement, which continues after it
+        self.terminates = false;
+
+        let closure = self.closures.pop().expect("Closure stack is empty.");
+        self.var_data = closure.outer_vars;
+        self.stack_size = stack_size;
+        self.current_fn_name = current_fn_name;
+        self.current_scope = current_scope;
+        self.loops = loops;
+        self.try_depth = try_depth;
+        self.code[jump_location] = self.code.len() as i64;
+
+        for identifier in closure.captures.iter() {
+            // a reference argument is captured by the value it refers to
+            let (is_arg, is_ref, mem_location, arg_location) = match self.var_data.get(identifier) {
+                Some(x) => (x.is_arg, x.pass_by != BY_VALUE, x.mem_location, x.arg_location),
+                None => (false, false, 0, 0),
+            };
+            self.variable_load(is_arg, false, is_ref, mem_location, arg_location);
+        }
+        self.code.push(CLOSURE_NEW);
+        self.code.push(fn_location);
+        self.code.push(closure.captures.len() as i64);
+        if closure.security > variable_security {
+            println!("Max security level exceeded with function literal capturing '{}' on line {}.", closure.captures.join("', '"), line_num);
+            self.error = true;
+        }
+        (fn_type, closure.security)
+    }
+
+    // A function name used as a value is a closure without captured variables.
+    fn function_value(&mut self, tokens: &[lexer::Token]) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let (return_type, return_security, arg_types, arg_securities) = match self.fn_data.get(&identifier) {
+            Some(x) => (x.fn_type, x.security, x.arg_types.clone(), x.arg_securities.clone()),
+            None => (ANY, 0, Vec::new(), Vec::new()),
+        };
+        if self.fn_data.get(&identifier).is_some_and(|x| x.arg_pass_by.iter().any(|y| *y != BY_VALUE)) {
+            println!("Function '{}' takes arguments by reference and cannot be used as a value on line {}.", identifier, tokens[self.current_token_num].line_num);
+            self.error = true;
+        }
+        let fn_type = self.function_type(&arg_types, &arg_securities, return_type, return_security);
+        self.code.push(CLOSURE_NEW);
+        self.fn_calls.push((identifier, self.code.len()));
+        self.code.push(0);
+        self.code.push(0);
+        self.consume_token();
+        (fn_type, 0)
+    }
+
+    // Calls the closure held by a variable, the result is as secret as the variable.
+    fn indirect_call(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let (var_type, var_security) = self.identifier_variable(tokens, variable_security);
+        let (arg_types, arg_securities, return_type, return_security) = match self.fn_type_of(var_type) {
+            Some(x) => (x.arg_types.clone(), x.arg_securities.clone(), x.return_type, x.return_security),
+            None => {
+                if var_type != ANY {
+                    println!("'{}' of type {} is not a function on line {}.", identifier, self.type_name(var_type), line_num);
+                    self.error = true;
+                }
+                (Vec::new(), Vec::new(), ANY, 0)
+            },
+        };
+        self.left_parenthesis(tokens);
+        let mut num_args: usize = 0;
+        while tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS && !self.is_last_token() {
+            if num_args < arg_types.len() {
+                self.expression(tokens, arg_types[num_args], arg_securities[num_args]);
+            } else {
+                self.expression(tokens, ANY, 100);
+            }
+            num_args += 1;
+            if tokens[self.current_token_num].token_num != RIGHT_PARENTHESIS {
+                self.comma(tokens);
+            }
+        }
+        self.right_parenthesis(tokens);
+        if num_args != arg_types.len() && return_type != ANY {
+            println!("'{}' takes {} arguments but {} were given on line {}.", identifier, arg_types.len(), num_args, line_num);
+            self.error = true;
+        }
+        self.code.push(CALL_INDIRECT);
+        self.code.push(num_args as i64);
+        if return_security > variable_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        (return_type, std::cmp::max(var_security, return_security))
+    }
+
+    // Reads where the argument types of a generic function start and the argument security levels. Security
+    // levels written as names are security level parameters, which are given for the arguments and the return.
+    fn generic_args(&mut self, tokens: &[lexer::Token], start: usize) -> (Vec<usize>, Vec<i64>, Vec<Option<String>>, Option<String>) {
+        let current_token_num = self.current_token_num;
+        let mut arg_type_locations: Vec<usize> = Vec::new();
+        let mut arg_securities: Vec<i64> = Vec::new();
+        let mut arg_levels: Vec<Option<String>> = Vec::new();
+        let mut return_level: Option<String> = None;
+        self.current_token_num = start;
+        // fn
+        self.consume_token();
+        if tokens[self.current_token_num].token_num != VOID {
+            self.parse_type(tokens, false);
+            // colon
+            self.consume_token();
+            if tokens[self.current_token_num].token_num == IDENTIFIER {
+                return_level = Some(tokens[self.current_token_num].token_string.clone());
+            }
+        }
+        // type or security
+        self.consume_token();
+        while tokens[self.current_token_num].token_num != LEFT_PARENTHESIS && !self.is_last_token() {
+            self.consume_token();
         }
         self.consume_token();
+        while tokens[self.current_token_num].token_num == IDENTIFIER {
+            // identifier
+            self.consume_token();
+            self.pass_by(tokens, false);
+            arg_type_locations.push(self.current_token_num);
+            self.parse_type(tokens, false);
+            // colon
+            self.consume_token();
+            let mut arg_security: i64 = 0;
+            if let Some(x) = self.security_level(tokens) {
+                arg_security = x;
+            }
+            arg_securities.push(arg_security);
+            if tokens[self.current_token_num].token_num == IDENTIFIER {
+                arg_levels.push(Some(tokens[self.current_token_num].token_string.clone()));
+            } else {
+                arg_levels.push(None);
+            }
+            self.consume_token();
+            if tokens[self.current_token_num].token_num == COMMA {
+                self.consume_token();
+            }
+        }
+        self.current_token_num = current_token_num;
+        (arg_type_locations, arg_securities, arg_levels, return_level)
+    }
+
+    // Type written at the given token with some type parameters bound, the others are any type.
+    fn type_at(&mut self, tokens: &[lexer::Token], location: usize, bindings: &HashMap<String, u8>) -> u8 {
+        let current_token_num = self.current_token_num;
+        let type_params = std::mem::replace(&mut self.type_params, bindings.clone());
+        self.current_token_num = location;
+        let var_type = self.parse_type(tokens, false);
+        self.current_token_num = current_token_num;
+        self.type_params = type_params;
+        var_type
+    }
+
+    // Binds the type parameters in the type written at the given token to the matching parts of an argument's type.
+    fn bind_type_params(&mut self, tokens: &[lexer::Token], location: usize, arg_type: u8, type_params: &Vec<String>, bindings: &mut HashMap<String, u8>) {
+        let token = &tokens[location];
+        if token.token_num != IDENTIFIER || arg_type == ANY {
+            return;
+        }
+        if type_params.contains(&token.token_string) {
+            if !bindings.contains_key(&token.token_string) {
+                bindings.insert(token.token_string.clone(), arg_type);
+            }
+            return;
+        }
+        let is_wrapper = (token.token_string == "Option" || token.token_string == "Result") && tokens[location + 1].token_num == LESS;
+        if let (true, Some((_, value_type, error_type))) = (is_wrapper, self.wrapper_of(arg_type)) {
+            self.bind_type_params(tokens, location + 2, value_type, type_params, bindings);
+            if token.token_string == "Result" {
+                // the error type follows the value type and a comma
+                let current_token_num = self.current_token_num;
+                self.current_token_num = location + 2;
+                self.parse_type(tokens, false);
+                let error_location = self.current_token_num + 1;
+                self.current_token_num = current_token_num;
+                self.bind_type_params(tokens, error_location, error_type, type_params, bindings);
+            }
+        }
+    }
+
+    // Infers the type parameters of a generic function from the arguments and calls the instance for those types.
+    // A security level parameter is bound to the highest level of the arguments written with it.
+    fn generic_call(&mut self, tokens: &[lexer::Token], variable_security: i64) -> (u8, i64) {
+        let identifier = tokens[self.current_token_num].token_string.clone();
+        let line_num = tokens[self.current_token_num].line_num;
+        let (start, type_params) = match self.generic_data.get(&identifier) {
+            Some(x) => (x.start, x.type_params.clone()),
+            None => return (ANY, 0),
+        };
+        let (arg_type_locations, arg_securities, arg_levels, return_level) = self.generic_args(tokens, start);
+        let mut bindings: HashMap<String, u8> = HashMap::new();
+        let mut levels: HashMap<String, i64> = HashMap::new();
+        self.identifier(tokens);
+        self.left_parenthesis(tokens);
+        for i in 0..arg_type_locations.len() {
+            let type_token = &tokens[arg_type_locations[i]];
+            let is_param = type_token.token_num == IDENTIFIER && type_params.contains(&type_token.token_string);
+            let mut expected_type: u8;
+            if is_param {
+                expected_type = *bindings.get(&type_token.token_string).unwrap_or(&ANY);
+            } else {
+                expected_type = self.type_at(tokens, arg_type_locations[i], &bindings);
+            }
+            // Option<T> or Result<T, E> with a parameter that is not known yet
+            if let Some((_, value_type, error_type)) = self.wrapper_of(expected_type) {
+                if value_type == ANY || error_type == ANY {
+                    expected_type = ANY;
+                }
+            }
+            let security_limit = if arg_levels[i].is_some() { 100 } else { arg_securities[i] };
+            let (arg_type, arg_security) = self.expression(tokens, expected_type, security_limit);
+            self.bind_type_params(tokens, arg_type_locations[i], arg_type, &type_params, &mut bindings);
+            if let Some(level_param) = &arg_levels[i] {
+                let level = levels.entry(level_param.clone()).or_insert(0);
+                *level = std::cmp::max(*level, arg_security);
+            }
+            if i + 1 < arg_type_locations.len() {
+                self.comma(tokens);
+            }
+        }
+        self.right_parenthesis(tokens);
+
+        let mut type_names: Vec<String> = Vec::new();
+        for type_param in type_params.iter() {
+            match bindings.get(type_param) {
+                Some(x) => type_names.push(self.type_name(*x)),
+                None => {
+                    println!("Cannot infer type parameter '{}' of '{}' on line {}.", type_param, identifier, line_num);
+                    self.error = true;
+                    return (ANY, 0);
+                },
+            }
+        }
+        if let Some(level_param) = return_level {
+            if !levels.contains_key(&level_param) {
+                println!("Cannot infer security level parameter '{}' of '{}' on line {}.", level_param, identifier, line_num);
+                self.error = true;
+                return (ANY, 0);
+            }
+        }
+        let mut level_names: Vec<&String> = levels.keys().collect();
+        level_names.sort();
+        for level_param in level_names {
+            type_names.push(format!("{}:{}", level_param, levels[level_param]));
+        }
+        let instance_name = format!("{}<{}>", identifier, type_names.join(", "));
+        if !self.fn_data.contains_key(&instance_name) {
+            let current_token_num = self.current_token_num;
+            let type_params = std::mem::replace(&mut self.type_params, bindings.clone());
+            let level_params = std::mem::replace(&mut self.level_params, levels.clone());
+            self.current_token_num = start;
+            self.index_function(tokens, instance_name.clone());
+            self.current_token_num = current_token_num;
+            self.type_params = type_params;
+            self.level_params = level_params;
+            let instance = Instance {
+                name: instance_name.clone(),
+                start,
+                bindings,
+                levels,
+                line_num,
+            };
+            self.instances.push(instance);
+        }
+
+        let (fn_type, security, num_args) = match self.fn_data.get(&instance_name) {
+            Some(x) => (x.fn_type, x.security, x.num_args),
+            None => (ANY, 0, 0),
+        };
+        if security > variable_security {
+            println!("Max security level exceeded with '{}' on line {}.", identifier, line_num);
+            self.error = true;
+        }
+        self.code.push(CALL);
+        self.fn_calls.push((instance_name, self.code.len()));
+        self.code.push(0);
+        self.code.push(num_args);
+        (fn_type, security)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compiles(source: &str) -> bool {
+        let (tokens, error) = lexer::lexer(source);
+        assert!(!error);
+        let mut parser = Parser::new(&tokens);
+        !parser.parse(&tokens)
+    }
+
+    #[test]
+    fn struct_fields_keep_their_level() {
+        let declaration = "struct C { name string:0, card int:2, }\n";
+        assert!(compiles(&format!("{}fn void main() {{ let s int:2 = 1; let c C:0 = C {{ name: \"a\", card: s }}; c.card = s; }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ let s int:2 = 1; let c C:0 = C {{ name: \"a\", card: 1 }}; c.name = int_to_string(s); }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ let s int:2 = 1; let c = C {{ name: int_to_string(s), card: 1 }}; }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ let s int:2 = 1; let c C:0 = C {{ name: \"a\", card: 1 }}; c.name += int_to_string(s); }}", declaration)));
+    }
+
+    #[test]
+    fn explicit_levels_are_not_lower_than_the_value() {
+        assert!(compiles("fn void main() { let s int:2 = 42; let a = int_to_string(s); let b :2 = a; let c string:3 = a; }"));
+        assert!(!compiles("fn void main() { let s int:2 = 42; let a :0 = int_to_string(s); print(a); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 42; let a string:1 = int_to_string(s); print(a); }"));
+    }
+
+    #[test]
+    fn library_functions_keep_the_level_of_their_arguments() {
+        assert!(compiles("fn void main() { let s int:2 = 1; let p int:2 = wrapping_add(s, 1); let q :2 = saturating_mul(s, 2); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let p int:0 = wrapping_add(s, 1); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let p int:0 = saturating_sub(1, s); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let p int:0 = 0; p = wrapping_mul(s, 3); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; print(int_to_string(s) + \"\"); let t string:0 = int_to_string(s) + \"\"; }"));
+    }
+
+    #[test]
+    fn decimal_functions_keep_the_level_of_their_arguments() {
+        assert!(compiles("fn void main() { let s int:2 = 1; let d decimal:2 = decimal_mul(int_to_decimal(s), 1.5d, \"half_up\"); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let d decimal:0 = int_to_decimal(s); }"));
+        assert!(!compiles("fn void main() { let s string:2 = \"1.5\"; let d decimal:0 = string_to_decimal(s); }"));
+        assert!(!compiles("fn void main() { let s decimal:2 = 1.5d; let d decimal:0 = decimal_mul(s, 2.0d, \"half_even\"); }"));
+        assert!(!compiles("fn void main() { let s decimal:2 = 1.5d; let d decimal:0 = decimal_div(1.0d, s, \"half_even\"); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let d decimal:0 = decimal_round(2.25d, s, \"half_even\"); }"));
+        assert!(!compiles("fn void main() { let s string:2 = \"down\"; let d decimal:0 = decimal_round(2.25d, 1, s); }"));
+    }
+
+    #[test]
+    fn bigint_functions_keep_the_level_of_their_arguments() {
+        assert!(compiles("fn void main() { let s int:2 = 1; let q int:2 = bigint_to_int(int_to_bigint(s) * 3n); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let q int:0 = bigint_to_int(int_to_bigint(s)); }"));
+        assert!(!compiles("fn void main() { let s int:2 = 1; let q bigint:0 = int_to_bigint(s); }"));
+        assert!(!compiles("fn void main() { let s string:2 = \"7\"; let q bigint:0 = string_to_bigint(s); }"));
+        assert!(!compiles("fn void main() { let s bigint:2 = 7n; let q string:0 = bigint_to_string(s); }"));
+    }
+
+    #[test]
+    fn parse_functions_keep_the_level_of_the_string() {
+        assert!(compiles("fn void main() { let s string:2 = \"5\"; let r Result<int, string>:2 = parse_int(s); }"));
+        assert!(!compiles("fn void main() { let s string:2 = \"5\"; let r Result<int, string>:0 = parse_int(s); }"));
+        assert!(!compiles("fn void main() { let s string:2 = \"5\"; let r Result<decimal, string>:1 = parse_decimal(s); }"));
+    }
+
+    #[test]
+    fn loop_variables_can_not_be_changed() {
+        let declaration = "fn void f(a mut ref int:0) { a = 5; }\nfn void g(a ref int:0) { print(a); }\n";
+        assert!(compiles(&format!("{}fn void main() {{ for i in 0..3 {{ g(i); let j int:0 = i; f(j); }} }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ for i in 0..3 {{ f(i); }} }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ for i in 0..3 {{ i = 5; }} }}", declaration)));
+        assert!(!compiles(&format!("{}fn void main() {{ for i in 0..3 {{ for j in 0..3 {{ i += j; }} }} }}", declaration)));
+    }
+
+    #[test]
+    fn constants_fold_like_the_vm() {
+        assert!(matches!(fold_operator(I_DIV, i64::MIN, -1), Ok(i64::MIN)));
+        assert!(matches!(fold_operator(I_MOD, i64::MIN, -1), Ok(0)));
+        assert!(matches!(fold_operator(I_DIV_CHECKED, i64::MIN, -1), Err(FoldError::Overflow)));
+        assert!(matches!(fold_operator(I_DIV, 1, 0), Err(FoldError::DivisionByZero)));
+        assert!(matches!(fold_operator(I_MOD, 1, 0), Err(FoldError::DivisionByZero)));
+        assert!(compiles("const A int:0 = -9223372036854775807 - 1; const B int:0 = A / -1; fn void main() { print(B); }"));
+        assert!(!compiles("const A int:0 = 1 / 0; fn void main() { print(A); }"));
+    }
+
+    #[test]
+    fn constants_are_not_declared_in_functions() {
+        assert!(!compiles("fn void main() { const X int:0 = 5; }"));
+        assert!(!compiles("fn void main() { static X int:0 = 5; }"));
     }
-}
\ No newline at end of file
+}
@@ -9,6 +9,10 @@ pub const VEC_INT: i64 = 4;
 pub const VEC_FLOAT: i64 = 5;
 pub const VEC_STRING: i64 = 6;
 pub const ANY: i64 = 7;
+pub const BOOL: i64 = 8;
+pub const DECIMAL: i64 = 9;
+pub const BIGINT: i64 = 10;
+pub const FILE: i64 = 11;
 
 // functions
 pub const PRINT: i64 = 0;
@@ -40,6 +44,45 @@ pub const VEC_STRING_POP: i64 = 25;
 pub const VEC_STRING_SET_INDEX: i64 = 26;
 pub const VEC_STRING_GET_INDEX: i64 = 27;
 pub const VEC_STRING_LEN: i64 = 28;
+pub const MAP_NEW: i64 = 29;
+pub const MAP_INSERT: i64 = 30;
+pub const MAP_GET: i64 = 31;
+pub const MAP_REMOVE: i64 = 32;
+pub const MAP_CONTAINS: i64 = 33;
+pub const MAP_LEN: i64 = 34;
+pub const MAP_KEYS: i64 = 35;
+pub const WRAPPING_ADD: i64 = 36;
+pub const WRAPPING_SUB: i64 = 37;
+pub const WRAPPING_MUL: i64 = 38;
+pub const SATURATING_ADD: i64 = 39;
+pub const SATURATING_SUB: i64 = 40;
+pub const SATURATING_MUL: i64 = 41;
+pub const INT_TO_DECIMAL: i64 = 42;
+pub const DECIMAL_TO_INT: i64 = 43;
+pub const FLOAT_TO_DECIMAL: i64 = 44;
+pub const DECIMAL_TO_FLOAT: i64 = 45;
+pub const STRING_TO_DECIMAL: i64 = 46;
+pub const DECIMAL_TO_STRING: i64 = 47;
+pub const DECIMAL_MUL: i64 = 48;
+pub const DECIMAL_DIV: i64 = 49;
+pub const DECIMAL_ROUND: i64 = 50;
+pub const INT_TO_BIGINT: i64 = 51;
+pub const BIGINT_TO_INT: i64 = 52;
+pub const STRING_TO_BIGINT: i64 = 53;
+pub const BIGINT_TO_STRING: i64 = 54;
+pub const PARSE_INT: i64 = 55;
+pub const PARSE_FLOAT: i64 = 56;
+pub const PARSE_DECIMAL: i64 = 57;
+pub const PARSE_BIGINT: i64 = 58;
+pub const READ_OPTION: i64 = 59;
+pub const ENV_GET: i64 = 60;
+pub const FILE_OPEN: i64 = 61;
+pub const FILE_READ_LINE: i64 = 62;
+pub const FILE_WRITE: i64 = 63;
+pub const FILE_CLOSE: i64 = 64;
+pub const FILE_EXISTS: i64 = 65;
+pub const DIR_EXISTS: i64 = 66;
+pub const DIR_LIST: i64 = 67;
 
 pub struct SlData {
     pub sl_num: i64,
@@ -130,7 +173,7 @@ pub fn get_sl_data() -> HashMap<String, SlData> {
     });
     sl_data.insert("vec_int_pop".to_string(), SlData {
         sl_num: VEC_INT_POP,
-        types: vec![INT],
+        types: vec![VEC_INT],
         num_types: 1,
         return_type: VOID,
     });
@@ -150,7 +193,7 @@ pub fn get_sl_data() -> HashMap<String, SlData> {
         sl_num: VEC_INT_LEN,
         types: vec![VEC_INT],
         num_types: 1,
-        return_type: VOID,
+        return_type: INT,
     });
     sl_data.insert("vec_float_new".to_string(), SlData {
         sl_num: VEC_FLOAT_NEW,
@@ -188,5 +231,271 @@ pub fn get_sl_data() -> HashMap<String, SlData> {
         num_types: 1,
         return_type: INT,
     });
+    sl_data.insert("vec_string_new".to_string(), SlData {
+        sl_num: VEC_STRING_NEW,
+        types: vec![],
+        num_types: 0,
+        return_type: VEC_STRING,
+    });
+    sl_data.insert("vec_string_push".to_string(), SlData {
+        sl_num: VEC_STRING_PUSH,
+        types: vec![VEC_STRING, STRING],
+        num_types: 2,
+        return_type: VOID,
+    });
+    sl_data.insert("vec_string_pop".to_string(), SlData {
+        sl_num: VEC_STRING_POP,
+        types: vec![VEC_STRING],
+        num_types: 1,
+        return_type: VOID,
+    });
+    sl_data.insert("vec_string_set_index".to_string(), SlData {
+        sl_num: VEC_STRING_SET_INDEX,
+        types: vec![VEC_STRING, INT, STRING],
+        num_types: 3,
+        return_type: VOID,
+    });
+    sl_data.insert("vec_string_get_index".to_string(), SlData {
+        sl_num: VEC_STRING_GET_INDEX,
+        types: vec![VEC_STRING, INT],
+        num_types: 2,
+        return_type: STRING,
+    });
+    sl_data.insert("vec_string_len".to_string(), SlData {
+        sl_num: VEC_STRING_LEN,
+        types: vec![VEC_STRING],
+        num_types: 1,
+        return_type: INT,
+    });
+    // map functions are type checked by the parser
+    sl_data.insert("map_new".to_string(), SlData {
+        sl_num: MAP_NEW,
+        types: vec![],
+        num_types: 0,
+        return_type: ANY,
+    });
+    sl_data.insert("map_insert".to_string(), SlData {
+        sl_num: MAP_INSERT,
+        types: vec![ANY, ANY, ANY],
+        num_types: 3,
+        return_type: VOID,
+    });
+    sl_data.insert("map_get".to_string(), SlData {
+        sl_num: MAP_GET,
+        types: vec![ANY, ANY],
+        num_types: 2,
+        return_type: ANY,
+    });
+    sl_data.insert("map_remove".to_string(), SlData {
+        sl_num: MAP_REMOVE,
+        types: vec![ANY, ANY],
+        num_types: 2,
+        return_type: VOID,
+    });
+    sl_data.insert("map_contains".to_string(), SlData {
+        sl_num: MAP_CONTAINS,
+        types: vec![ANY, ANY],
+        num_types: 2,
+        return_type: BOOL,
+    });
+    sl_data.insert("map_len".to_string(), SlData {
+        sl_num: MAP_LEN,
+        types: vec![ANY],
+        num_types: 1,
+        return_type: INT,
+    });
+    sl_data.insert("map_keys".to_string(), SlData {
+        sl_num: MAP_KEYS,
+        types: vec![ANY],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("wrapping_add".to_string(), SlData {
+        sl_num: WRAPPING_ADD,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("wrapping_sub".to_string(), SlData {
+        sl_num: WRAPPING_SUB,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("wrapping_mul".to_string(), SlData {
+        sl_num: WRAPPING_MUL,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("saturating_add".to_string(), SlData {
+        sl_num: SATURATING_ADD,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("saturating_sub".to_string(), SlData {
+        sl_num: SATURATING_SUB,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("saturating_mul".to_string(), SlData {
+        sl_num: SATURATING_MUL,
+        types: vec![INT, INT],
+        num_types: 2,
+        return_type: INT,
+    });
+    sl_data.insert("int_to_decimal".to_string(), SlData {
+        sl_num: INT_TO_DECIMAL,
+        types: vec![INT],
+        num_types: 1,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("decimal_to_int".to_string(), SlData {
+        sl_num: DECIMAL_TO_INT,
+        types: vec![DECIMAL],
+        num_types: 1,
+        return_type: INT,
+    });
+    sl_data.insert("float_to_decimal".to_string(), SlData {
+        sl_num: FLOAT_TO_DECIMAL,
+        types: vec![FLOAT],
+        num_types: 1,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("decimal_to_float".to_string(), SlData {
+        sl_num: DECIMAL_TO_FLOAT,
+        types: vec![DECIMAL],
+        num_types: 1,
+        return_type: FLOAT,
+    });
+    sl_data.insert("string_to_decimal".to_string(), SlData {
+        sl_num: STRING_TO_DECIMAL,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("decimal_to_string".to_string(), SlData {
+        sl_num: DECIMAL_TO_STRING,
+        types: vec![DECIMAL],
+        num_types: 1,
+        return_type: STRING,
+    });
+    sl_data.insert("decimal_mul".to_string(), SlData {
+        sl_num: DECIMAL_MUL,
+        types: vec![DECIMAL, DECIMAL, STRING],
+        num_types: 3,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("decimal_div".to_string(), SlData {
+        sl_num: DECIMAL_DIV,
+        types: vec![DECIMAL, DECIMAL, STRING],
+        num_types: 3,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("decimal_round".to_string(), SlData {
+        sl_num: DECIMAL_ROUND,
+        types: vec![DECIMAL, INT, STRING],
+        num_types: 3,
+        return_type: DECIMAL,
+    });
+    sl_data.insert("int_to_bigint".to_string(), SlData {
+        sl_num: INT_TO_BIGINT,
+        types: vec![INT],
+        num_types: 1,
+        return_type: BIGINT,
+    });
+    sl_data.insert("bigint_to_int".to_string(), SlData {
+        sl_num: BIGINT_TO_INT,
+        types: vec![BIGINT],
+        num_types: 1,
+        return_type: INT,
+    });
+    sl_data.insert("string_to_bigint".to_string(), SlData {
+        sl_num: STRING_TO_BIGINT,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: BIGINT,
+    });
+    sl_data.insert("bigint_to_string".to_string(), SlData {
+        sl_num: BIGINT_TO_STRING,
+        types: vec![BIGINT],
+        num_types: 1,
+        return_type: STRING,
+    });
+    // the parse functions return Result<type, string>, which the parser gives them
+    sl_data.insert("parse_int".to_string(), SlData {
+        sl_num: PARSE_INT,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("parse_float".to_string(), SlData {
+        sl_num: PARSE_FLOAT,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("parse_decimal".to_string(), SlData {
+        sl_num: PARSE_DECIMAL,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("parse_bigint".to_string(), SlData {
+        sl_num: PARSE_BIGINT,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("env_get".to_string(), SlData {
+        sl_num: ENV_GET,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("file_open".to_string(), SlData {
+        sl_num: FILE_OPEN,
+        types: vec![STRING, STRING],
+        num_types: 2,
+        return_type: FILE,
+    });
+    sl_data.insert("file_read_line".to_string(), SlData {
+        sl_num: FILE_READ_LINE,
+        types: vec![FILE],
+        num_types: 1,
+        return_type: ANY,
+    });
+    sl_data.insert("file_write".to_string(), SlData {
+        sl_num: FILE_WRITE,
+        types: vec![FILE, STRING],
+        num_types: 2,
+        return_type: VOID,
+    });
+    sl_data.insert("file_close".to_string(), SlData {
+        sl_num: FILE_CLOSE,
+        types: vec![FILE],
+        num_types: 1,
+        return_type: VOID,
+    });
+    sl_data.insert("file_exists".to_string(), SlData {
+        sl_num: FILE_EXISTS,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: BOOL,
+    });
+    sl_data.insert("dir_exists".to_string(), SlData {
+        sl_num: DIR_EXISTS,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: BOOL,
+    });
+    sl_data.insert("dir_list".to_string(), SlData {
+        sl_num: DIR_LIST,
+        types: vec![STRING],
+        num_types: 1,
+        return_type: VEC_STRING,
+    });
     sl_data
 }
\ No newline at end of file
@@ -13,6 +13,14 @@ const I_GREATER: i64 = 11;
 const I_NOT_EQUAL: i64 = 12;
 const I_LESS_EQUAL: i64 = 13;
 const I_GREATER_EQUAL: i64 = 14;
+const I_MOD: i64 = 43;
+const I_BIT_AND: i64 = 45;
+const I_BIT_OR: i64 = 46;
+const I_BIT_XOR: i64 = 47;
+const I_SHIFT_LEFT: i64 = 48;
+const I_SHIFT_RIGHT: i64 = 49;
+const I_POW: i64 = 50;
+const I_NEG: i64 = 52;
 
 const F_CONSTANT: i64 = 15;
 const F_ADD: i64 = 16;
@@ -25,6 +33,9 @@ const F_GREATER: i64 = 22;
 const F_NOT_EQUAL: i64 = 23;
 const F_LESS_EQUAL: i64 = 24;
 const F_GREATER_EQUAL: i64 = 25;
+const F_MOD: i64 = 44;
+const F_POW: i64 = 51;
+const F_NEG: i64 = 53;
 
 const S_CONSTANT: i64 = 26;
 const S_ADD: i64 = 27;
@@ -33,9 +44,11 @@ const S_NOT_EQUAL: i64 = 29;
 
 const OP_AND: i64 = 30;
 const OP_OR: i64 = 31;
+const OP_NOT: i64 = 41;
 
 const JUMP_IF_FALSE: i64 = 32;
 const JUMP: i64 = 33;
+const JUMP_IF_TRUE: i64 = 42;
 
 const CALL: i64 = 34;
 const RETURN_VAL: i64 = 35;
@@ -47,6 +60,51 @@ const USE: i64 = 39;
 
 const HALT: i64 = 40;
 
+const LOCAL_ALLOC: i64 = 54;
+
+const STRUCT_NEW: i64 = 55;
+const FIELD_LOAD: i64 = 56;
+const FIELD_STORE: i64 = 57;
+
+const JUMP_TABLE: i64 = 58;
+
+const GLOBAL_LOAD: i64 = 59;
+const GLOBAL_STORE: i64 = 60;
+
+const CLOSURE_NEW: i64 = 61;
+const CALL_INDIRECT: i64 = 62;
+
+const I_ADD_CHECKED: i64 = 63;
+const I_SUB_CHECKED: i64 = 64;
+const I_MUL_CHECKED: i64 = 65;
+const I_DIV_CHECKED: i64 = 66;
+const I_POW_CHECKED: i64 = 67;
+const I_NEG_CHECKED: i64 = 68;
+
+const D_ADD: i64 = 69;
+const D_SUB: i64 = 70;
+const D_MUL: i64 = 71;
+const D_DIV: i64 = 72;
+const D_NEG: i64 = 73;
+
+const B_ADD: i64 = 74;
+const B_SUB: i64 = 75;
+const B_MUL: i64 = 76;
+const B_DIV: i64 = 77;
+const B_MOD: i64 = 78;
+const B_POW: i64 = 79;
+const B_NEG: i64 = 80;
+const B_COMPARE: i64 = 81;
+const TRY_BEGIN: i64 = 82;
+const TRY_END: i64 = 83;
+const THROW_ERROR: i64 = 84;
+const LOCAL_ADDRESS: i64 = 85;
+const ARG_ADDRESS: i64 = 86;
+const REF_LOAD: i64 = 87;
+const REF_STORE: i64 = 88;
+const ARGS_LOAD: i64 = 89;
+const EXIT: i64 = 90;
+
 pub struct Disassembler {
     ip: usize,
     code: Vec<i64>,
@@ -74,45 +132,75 @@ impl Disassembler {
 
             match opcode {
                 POP => {
-                    println!("{}: {} {}", self.ip - 1, "pop", self.code[self.ip]);
+                    println!("{}: pop {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 LOCAL_LOAD => {
-                    println!("{}: {} {}", self.ip - 1, "local_load", self.code[self.ip]);
+                    println!("{}: local_load {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 LOCAL_STORE => {
-                    println!("{}: {} {}", self.ip - 1, "local_store", self.code[self.ip]);
+                    println!("{}: local_store {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 I_CONSTANT => {
-                    println!("{}: {} {}", self.ip - 1, "i_constant", self.code[self.ip]);
-                    self.ip += 1;
-                },
-                I_ADD => println!("{}: {}", self.ip - 1, "i_add"),
-                I_SUB => println!("{}: {}", self.ip - 1, "i_sub"),
-                I_MUL => println!("{}: {}", self.ip - 1, "i_mul"),
-                I_DIV => println!("{}: {}", self.ip - 1, "i_div"),
-                I_EQUAL => println!("{}: {}", self.ip - 1, "i_equal"),
-                I_LESS => println!("{}: {}", self.ip - 1, "i_less"),
-                I_GREATER => println!("{}: {}", self.ip - 1, "i_greater"),
-                I_NOT_EQUAL => println!("{}: {}", self.ip - 1, "i_not_equal"),
-                I_LESS_EQUAL => println!("{}: {}", self.ip - 1, "i_less_equal"),
-                I_GREATER_EQUAL => println!("{}: {}", self.ip - 1, "i_greater_equal"),
+                    println!("{}: i_constant {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                I_ADD => println!("{}: i_add", self.ip - 1),
+                I_SUB => println!("{}: i_sub", self.ip - 1),
+                I_MUL => println!("{}: i_mul", self.ip - 1),
+                I_DIV => println!("{}: i_div", self.ip - 1),
+                I_EQUAL => println!("{}: i_equal", self.ip - 1),
+                I_LESS => println!("{}: i_less", self.ip - 1),
+                I_GREATER => println!("{}: i_greater", self.ip - 1),
+                I_NOT_EQUAL => println!("{}: i_not_equal", self.ip - 1),
+                I_LESS_EQUAL => println!("{}: i_less_equal", self.ip - 1),
+                I_GREATER_EQUAL => println!("{}: i_greater_equal", self.ip - 1),
+                I_MOD => println!("{}: i_mod", self.ip - 1),
+                I_BIT_AND => println!("{}: i_bit_and", self.ip - 1),
+                I_BIT_OR => println!("{}: i_bit_or", self.ip - 1),
+                I_BIT_XOR => println!("{}: i_bit_xor", self.ip - 1),
+                I_SHIFT_LEFT => println!("{}: i_shift_left", self.ip - 1),
+                I_SHIFT_RIGHT => println!("{}: i_shift_right", self.ip - 1),
+                I_POW => println!("{}: i_pow", self.ip - 1),
+                I_NEG => println!("{}: i_neg", self.ip - 1),
+                I_ADD_CHECKED => println!("{}: i_add_checked", self.ip - 1),
+                I_SUB_CHECKED => println!("{}: i_sub_checked", self.ip - 1),
+                I_MUL_CHECKED => println!("{}: i_mul_checked", self.ip - 1),
+                I_DIV_CHECKED => println!("{}: i_div_checked", self.ip - 1),
+                I_POW_CHECKED => println!("{}: i_pow_checked", self.ip - 1),
+                I_NEG_CHECKED => println!("{}: i_neg_checked", self.ip - 1),
+                D_ADD => println!("{}: d_add", self.ip - 1),
+                D_SUB => println!("{}: d_sub", self.ip - 1),
+                D_MUL => println!("{}: d_mul", self.ip - 1),
+                D_DIV => println!("{}: d_div", self.ip - 1),
+                D_NEG => println!("{}: d_neg", self.ip - 1),
+                B_ADD => println!("{}: b_add", self.ip - 1),
+                B_SUB => println!("{}: b_sub", self.ip - 1),
+                B_MUL => println!("{}: b_mul", self.ip - 1),
+                B_DIV => println!("{}: b_div", self.ip - 1),
+                B_MOD => println!("{}: b_mod", self.ip - 1),
+                B_POW => println!("{}: b_pow", self.ip - 1),
+                B_NEG => println!("{}: b_neg", self.ip - 1),
+                B_COMPARE => println!("{}: b_compare", self.ip - 1),
                 F_CONSTANT => {
-                    println!("{}: {} {}", self.ip - 1, "f_constant", f64::from_be_bytes(self.code[self.ip].to_be_bytes()));
-                    self.ip += 1;
-                },
-                F_ADD => println!("{}: {}", self.ip - 1, "f_add"),
-                F_SUB => println!("{}: {}", self.ip - 1, "f_sub"),
-                F_MUL => println!("{}: {}", self.ip - 1, "f_mul"),
-                F_DIV => println!("{}: {}", self.ip - 1, "f_div"),
-                F_EQUAL => println!("{}: {}", self.ip - 1, "f_equal"),
-                F_LESS => println!("{}: {}", self.ip - 1, "f_less"),
-                F_GREATER => println!("{}: {}", self.ip - 1, "f_greater"),
-                F_NOT_EQUAL => println!("{}: {}", self.ip - 1, "f_not_equal"),
-                F_LESS_EQUAL => println!("{}: {}", self.ip - 1, "f_less_equal"),
-                F_GREATER_EQUAL => println!("{}: {}", self.ip - 1, "f_greater_equal"),
+                    println!("{}: f_constant {}", self.ip - 1, f64::from_be_bytes(self.code[self.ip].to_be_bytes()));
+                    self.ip += 1;
+                },
+                F_ADD => println!("{}: f_add", self.ip - 1),
+                F_SUB => println!("{}: f_sub", self.ip - 1),
+                F_MUL => println!("{}: f_mul", self.ip - 1),
+                F_DIV => println!("{}: f_div", self.ip - 1),
+                F_EQUAL => println!("{}: f_equal", self.ip - 1),
+                F_LESS => println!("{}: f_less", self.ip - 1),
+                F_GREATER => println!("{}: f_greater", self.ip - 1),
+                F_NOT_EQUAL => println!("{}: f_not_equal", self.ip - 1),
+                F_LESS_EQUAL => println!("{}: f_less_equal", self.ip - 1),
+                F_GREATER_EQUAL => println!("{}: f_greater_equal", self.ip - 1),
+                F_MOD => println!("{}: f_mod", self.ip - 1),
+                F_POW => println!("{}: f_pow", self.ip - 1),
+                F_NEG => println!("{}: f_neg", self.ip - 1),
                 S_CONSTANT => {
                     let index = self.ip - 1;
                     let mut string: String = String::new();
@@ -125,109 +213,314 @@ impl Disassembler {
                         }
                         self.ip += 1;
                     }
-                    println!("{}: {} \"{}\"", index, "s_constant", string);
+                    println!("{}: s_constant \"{}\"", index, string);
                     self.ip += 1;
                 },
-                S_ADD => println!("{}: {}", self.ip - 1, "s_add"),
-                S_EQUAL => println!("{}: {}", self.ip - 1, "s_equal"),
-                S_NOT_EQUAL => println!("{}: {}", self.ip - 1, "s_not_equal"),
-                OP_AND => println!("{}: {}", self.ip - 1, "op_and"),
-                OP_OR => println!("{}: {}", self.ip - 1, "op_or"),
+                S_ADD => println!("{}: s_add", self.ip - 1),
+                S_EQUAL => println!("{}: s_equal", self.ip - 1),
+                S_NOT_EQUAL => println!("{}: s_not_equal", self.ip - 1),
+                OP_AND => println!("{}: op_and", self.ip - 1),
+                OP_OR => println!("{}: op_or", self.ip - 1),
+                OP_NOT => println!("{}: op_not", self.ip - 1),
                 JUMP_IF_FALSE => {
-                    println!("{}: {} {}", self.ip - 1, "jump_if_false", self.code[self.ip]);
+                    println!("{}: jump_if_false {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                JUMP_IF_TRUE => {
+                    println!("{}: jump_if_true {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 JUMP => {
-                    println!("{}: {} {}", self.ip - 1, "jump", self.code[self.ip]);
+                    println!("{}: jump {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 CALL => {
-                    println!("{}: {} {} {}", self.ip - 1, "call", self.code[self.ip], self.code[self.ip + 1]);
+                    println!("{}: call {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
                     self.ip += 2;
                 },
-                RETURN_VAL => println!("{}: {}", self.ip - 1, "return_val"),
-                RETURN_NON_VAL => println!("{}: {}", self.ip - 1, "return_non_val"),
+                RETURN_VAL => println!("{}: return_val", self.ip - 1),
+                RETURN_NON_VAL => println!("{}: return_non_val", self.ip - 1),
                 ARG_LOAD => {
-                    println!("{}: {} {}", self.ip - 1, "arg_load", self.code[self.ip]);
+                    println!("{}: arg_load {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 ARG_STORE => {
-                    println!("{}: {} {}", self.ip - 1, "arg_store", self.code[self.ip]);
+                    println!("{}: arg_store {}", self.ip - 1, self.code[self.ip]);
                     self.ip += 1;
                 },
                 USE => {
                     if self.code[self.ip] == 0 {
-                        println!("{}: {}", self.ip - 1, "use print");
+                        println!("{}: use print", self.ip - 1);
                         self.ip += 2;
                     } else if self.code[self.ip] == 1 {
-                        println!("{}: {}", self.ip - 1, "use read");
+                        println!("{}: use read", self.ip - 1);
                         self.ip += 2;
                     } else if self.code[self.ip] == 2 {
-                        println!("{}: {}", self.ip - 1, "use string_to_int");
+                        println!("{}: use string_to_int", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 3 {
-                        println!("{}: {}", self.ip - 1, "use string_to_float");
+                        println!("{}: use string_to_float", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 4 {
-                        println!("{}: {}", self.ip - 1, "use int_to_float");
+                        println!("{}: use int_to_float", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 5 {
-                        println!("{}: {}", self.ip - 1, "use int_to_string");
+                        println!("{}: use int_to_string", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 6 {
-                        println!("{}: {}", self.ip - 1, "use float_to_int");
+                        println!("{}: use float_to_int", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 7 {
-                        println!("{}: {}", self.ip - 1, "use float_to_string");
+                        println!("{}: use float_to_string", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 8 {
-                        println!("{}: {}", self.ip - 1, "use get_string_index");
+                        println!("{}: use get_string_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 9 {
-                        println!("{}: {}", self.ip - 1, "use set_string_index");
+                        println!("{}: use set_string_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 10 {
-                        println!("{}: {}", self.ip - 1, "use get_copy_string");
+                        println!("{}: use get_copy_string", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 11 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_new");
+                        println!("{}: use vec_int_new", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 12 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_push");
+                        println!("{}: use vec_int_push", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 13 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_pop");
+                        println!("{}: use vec_int_pop", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 14 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_set_index");
+                        println!("{}: use vec_int_set_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 15 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_get_index");
+                        println!("{}: use vec_int_get_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 16 {
-                        println!("{}: {}", self.ip - 1, "use vec_int_len");
+                        println!("{}: use vec_int_len", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 17 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_new");
+                        println!("{}: use vec_float_new", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 18 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_push");
+                        println!("{}: use vec_float_push", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 19 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_pop");
+                        println!("{}: use vec_float_pop", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 20 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_set_index");
+                        println!("{}: use vec_float_set_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 21 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_get_index");
+                        println!("{}: use vec_float_get_index", self.ip - 1);
                         self.ip += 1;
                     } else if self.code[self.ip] == 22 {
-                        println!("{}: {}", self.ip - 1, "use vec_float_len");
+                        println!("{}: use vec_float_len", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 23 {
+                        println!("{}: use vec_string_new", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 24 {
+                        println!("{}: use vec_string_push", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 25 {
+                        println!("{}: use vec_string_pop", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 26 {
+                        println!("{}: use vec_string_set_index", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 27 {
+                        println!("{}: use vec_string_get_index", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 28 {
+                        println!("{}: use vec_string_len", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 29 {
+                        println!("{}: use map_new", self.ip - 1);
+                        self.ip += 2;
+                    } else if self.code[self.ip] == 30 {
+                        println!("{}: use map_insert", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 31 {
+                        println!("{}: use map_get", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 32 {
+                        println!("{}: use map_remove", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 33 {
+                        println!("{}: use map_contains", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 34 {
+                        println!("{}: use map_len", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 35 {
+                        println!("{}: use map_keys", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 36 {
+                        println!("{}: use wrapping_add", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 37 {
+                        println!("{}: use wrapping_sub", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 38 {
+                        println!("{}: use wrapping_mul", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 39 {
+                        println!("{}: use saturating_add", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 40 {
+                        println!("{}: use saturating_sub", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 41 {
+                        println!("{}: use saturating_mul", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 42 {
+                        println!("{}: use int_to_decimal", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 43 {
+                        println!("{}: use decimal_to_int", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 44 {
+                        println!("{}: use float_to_decimal", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 45 {
+                        println!("{}: use decimal_to_float", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 46 {
+                        println!("{}: use string_to_decimal", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 47 {
+                        println!("{}: use decimal_to_string", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 48 {
+                        println!("{}: use decimal_mul", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 49 {
+                        println!("{}: use decimal_div", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 50 {
+                        println!("{}: use decimal_round", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 51 {
+                        println!("{}: use int_to_bigint", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 52 {
+                        println!("{}: use bigint_to_int", self.ip - 1);
                         self.ip += 1;
+                    } else if self.code[self.ip] == 53 {
+                        println!("{}: use string_to_bigint", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 54 {
+                        println!("{}: use bigint_to_string", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 55 {
+                        println!("{}: use parse_int", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 56 {
+                        println!("{}: use parse_float", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 57 {
+                        println!("{}: use parse_decimal", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 58 {
+                        println!("{}: use parse_bigint", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 59 {
+                        println!("{}: use read_option", self.ip - 1);
+                        self.ip += 2;
+                    } else if self.code[self.ip] == 60 {
+                        println!("{}: use env_get", self.ip - 1);
+                        self.ip += 2;
+                    } else if self.code[self.ip] == 61 {
+                        println!("{}: use file_open", self.ip - 1);
+                        self.ip += 2;
+                    } else if self.code[self.ip] == 62 {
+                        println!("{}: use file_read_line", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 63 {
+                        println!("{}: use file_write", self.ip - 1);
+                        self.ip += 2;
+                    } else if self.code[self.ip] == 64 {
+                        println!("{}: use file_close", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 65 {
+                        println!("{}: use file_exists", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 66 {
+                        println!("{}: use dir_exists", self.ip - 1);
+                        self.ip += 1;
+                    } else if self.code[self.ip] == 67 {
+                        println!("{}: use dir_list", self.ip - 1);
+                        self.ip += 2;
                     }
                 }
-                HALT => println!("{}: {}", self.ip - 1, "halt"),
+                LOCAL_ALLOC => {
+                    println!("{}: local_alloc {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                STRUCT_NEW => {
+                    let num_fields = self.code[self.ip] as usize;
+                    println!("{}: struct_new {} {:?}", self.ip - 1, num_fields, &self.code[self.ip + 1..self.ip + 1 + num_fields]);
+                    self.ip += num_fields + 1;
+                },
+                FIELD_LOAD => {
+                    println!("{}: field_load {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    self.ip += 2;
+                },
+                FIELD_STORE => {
+                    println!("{}: field_store {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                GLOBAL_LOAD => {
+                    println!("{}: global_load {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                GLOBAL_STORE => {
+                    println!("{}: global_store {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                JUMP_TABLE => {
+                    let count = self.code[self.ip + 1] as usize;
+                    println!("{}: jump_table {} {} {} {:?}", self.ip - 1, self.code[self.ip], count, self.code[self.ip + 2], &self.code[self.ip + 3..self.ip + 3 + count]);
+                    self.ip += count + 3;
+                },
+                CLOSURE_NEW => {
+                    println!("{}: closure_new {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    self.ip += 2;
+                },
+                CALL_INDIRECT => {
+                    println!("{}: call_indirect {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                TRY_BEGIN => {
+                    println!("{}: try_begin {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    self.ip += 2;
+                },
+                TRY_END => println!("{}: try_end", self.ip - 1),
+                THROW_ERROR => {
+                    println!("{}: throw_error {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                LOCAL_ADDRESS => {
+                    println!("{}: local_address {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                ARG_ADDRESS => {
+                    println!("{}: arg_address {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                REF_LOAD => {
+                    println!("{}: ref_load {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                REF_STORE => {
+                    println!("{}: ref_store {}", self.ip - 1, self.code[self.ip]);
+                    self.ip += 1;
+                },
+                ARGS_LOAD => println!("{}: args_load", self.ip - 1),
+                EXIT => println!("{}: exit", self.ip - 1),
+                HALT => println!("{}: halt", self.ip - 1),
                 _ => panic!("Bad Opcode: {}", opcode),
             }
         }
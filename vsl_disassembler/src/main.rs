@@ -47,6 +47,6 @@ fn main() {
 		let mut disassembler = disassembler::Disassembler::new(program);
 		disassembler.disassemble();
 	}
-    let time: f64 = start.elapsed().as_micros() as f64 / 1000000 as f64;
+    let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
     println!("Program completed in {} seconds.", time);
 }
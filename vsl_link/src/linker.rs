@@ -0,0 +1,375 @@
+use std::fs;
+use std::io::Write;
+use std::collections::HashMap;
+
+const CALL: i64 = 34;
+const LOCAL_ALLOC: i64 = 54;
+
+// "VSLOBJ" followed by the format version
+const MAGIC: i64 = 0x5653_4c4f_424a_0001;
+
+const FUNCTION: i64 = 0;
+
+struct Symbol {
+    pub kind: i64,
+    pub name: String,
+    pub var_type: String,
+    pub security: i64,
+    pub arg_types: Vec<String>,
+    pub arg_securities: Vec<i64>,
+    pub value: i64,
+    pub refs: Vec<usize>,
+}
+
+struct Object {
+    pub file_name: String,
+    pub init_len: usize,
+    pub init_locals: i64,
+    pub num_globals: i64,
+    pub code: Vec<i64>,
+    pub relocations: Vec<usize>,
+    pub global_relocations: Vec<usize>,
+    pub exports: Vec<Symbol>,
+    pub imports: Vec<Symbol>,
+    // where the init code and the functions of the object start in the program
+    pub init_base: usize,
+    pub fn_base: usize,
+    pub global_base: i64,
+}
+
+impl Object {
+    fn address(&self, location: i64) -> i64 {
+        if (location as usize) < self.init_len {
+            return self.init_base as i64 + location;
+        }
+        self.fn_base as i64 + location - self.init_len as i64
+    }
+}
+
+fn signature_string(symbol: &Symbol) -> String {
+    if symbol.kind == FUNCTION {
+        let mut args: Vec<String> = Vec::new();
+        for i in 0..symbol.arg_types.len() {
+            args.push(format!("{}:{}", symbol.arg_types[i], symbol.arg_securities[i]));
+        }
+        if symbol.var_type == "void" {
+            return format!("fn void {}({})", symbol.name, args.join(", "));
+        }
+        return format!("fn {}:{} {}({})", symbol.var_type, symbol.security, symbol.name, args.join(", "));
+    }
+    format!("static {} {}:{}", symbol.name, symbol.var_type, symbol.security)
+}
+
+fn same_signature(a: &Symbol, b: &Symbol) -> bool {
+    a.kind == b.kind && a.var_type == b.var_type && a.security == b.security
+        && a.arg_types == b.arg_types && a.arg_securities == b.arg_securities
+}
+
+// Reads the words of an object file in order.
+struct Reader {
+    data: Vec<i64>,
+    index: usize,
+    error: bool,
+}
+
+impl Reader {
+    fn next(&mut self) -> i64 {
+        if self.index >= self.data.len() {
+            self.error = true;
+            return 0;
+        }
+        self.index += 1;
+        self.data[self.index - 1]
+    }
+
+    // A count of the items that follow, each at least one word, so a corrupt count can not be larger than
+    // the rest of the file.
+    fn next_count(&mut self) -> usize {
+        let count = self.next();
+        if count < 0 || count as usize > self.data.len() - self.index {
+            self.error = true;
+            return 0;
+        }
+        count as usize
+    }
+
+    fn next_string(&mut self) -> String {
+        let mut string = String::new();
+        loop {
+            let c = self.next();
+            if c == 0 || self.error {
+                break;
+            }
+            string.push(c as u8 as char);
+        }
+        string
+    }
+
+    fn next_symbol(&mut self, is_import: bool) -> Symbol {
+        let kind = self.next();
+        let name = self.next_string();
+        let var_type = self.next_string();
+        let security = self.next();
+        let num_args = self.next_count();
+        let mut arg_types: Vec<String> = Vec::new();
+        let mut arg_securities: Vec<i64> = Vec::new();
+        for _ in 0..num_args {
+            arg_types.push(self.next_string());
+            arg_securities.push(self.next());
+        }
+        let mut value = 0;
+        let mut refs: Vec<usize> = Vec::new();
+        if is_import && kind == FUNCTION {
+            let num_refs = self.next_count();
+            for _ in 0..num_refs {
+                refs.push(self.next() as usize);
+            }
+        } else {
+            value = self.next();
+        }
+        Symbol {
+            kind,
+            name,
+            var_type,
+            security,
+            arg_types,
+            arg_securities,
+            value,
+            refs,
+        }
+    }
+}
+
+pub struct Linker {
+    error: bool,
+    objects: Vec<Object>,
+    code: Vec<i64>,
+}
+
+impl Linker {
+    pub fn new() -> Linker {
+        Linker {
+            error: false,
+            objects: Vec::new(),
+            code: Vec::new(),
+        }
+    }
+
+    pub fn add_object(&mut self, file_name: &String, data: Vec<i64>) {
+        let mut reader = Reader {
+            data,
+            index: 0,
+            error: false,
+        };
+        if reader.next() != MAGIC {
+            println!("'{}' is not a VSL object file.", file_name);
+            self.error = true;
+            return;
+        }
+        let init_len = reader.next() as usize;
+        let init_locals = reader.next();
+        let num_globals = reader.next();
+        let mut code: Vec<i64> = Vec::new();
+        let code_len = reader.next_count();
+        for _ in 0..code_len {
+            code.push(reader.next());
+        }
+        let mut relocations: Vec<usize> = Vec::new();
+        let num_relocations = reader.next_count();
+        for _ in 0..num_relocations {
+            relocations.push(reader.next() as usize);
+        }
+        let mut global_relocations: Vec<usize> = Vec::new();
+        let num_global_relocations = reader.next_count();
+        for _ in 0..num_global_relocations {
+            global_relocations.push(reader.next() as usize);
+        }
+        let mut exports: Vec<Symbol> = Vec::new();
+        let num_exports = reader.next_count();
+        for _ in 0..num_exports {
+            exports.push(reader.next_symbol(false));
+        }
+        let mut imports: Vec<Symbol> = Vec::new();
+        let num_imports = reader.next_count();
+        for _ in 0..num_imports {
+            imports.push(reader.next_symbol(true));
+        }
+        if reader.error {
+            println!("Object file '{}' is truncated.", file_name);
+            self.error = true;
+            return;
+        }
+        let mut locations = relocations.iter().chain(global_relocations.iter()).chain(imports.iter().flat_map(|x| x.refs.iter()));
+        if init_len > code.len() || !locations.all(|x| *x < code.len()) {
+            println!("Object file '{}' is corrupt.", file_name);
+            self.error = true;
+            return;
+        }
+
+        self.objects.push(Object {
+            file_name: file_name.clone(),
+            init_len,
+            init_locals,
+            num_globals,
+            code,
+            relocations,
+            global_relocations,
+            exports,
+            imports,
+            init_base: 0,
+            fn_base: 0,
+            global_base: 0,
+        });
+    }
+
+    // The program initializes the statics of every object in the order given, calls main and is followed by
+    // the functions of every object.
+    pub fn link(&mut self) -> bool {
+        if self.error {
+            return true;
+        }
+
+        // name -> (object, export)
+        let mut symbols: HashMap<String, (usize, usize)> = HashMap::new();
+        for (object_num, object) in self.objects.iter().enumerate() {
+            for (symbol_num, symbol) in object.exports.iter().enumerate() {
+                match symbols.get(&symbol.name) {
+                    Some(x) => {
+                        println!("'{}' is defined in both '{}' and '{}'.", symbol.name, self.objects[x.0].file_name, object.file_name);
+                        self.error = true;
+                    },
+                    None => {
+                        symbols.insert(symbol.name.clone(), (object_num, symbol_num));
+                    },
+                }
+            }
+        }
+        let main = match symbols.get("main") {
+            Some(x) => *x,
+            None => {
+                println!("No main function found in program.");
+                return true;
+            },
+        };
+
+        let mut num_globals: i64 = 0;
+        let mut init_locals: i64 = 0;
+        for object in self.objects.iter_mut() {
+            object.global_base = num_globals;
+            num_globals += object.num_globals;
+            if object.init_locals > init_locals {
+                init_locals = object.init_locals;
+            }
+        }
+        if num_globals > 0 {
+            // there is no frame yet so the hidden locals of the initializers start after three empty slots
+            self.code.push(LOCAL_ALLOC);
+            self.code.push(init_locals + 3);
+        }
+        for object in self.objects.iter_mut() {
+            object.init_base = self.code.len();
+            self.code.extend_from_slice(&object.code[..object.init_len]);
+        }
+        self.code.push(CALL);
+        let main_location = self.code.len();
+        self.code.push(0);
+        // main always has 0 arguments
+        self.code.push(0);
+        for object in self.objects.iter_mut() {
+            object.fn_base = self.code.len();
+            self.code.extend_from_slice(&object.code[object.init_len..]);
+        }
+        let main_object = &self.objects[main.0];
+        self.code[main_location] = main_object.address(main_object.exports[main.1].value);
+
+        for object in self.objects.iter() {
+            for location in object.relocations.iter() {
+                let address = object.address(*location as i64) as usize;
+                self.code[address] = object.address(object.code[*location]);
+            }
+
+            // global slots of imported statics are replaced by the slot of the object defining them
+            let mut global_slots: HashMap<i64, i64> = HashMap::new();
+            for import in object.imports.iter() {
+                let export = match symbols.get(&import.name) {
+                    Some(x) => &self.objects[x.0].exports[x.1],
+                    None => {
+                        println!("Undefined reference to '{}' in '{}'.", import.name, object.file_name);
+                        self.error = true;
+                        continue;
+                    },
+                };
+                let export_object = &self.objects[symbols[&import.name].0];
+                if !same_signature(import, export) {
+                    println!("'{}' expects '{}' but '{}' defines '{}'.", object.file_name, signature_string(import),
+                        export_object.file_name, signature_string(export));
+                    self.error = true;
+                    continue;
+                }
+                if import.kind == FUNCTION {
+                    for location in import.refs.iter() {
+                        let address = object.address(*location as i64) as usize;
+                        self.code[address] = export_object.address(export.value);
+                    }
+                } else {
+                    global_slots.insert(import.value, export_object.global_base + export.value);
+                }
+            }
+            for location in object.global_relocations.iter() {
+                let address = object.address(*location as i64) as usize;
+                let slot = object.code[*location];
+                self.code[address] = match global_slots.get(&slot) {
+                    Some(x) => *x,
+                    None => object.global_base + slot,
+                };
+            }
+        }
+        self.error
+    }
+
+    pub fn output_code(&mut self) {
+        let mut binary_data: Vec<u8> = Vec::new();
+        for chunk in self.code.iter() {
+            binary_data.extend_from_slice(&chunk.to_be_bytes());
+        }
+
+        let mut file = fs::File::create("program").expect("Failed to create program file.");
+        file.write_all(&binary_data).expect("Failed to write to binary file");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(data: Vec<i64>) -> Linker {
+        let mut linker = Linker::new();
+        linker.add_object(&"test.vso".to_string(), data);
+        linker
+    }
+
+    #[test]
+    fn empty_object_is_read() {
+        let linker = add(vec![MAGIC, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(!linker.error);
+        assert_eq!(linker.objects.len(), 1);
+    }
+
+    #[test]
+    fn corrupt_counts_stop_reading() {
+        assert!(add(vec![MAGIC, 0, 0, 0, i64::MAX]).error);
+        assert!(add(vec![MAGIC, 0, 0, 0, -1]).error);
+        assert!(add(vec![MAGIC, 0, 0, 0, 1, 5, i64::MAX]).error);
+        // an import with a huge number of arguments
+        assert!(add(vec![MAGIC, 0, 0, 0, 0, 0, 0, 0, 1, FUNCTION, 0, 0, 0, i64::MAX]).error);
+        assert!(add(vec![MAGIC, 0, 0, 0, 0]).error);
+    }
+
+    #[test]
+    fn locations_outside_the_code_are_corrupt() {
+        assert!(add(vec![MAGIC, 2, 0, 0, 1, 5, 0, 0, 0, 0]).error);
+        assert!(add(vec![MAGIC, 0, 0, 0, 1, 5, 1, 1, 0, 0, 0]).error);
+        assert!(add(vec![MAGIC, 0, 0, 0, 1, 5, 0, 1, 7, 0, 0]).error);
+        assert!(!add(vec![MAGIC, 0, 0, 0, 1, 5, 1, 0, 0, 0, 0]).error);
+    }
+}
@@ -0,0 +1,35 @@
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+mod linker;
+
+fn main() {
+	let start = Instant::now();
+	let args: Vec<String> = env::args().collect();
+	if args.len() >= 2 {
+		let mut linker = linker::Linker::new();
+		for filename in args[1..].iter() {
+			let bytes: Vec<u8> = fs::read(filename).expect("Failed to read object file.");
+
+			let mut data: Vec<i64> = Vec::new();
+			for chunk in bytes.chunks_exact(8) {
+				let mut word: [u8; 8] = [0; 8];
+				word.copy_from_slice(chunk);
+				data.push(i64::from_be_bytes(word));
+			}
+			linker.add_object(filename, data);
+		}
+
+		let error = linker.link();
+		if error {
+			let time: f64 = start.elapsed().as_micros() as f64 / 1_000_000.0;
+			println!("Failed to link program due to errors.");
+			println!("Program completed in {} seconds.", time);
+			return;
+		}
+		linker.output_code();
+	}
+    let time: f64 = start.elapsed().as_micros() as f64 / 1_000_000.0;
+    println!("Program completed in {} seconds.", time);
+}
@@ -0,0 +1,86 @@
+use std::fs;
+
+// A security policy gives levels to data coming from outside of the program. Every line of the policy
+// file is 'kind pattern level', e.g. 'env DB_PASSWORD 90', where a pattern ending in '*' matches every
+// name starting with the rest of it. Lines starting with '#' are comments.
+#[derive(Default)]
+pub struct Policy {
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    kind: String,
+    pattern: String,
+    level: i64,
+}
+
+impl Policy {
+    pub fn new() -> Policy {
+        Policy::default()
+    }
+
+    pub fn load(filename: &String) -> Option<Policy> {
+        let source = match fs::read_to_string(filename) {
+            Ok(x) => x,
+            Err(_error) => {
+                println!("Failed to open policy file '{}'.", filename);
+                return None;
+            },
+        };
+        let mut policy = Policy::new();
+        let mut is_valid = true;
+        for (line_num, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let level = if words.len() == 3 { words[2].parse::<i64>().ok() } else { None };
+            match level {
+                Some(x) if (0..=100).contains(&x) => {
+                    policy.entries.push(Entry {
+                        kind: words[0].to_string(),
+                        pattern: words[1].to_string(),
+                        level: x,
+                    });
+                },
+                _ => {
+                    println!("Expected 'kind pattern level' with a level between 0 and 100 on line {} of policy file '{}'.", line_num + 1, filename);
+                    is_valid = false;
+                },
+            }
+        }
+        if !is_valid {
+            return None;
+        }
+        Some(policy)
+    }
+
+    // The highest level of the entries matching the name, or None when none do.
+    pub fn find(&self, kind: &str, name: &str) -> Option<i64> {
+        let mut level: Option<i64> = None;
+        for entry in self.entries.iter() {
+            if entry.kind != kind {
+                continue;
+            }
+            let is_match = match entry.pattern.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => entry.pattern == name,
+            };
+            if is_match && level.is_none_or(|x| entry.level > x) {
+                level = Some(entry.level);
+            }
+        }
+        level
+    }
+
+    // The level of a name, 0 when no entry matches it.
+    pub fn level(&self, kind: &str, name: &str) -> i64 {
+        self.find(kind, name).unwrap_or(0)
+    }
+
+    // The highest level any name of the kind can have, for names only known at run time.
+    pub fn max_level(&self, kind: &str) -> i64 {
+        self.entries.iter().filter(|x| x.kind == kind).map(|x| x.level).max().unwrap_or(0)
+    }
+}
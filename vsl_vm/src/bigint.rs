@@ -0,0 +1,320 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Sign and magnitude, the magnitude is little endian in base 2^32 without leading zero limbs so zero has no limbs
+// and is never negative.
+#[derive(Clone)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+fn magnitude_compare(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for (x, y) in a.iter().zip(b.iter()).rev() {
+        if x != y {
+            return x.cmp(y);
+        }
+    }
+    Ordering::Equal
+}
+
+fn magnitude_add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result: Vec<u32> = Vec::new();
+    let mut carry: u64 = 0;
+    for i in 0..std::cmp::max(a.len(), b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry != 0 {
+        result.push(carry as u32);
+    }
+    result
+}
+
+// a must not be smaller than b
+fn magnitude_sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result: Vec<u32> = Vec::new();
+    let mut borrow: i64 = 0;
+    for (i, x) in a.iter().enumerate() {
+        let mut difference = *x as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+        result.push(difference as u32);
+    }
+    trim(&mut result);
+    result
+}
+
+fn magnitude_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut result: Vec<u32> = vec![0; a.len() + b.len()];
+    for (i, x) in a.iter().enumerate() {
+        let mut carry: u64 = 0;
+        for (j, y) in b.iter().enumerate() {
+            let product = *x as u64 * *y as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+        result[i + b.len()] = carry as u32;
+    }
+    trim(&mut result);
+    result
+}
+
+// Divides by a single limb, giving the quotient and the remainder.
+fn magnitude_divmod_small(a: &[u32], b: u32) -> (Vec<u32>, u32) {
+    let mut result: Vec<u32> = vec![0; a.len()];
+    let mut remainder: u64 = 0;
+    for i in (0..a.len()).rev() {
+        let current = (remainder << 32) | a[i] as u64;
+        result[i] = (current / b as u64) as u32;
+        remainder = current % b as u64;
+    }
+    trim(&mut result);
+    (result, remainder as u32)
+}
+
+// Long division one bit at a time, b must not be zero.
+fn magnitude_divmod(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient: Vec<u32> = vec![0; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for i in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + bit i of a
+        remainder = magnitude_add(&remainder, &remainder);
+        if (a[i / 32] >> (i % 32)) & 1 == 1 {
+            remainder = magnitude_add(&remainder, &[1]);
+        }
+        if magnitude_compare(&remainder, b) != Ordering::Less {
+            remainder = magnitude_sub(&remainder, b);
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+    trim(&mut quotient);
+    (quotient, remainder)
+}
+
+impl BigInt {
+    fn new(negative: bool, mut limbs: Vec<u32>) -> BigInt {
+        trim(&mut limbs);
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> BigInt {
+        let magnitude = value.unsigned_abs();
+        BigInt::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+        let magnitude = *self.limbs.first().unwrap_or(&0) as i128 | (*self.limbs.get(1).unwrap_or(&0) as i128) << 32;
+        let value = if self.negative { -magnitude } else { magnitude };
+        if value > i64::MAX as i128 || value < i64::MIN as i128 {
+            return None;
+        }
+        Some(value as i64)
+    }
+
+    pub fn parse(string: &str) -> Option<BigInt> {
+        let string = string.trim();
+        let (negative, digits) = match string.strip_prefix('-') {
+            Some(x) => (true, x),
+            None => (false, string),
+        };
+        if digits.is_empty() || !digits.chars().all(|x| x.is_ascii_digit()) {
+            return None;
+        }
+        let mut limbs: Vec<u32> = Vec::new();
+        for c in digits.chars() {
+            limbs = magnitude_mul(&limbs, &[10]);
+            limbs = magnitude_add(&limbs, &[c.to_digit(10)?]);
+        }
+        Some(BigInt::new(negative, limbs))
+    }
+
+    pub fn neg(&self) -> BigInt {
+        BigInt::new(!self.negative, self.limbs.clone())
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, magnitude_add(&self.limbs, &other.limbs));
+        }
+        match magnitude_compare(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, magnitude_sub(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, magnitude_sub(&self.limbs, &other.limbs)),
+        }
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative != other.negative, magnitude_mul(&self.limbs, &other.limbs))
+    }
+
+    // Division truncates toward zero and the remainder takes the sign of the dividend, like int. None if other is zero.
+    pub fn divmod(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.limbs.is_empty() {
+            return None;
+        }
+        let (quotient, remainder) = magnitude_divmod(&self.limbs, &other.limbs);
+        Some((BigInt::new(self.negative != other.negative, quotient), BigInt::new(self.negative, remainder)))
+    }
+
+    pub fn pow(&self, mut exponent: u32) -> BigInt {
+        let mut result = BigInt::from_i64(1);
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    // -1, 0 or 1. Every limb up to the longer length is read without branching on the values so that comparing
+    // a secret takes the same time for any values of the same size.
+    pub fn compare(&self, other: &BigInt) -> i64 {
+        let mut magnitude: i64 = 0;
+        for i in (0..std::cmp::max(self.limbs.len(), other.limbs.len())).rev() {
+            let a = *self.limbs.get(i).unwrap_or(&0) as i64;
+            let b = *other.limbs.get(i).unwrap_or(&0) as i64;
+            let greater = ((b - a) >> 63) & 1;
+            let less = ((a - b) >> 63) & 1;
+            // only the most significant difference counts
+            let undecided = ((magnitude | -magnitude) >> 63) + 1;
+            magnitude += undecided * (greater - less);
+        }
+        let a_negative = self.negative as i64;
+        let b_negative = other.negative as i64;
+        let signs_differ = a_negative ^ b_negative;
+        signs_differ * (b_negative - a_negative) + (1 - signs_differ) * magnitude * (1 - 2 * a_negative)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.limbs.is_empty() {
+            return write!(f, "0");
+        }
+        // nine decimal digits at a time
+        let mut chunks: Vec<u32> = Vec::new();
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            let (quotient, remainder) = magnitude_divmod_small(&limbs, 1_000_000_000);
+            chunks.push(remainder);
+            limbs = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks[chunks.len() - 1])?;
+        for chunk in chunks[..chunks.len() - 1].iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(string: &str) -> BigInt {
+        BigInt::parse(string).unwrap()
+    }
+
+    #[test]
+    fn add_and_sub_carry_across_limbs() {
+        assert_eq!(big("4294967295").add(&big("1")).to_string(), "4294967296");
+        assert_eq!(big("18446744073709551615").add(&big("1")).to_string(), "18446744073709551616");
+        assert_eq!(big("18446744073709551616").sub(&big("1")).to_string(), "18446744073709551615");
+        assert_eq!(big("4294967296").sub(&big("4294967297")).to_string(), "-1");
+        assert_eq!(big("-4294967296").add(&big("4294967295")).to_string(), "-1");
+    }
+
+    #[test]
+    fn mul_carries_across_limbs() {
+        assert_eq!(big("4294967295").mul(&big("4294967295")).to_string(), "18446744065119617025");
+        assert_eq!(big("-18446744073709551616").mul(&big("18446744073709551616")).to_string(), "-340282366920938463463374607431768211456");
+        assert_eq!(big("2").pow(100).to_string(), "1267650600228229401496703205376");
+    }
+
+    #[test]
+    fn zero_is_never_negative() {
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("-0").compare(&big("0")), 0);
+        assert_eq!(big("5").sub(&big("5")).to_string(), "0");
+        assert_eq!(big("0").neg().to_string(), "0");
+        assert_eq!(big("-3").mul(&big("0")).to_string(), "0");
+        let (quotient, remainder) = big("-3").divmod(&big("5")).unwrap();
+        assert_eq!(quotient.to_string(), "0");
+        assert_eq!(remainder.to_string(), "-3");
+        let (_, remainder) = big("-10").divmod(&big("5")).unwrap();
+        assert_eq!(remainder.compare(&big("0")), 0);
+    }
+
+    #[test]
+    fn divmod_truncates_toward_zero() {
+        let cases = [("7", "2", "3", "1"), ("-7", "2", "-3", "-1"), ("7", "-2", "-3", "1"), ("-7", "-2", "3", "-1")];
+        for (a, b, quotient, remainder) in cases.iter() {
+            let (q, r) = big(a).divmod(&big(b)).unwrap();
+            assert_eq!((q.to_string(), r.to_string()), (quotient.to_string(), remainder.to_string()));
+        }
+        let (q, r) = big("2").pow(100).add(&big("5")).divmod(&big("2").pow(64)).unwrap();
+        assert_eq!(q.to_string(), "68719476736");
+        assert_eq!(r.to_string(), "5");
+        assert!(big("1").divmod(&big("0")).is_none());
+        assert!(big("1").divmod(&big("-0")).is_none());
+    }
+
+    #[test]
+    fn to_i64_checks_the_range() {
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(BigInt::from_i64(i64::MAX).to_i64(), Some(i64::MAX));
+        assert_eq!(BigInt::from_i64(i64::MIN).to_string(), "-9223372036854775808");
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(big("-9223372036854775809").to_i64(), None);
+        assert_eq!(big("-4294967296").to_i64(), Some(-4294967296));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_strings() {
+        for string in ["", "-", "--1", "+1", "12a", "1.5", "1 2", "0x10", "1_000"] {
+            assert!(BigInt::parse(string).is_none(), "{}", string);
+        }
+        assert_eq!(big(" 42 ").to_string(), "42");
+        assert_eq!(big("000123").to_string(), "123");
+        assert_eq!(big("1000000000000000000").to_string(), "1000000000000000000");
+    }
+
+    #[test]
+    fn compare_orders_signs_and_limbs() {
+        assert_eq!(big("-1").compare(&big("1")), -1);
+        assert_eq!(big("1").compare(&big("-1")), 1);
+        assert_eq!(big("-5").compare(&big("-3")), -1);
+        assert_eq!(big("4294967296").compare(&big("4294967295")), 1);
+        assert_eq!(big("18446744073709551616").compare(&big("18446744073709551617")), -1);
+        assert_eq!(big("-18446744073709551616").compare(&big("-18446744073709551616")), 0);
+    }
+}
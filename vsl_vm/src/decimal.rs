@@ -0,0 +1,220 @@
+// Decimals are ints holding the value times 10^SCALE, so adding, subtracting and comparing them is exact.
+pub const SCALE: usize = 4;
+pub const ONE: i64 = 10000;
+
+pub enum Rounding {
+    HalfEven,
+    HalfUp,
+    Down,
+    Up,
+    Floor,
+    Ceiling,
+}
+
+pub fn rounding(name: &str) -> Option<Rounding> {
+    match name {
+        "half_even" => Some(Rounding::HalfEven),
+        "half_up" => Some(Rounding::HalfUp),
+        "down" => Some(Rounding::Down),
+        "up" => Some(Rounding::Up),
+        "floor" => Some(Rounding::Floor),
+        "ceiling" => Some(Rounding::Ceiling),
+        _ => None,
+    }
+}
+
+// n / d rounded to an integer. 'half_up' rounds ties away from zero, 'down' and 'up' round toward and away from zero.
+fn round_div(n: i128, d: i128, mode: &Rounding) -> i128 {
+    let quotient = n / d;
+    let remainder = n % d;
+    if remainder == 0 {
+        return quotient;
+    }
+    let sign = if (n < 0) != (d < 0) { -1 } else { 1 };
+    let twice_remainder = remainder.abs() * 2;
+    let away = match mode {
+        Rounding::HalfEven => twice_remainder > d.abs() || (twice_remainder == d.abs() && quotient % 2 != 0),
+        Rounding::HalfUp => twice_remainder >= d.abs(),
+        Rounding::Down => false,
+        Rounding::Up => true,
+        Rounding::Floor => sign < 0,
+        Rounding::Ceiling => sign > 0,
+    };
+    if away {
+        return quotient + sign;
+    }
+    quotient
+}
+
+fn to_i64(value: i128) -> Option<i64> {
+    if value > i64::MAX as i128 || value < i64::MIN as i128 {
+        return None;
+    }
+    Some(value as i64)
+}
+
+pub fn mul(a: i64, b: i64, mode: &Rounding) -> Option<i64> {
+    to_i64(round_div(a as i128 * b as i128, ONE as i128, mode))
+}
+
+// b must not be zero
+pub fn div(a: i64, b: i64, mode: &Rounding) -> Option<i64> {
+    to_i64(round_div(a as i128 * ONE as i128, b as i128, mode))
+}
+
+// Rounds to the given number of decimal places, places at or above the scale leave the value as it is.
+pub fn round(a: i64, places: i64, mode: &Rounding) -> Option<i64> {
+    if places >= SCALE as i64 {
+        return Some(a);
+    }
+    let places = if places < 0 { 0 } else { places as u32 };
+    let step = 10i128.pow(SCALE as u32 - places);
+    to_i64(round_div(a as i128, step, mode) * step)
+}
+
+pub fn from_float(x: f64) -> Option<i64> {
+    let scaled = x * ONE as f64;
+    if !scaled.is_finite() || scaled.abs() >= 9.2e18 {
+        return None;
+    }
+    let floor = scaled.floor();
+    let difference = scaled - floor;
+    let mut result = floor;
+    if difference > 0.5 || (difference == 0.5 && floor % 2.0 != 0.0) {
+        result += 1.0;
+    }
+    Some(result as i64)
+}
+
+// Parses [-]digits[.digits], digits past the scale are rounded half to even.
+pub fn parse(string: &str) -> Option<i64> {
+    let string = string.trim();
+    let (negative, digits) = match string.strip_prefix('-') {
+        Some(x) => (true, x),
+        None => (false, string),
+    };
+    let (whole, fraction) = match digits.split_once('.') {
+        Some((x, y)) => (x, y),
+        None => (digits, ""),
+    };
+    if whole.is_empty() || !whole.chars().all(|x| x.is_ascii_digit()) || !fraction.chars().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    let mut value: i128 = 0;
+    for c in whole.chars().chain(fraction.chars()) {
+        value = value.checked_mul(10)?.checked_add(c.to_digit(10)? as i128)?;
+    }
+    if fraction.len() > SCALE {
+        value = round_div(value, 10i128.checked_pow((fraction.len() - SCALE) as u32)?, &Rounding::HalfEven);
+    } else {
+        value *= 10i128.pow((SCALE - fraction.len()) as u32);
+    }
+    if negative {
+        value = -value;
+    }
+    to_i64(value)
+}
+
+// Prints at least two decimal places and no trailing zeros after that, 12.5 is printed as 12.50.
+pub fn to_string(a: i64) -> String {
+    let value = (a as i128).abs();
+    let mut fraction = format!("{:0width$}", value % ONE as i128, width = SCALE);
+    while fraction.len() > 2 && fraction.ends_with('0') {
+        fraction.pop();
+    }
+    let sign = if a < 0 { "-" } else { "" };
+    format!("{}{}.{}", sign, value / ONE as i128, fraction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rounds_extra_places_half_to_even() {
+        assert_eq!(parse("12.5"), Some(125000));
+        assert_eq!(parse(" 3.25 "), Some(32500));
+        assert_eq!(parse("7"), Some(70000));
+        assert_eq!(parse("0.00015"), Some(2));
+        assert_eq!(parse("0.00025"), Some(2));
+        assert_eq!(parse("-0.00015"), Some(-2));
+    }
+
+    #[test]
+    fn parse_gives_zero_without_a_sign() {
+        assert_eq!(parse("-0"), Some(0));
+        assert_eq!(parse("-0.00005"), Some(0));
+        assert_eq!(to_string(parse("-0.00005").unwrap()), "0.00");
+    }
+
+    #[test]
+    fn parse_rejects_malformed_strings() {
+        for string in ["", "-", ".5", "1.2.3", "1e3", "+1", "--1", "1,5", "12a", "1. 5"] {
+            assert_eq!(parse(string), None, "{}", string);
+        }
+        assert_eq!(parse("99999999999999999999"), None);
+    }
+
+    #[test]
+    fn mul_rounds_with_each_mode() {
+        // 0.0001 * 0.5 and -0.0001 * 0.5 are exactly halfway between two decimals
+        let half = ONE / 2;
+        let expected = [
+            (Rounding::HalfEven, 0, 0),
+            (Rounding::HalfUp, 1, -1),
+            (Rounding::Down, 0, 0),
+            (Rounding::Up, 1, -1),
+            (Rounding::Floor, 0, -1),
+            (Rounding::Ceiling, 1, 0),
+        ];
+        for (mode, positive, negative) in expected.iter() {
+            assert_eq!(mul(1, half, mode), Some(*positive));
+            assert_eq!(mul(-1, half, mode), Some(*negative));
+        }
+        assert_eq!(mul(3, half, &Rounding::HalfEven), Some(2));
+        assert_eq!(mul(i64::MAX, 2 * ONE, &Rounding::HalfEven), None);
+    }
+
+    #[test]
+    fn div_rounds_half_to_even() {
+        assert_eq!(div(ONE, 3 * ONE, &Rounding::HalfEven), Some(3333));
+        assert_eq!(div(2 * ONE, 3 * ONE, &Rounding::HalfEven), Some(6667));
+        assert_eq!(div(-2 * ONE, 3 * ONE, &Rounding::HalfEven), Some(-6667));
+        assert_eq!(div(2 * ONE, -3 * ONE, &Rounding::Down), Some(-6666));
+        assert_eq!(div(1, 2 * ONE, &Rounding::HalfEven), Some(0));
+        assert_eq!(div(3, 2 * ONE, &Rounding::HalfEven), Some(2));
+        assert_eq!(div(i64::MAX, 1, &Rounding::HalfEven), None);
+    }
+
+    #[test]
+    fn round_to_places() {
+        assert_eq!(round(12345, 2, &Rounding::HalfEven), Some(12300));
+        assert_eq!(round(12350, 2, &Rounding::HalfEven), Some(12400));
+        assert_eq!(round(12250, 2, &Rounding::HalfEven), Some(12200));
+        assert_eq!(round(-12250, 2, &Rounding::HalfUp), Some(-12300));
+        assert_eq!(round(12345, 0, &Rounding::Ceiling), Some(20000));
+        assert_eq!(round(12345, -1, &Rounding::Floor), Some(10000));
+        assert_eq!(round(12345, 4, &Rounding::Floor), Some(12345));
+        assert_eq!(round(i64::MAX, 0, &Rounding::Up), None);
+    }
+
+    #[test]
+    fn from_float_rounds_half_to_even() {
+        assert_eq!(from_float(1.5), Some(15000));
+        assert_eq!(from_float(0.00005), Some(0));
+        assert_eq!(from_float(-2.25), Some(-22500));
+        assert_eq!(from_float(f64::NAN), None);
+        assert_eq!(from_float(f64::INFINITY), None);
+        assert_eq!(from_float(1e300), None);
+    }
+
+    #[test]
+    fn to_string_keeps_two_places() {
+        assert_eq!(to_string(125000), "12.50");
+        assert_eq!(to_string(10000), "1.00");
+        assert_eq!(to_string(0), "0.00");
+        assert_eq!(to_string(-5), "-0.0005");
+        assert_eq!(to_string(12345), "1.2345");
+        assert_eq!(to_string(i64::MIN), "-922337203685477.5808");
+    }
+}
@@ -1,12 +1,45 @@
 use std::env;
 use std::fs;
+use std::path::Path;
 use std::time::Instant;
 
+mod bigint;
+mod decimal;
 mod vm;
 
+use vsl_policy::Policy;
+
 fn main() {
 	let start = Instant::now();
-	let args: Vec<String> = env::args().collect();
+	// vsl_vm program [debug] [--policy file] [-- program arguments...]
+	let mut args: Vec<String> = env::args().collect();
+	let mut program_args: Vec<String> = Vec::new();
+	if let Some(i) = args.iter().position(|x| x == "--") {
+		program_args = args.split_off(i + 1);
+		args.pop();
+	}
+	// vsl.policy in the working directory is used when no other policy is given
+	let mut policy_file: Option<String> = None;
+	if let Some(i) = args.iter().position(|x| x == "--policy") {
+		if i + 1 < args.len() {
+			policy_file = Some(args.remove(i + 1));
+		}
+		args.remove(i);
+	} else if Path::new("vsl.policy").exists() {
+		policy_file = Some("vsl.policy".to_string());
+	}
+	let policy = match &policy_file {
+		Some(x) => match Policy::load(x) {
+			Some(x) => x,
+			None => std::process::exit(1),
+		},
+		None => Policy::new(),
+	};
+	if args.len() > 1 {
+		// like argv the program path comes first
+		program_args.insert(0, args[1].clone());
+	}
+	let mut exit_code: i64 = 0;
 	if args.len() == 2 {
 		let filename = &args[1];
 		let bytes: Vec<u8> = fs::read(filename).expect("Failed to read binary file.");
@@ -45,10 +78,13 @@ fn main() {
 		}
 
 		let mut vm = vm::VM::new(program, false);
+		vm.args = program_args;
+		vm.policy = policy;
 		vm.execute();
+		exit_code = vm.exit_code;
 	} else if args.len() == 3 {
 		let filename = &args[1];
-		let debug = *&args[2].parse::<i64>().expect("Could not parse integer.");
+		let debug = args[2].parse::<i64>().expect("Could not parse integer.");
 		let bytes: Vec<u8> = fs::read(filename).expect("Failed to read binary file.");
 		
 		let mut program: Vec<i64> = Vec::new();
@@ -83,15 +119,14 @@ fn main() {
 		        byte_count = 0;
 		    }
 		}
-		let debug_bool: bool;
-		if debug == 0 {
-			debug_bool = false;
-		} else {
-			debug_bool = true;
-		}
+		let debug_bool: bool = debug != 0;
 		let mut vm = vm::VM::new(program, debug_bool);
+		vm.args = program_args;
+		vm.policy = policy;
 		vm.execute();
+		exit_code = vm.exit_code;
 	}
-    let time: f64 = start.elapsed().as_micros() as f64 / 1000000 as f64;
+    let time: f64 = start.elapsed().as_micros() as f64 / 1000000_f64;
     println!("Program completed in {} seconds.", time);
+    std::process::exit(exit_code as i32);
 }
@@ -1,5 +1,13 @@
 use std::io::Write;
+use std::io::BufRead;
 use std::io;
+use std::fs;
+use std::path::Path;
+use std::collections::BTreeMap;
+
+use crate::decimal;
+use crate::bigint::BigInt;
+use vsl_policy::Policy;
 
 const POP: i64 = 1;
 const LOCAL_LOAD: i64 = 2;
@@ -16,6 +24,14 @@ const I_GREATER: i64 = 11;
 const I_NOT_EQUAL: i64 = 12;
 const I_LESS_EQUAL: i64 = 13;
 const I_GREATER_EQUAL: i64 = 14;
+const I_MOD: i64 = 43;
+const I_BIT_AND: i64 = 45;
+const I_BIT_OR: i64 = 46;
+const I_BIT_XOR: i64 = 47;
+const I_SHIFT_LEFT: i64 = 48;
+const I_SHIFT_RIGHT: i64 = 49;
+const I_POW: i64 = 50;
+const I_NEG: i64 = 52;
 
 const F_CONSTANT: i64 = 15;
 const F_ADD: i64 = 16;
@@ -28,6 +44,9 @@ const F_GREATER: i64 = 22;
 const F_NOT_EQUAL: i64 = 23;
 const F_LESS_EQUAL: i64 = 24;
 const F_GREATER_EQUAL: i64 = 25;
+const F_MOD: i64 = 44;
+const F_POW: i64 = 51;
+const F_NEG: i64 = 53;
 
 const S_CONSTANT: i64 = 26;
 const S_ADD: i64 = 27;
@@ -36,9 +55,11 @@ const S_NOT_EQUAL: i64 = 29;
 
 const OP_AND: i64 = 30;
 const OP_OR: i64 = 31;
+const OP_NOT: i64 = 41;
 
 const JUMP_IF_FALSE: i64 = 32;
 const JUMP: i64 = 33;
+const JUMP_IF_TRUE: i64 = 42;
 
 const CALL: i64 = 34;
 const RETURN_VAL: i64 = 35;
@@ -50,12 +71,68 @@ const USE: i64 = 39;
 
 const HALT: i64 = 40;
 
+const LOCAL_ALLOC: i64 = 54;
+
+const STRUCT_NEW: i64 = 55;
+const FIELD_LOAD: i64 = 56;
+const FIELD_STORE: i64 = 57;
+
+const JUMP_TABLE: i64 = 58;
+
+const GLOBAL_LOAD: i64 = 59;
+const GLOBAL_STORE: i64 = 60;
+
+const CLOSURE_NEW: i64 = 61;
+const CALL_INDIRECT: i64 = 62;
+
+// integer arithmetic of checked functions, overflow stops the program
+const I_ADD_CHECKED: i64 = 63;
+const I_SUB_CHECKED: i64 = 64;
+const I_MUL_CHECKED: i64 = 65;
+const I_DIV_CHECKED: i64 = 66;
+const I_POW_CHECKED: i64 = 67;
+const I_NEG_CHECKED: i64 = 68;
+
+// decimal arithmetic always stops the program on overflow, comparisons use the int opcodes
+const D_ADD: i64 = 69;
+const D_SUB: i64 = 70;
+const D_MUL: i64 = 71;
+const D_DIV: i64 = 72;
+const D_NEG: i64 = 73;
+
+// bigint operands and results are locations in bigint_constants, b_compare pushes -1, 0 or 1
+const B_ADD: i64 = 74;
+const B_SUB: i64 = 75;
+const B_MUL: i64 = 76;
+const B_DIV: i64 = 77;
+const B_MOD: i64 = 78;
+const B_POW: i64 = 79;
+const B_NEG: i64 = 80;
+const B_COMPARE: i64 = 81;
+
+// try_begin pushes a handler with its catch address and security level, throw_error pops the message and unwinds to it
+const TRY_BEGIN: i64 = 82;
+const TRY_END: i64 = 83;
+const THROW_ERROR: i64 = 84;
+
+// references are the stack location of a variable, ref_load and ref_store go through the one held by an argument
+const LOCAL_ADDRESS: i64 = 85;
+const ARG_ADDRESS: i64 = 86;
+const REF_LOAD: i64 = 87;
+const REF_STORE: i64 = 88;
+
+const ARGS_LOAD: i64 = 89;
+const EXIT: i64 = 90;
+
 //----------------------------------------------------------------------------------------------------
 
 // data types
 const INT: i64 = 0;
 const FLOAT: i64 = 1;
 const STRING: i64 = 2;
+const BOOL: i64 = 8;
+const DECIMAL: i64 = 9;
+const BIGINT: i64 = 10;
 
 // functions
 const PRINT: i64 = 0;
@@ -87,15 +164,110 @@ const VEC_STRING_POP: i64 = 25;
 const VEC_STRING_SET_INDEX: i64 = 26;
 const VEC_STRING_GET_INDEX: i64 = 27;
 const VEC_STRING_LEN: i64 = 28;
+const MAP_NEW: i64 = 29;
+const MAP_INSERT: i64 = 30;
+const MAP_GET: i64 = 31;
+const MAP_REMOVE: i64 = 32;
+const MAP_CONTAINS: i64 = 33;
+const MAP_LEN: i64 = 34;
+const MAP_KEYS: i64 = 35;
+const WRAPPING_ADD: i64 = 36;
+const WRAPPING_SUB: i64 = 37;
+const WRAPPING_MUL: i64 = 38;
+const SATURATING_ADD: i64 = 39;
+const SATURATING_SUB: i64 = 40;
+const SATURATING_MUL: i64 = 41;
+const INT_TO_DECIMAL: i64 = 42;
+const DECIMAL_TO_INT: i64 = 43;
+const FLOAT_TO_DECIMAL: i64 = 44;
+const DECIMAL_TO_FLOAT: i64 = 45;
+const STRING_TO_DECIMAL: i64 = 46;
+const DECIMAL_TO_STRING: i64 = 47;
+const DECIMAL_MUL: i64 = 48;
+const DECIMAL_DIV: i64 = 49;
+const DECIMAL_ROUND: i64 = 50;
+const INT_TO_BIGINT: i64 = 51;
+const BIGINT_TO_INT: i64 = 52;
+const STRING_TO_BIGINT: i64 = 53;
+const BIGINT_TO_STRING: i64 = 54;
+const PARSE_INT: i64 = 55;
+const PARSE_FLOAT: i64 = 56;
+const PARSE_DECIMAL: i64 = 57;
+const PARSE_BIGINT: i64 = 58;
+const READ_OPTION: i64 = 59;
+const ENV_GET: i64 = 60;
+const FILE_OPEN: i64 = 61;
+const FILE_READ_LINE: i64 = 62;
+const FILE_WRITE: i64 = 63;
+const FILE_CLOSE: i64 = 64;
+const FILE_EXISTS: i64 = 65;
+const DIR_EXISTS: i64 = 66;
+const DIR_LIST: i64 = 67;
 
 
  //---------------------------------------------------------------------------------------------------
 
+// map keys are kept in order so that map_keys is deterministic
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+enum MapKey {
+    Int(i64),
+    String(String),
+}
+
+// A try block that has not ended, a throw returns to the frame and stack height it was entered at.
+struct Handler {
+    address: usize,
+    security: i64,
+    fp: usize,
+    sp: usize,
+}
+
+// A file opened for reading or for writing, with the security level of the data it may hold.
+struct OpenFile {
+    reader: Option<io::BufReader<fs::File>>,
+    writer: Option<fs::File>,
+    security: i64,
+}
+
+// Integer power for exponents of any size, wrapping around on overflow.
+fn int_pow_wrapping(a: i64, b: u64) -> i64 {
+    let mut result: i64 = 1;
+    let mut base = a;
+    let mut exponent = b;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.wrapping_mul(base);
+        }
+        base = base.wrapping_mul(base);
+        exponent >>= 1;
+    }
+    result
+}
+
+// Integer power, None on overflow. Bases other than -1, 0 and 1 overflow before the exponent reaches 64.
+fn int_pow_checked(a: i64, b: u64) -> Option<i64> {
+    if (-1..=1).contains(&a) {
+        return Some(int_pow_wrapping(a, b));
+    }
+    if b >= 64 {
+        return None;
+    }
+    a.checked_pow(b as u32)
+}
+
 pub struct VM {
     string_constants: Vec<String>,
+    bigint_constants: Vec<BigInt>,
     vec_int_constants: Vec<Vec<i64>>,
     vec_float_constants: Vec<Vec<f64>>,
     vec_string_constants: Vec<Vec<String>>,
+    struct_constants: Vec<Vec<i64>>,
+    struct_securities: Vec<Vec<i64>>,
+    map_constants: Vec<BTreeMap<MapKey, i64>>,
+    map_key_types: Vec<i64>,
+    globals: Vec<i64>,
+    handlers: Vec<Handler>,
+    files: Vec<Option<OpenFile>>,
     stack: Vec<i64>,
     code: Vec<i64>,
     ip: usize,
@@ -103,22 +275,37 @@ pub struct VM {
     sp: usize,
     debug: bool,
     pub halt: bool,
+    // arguments given to main and the exit code returned by it
+    pub args: Vec<String>,
+    pub exit_code: i64,
+    pub policy: Policy,
 }
 
 impl VM {
     pub fn new(program: Vec<i64>, debug: bool) -> VM {
         let mut vm = VM {
             string_constants: Vec::new(),
+            bigint_constants: Vec::new(),
             vec_int_constants: Vec::new(),
             vec_float_constants: Vec::new(),
             vec_string_constants: Vec::new(),
+            struct_constants: Vec::new(),
+            struct_securities: Vec::new(),
+            map_constants: Vec::new(),
+            map_key_types: Vec::new(),
+            globals: Vec::new(),
+            handlers: Vec::new(),
+            files: Vec::new(),
             stack: Vec::new(),
             code: Vec::new(),
             ip: 0,
             fp: 0,
             sp: 0,
-            debug: debug,
+            debug,
             halt: false,
+            args: Vec::new(),
+            exit_code: 0,
+            policy: Policy::new(),
         };
         
         for chunck in program {
@@ -128,14 +315,14 @@ impl VM {
     }
 
     pub fn execute(&mut self) {
-        while self.halt == false {
+        while !self.halt {
             let opcode = self.code[self.ip];
             self.ip += 1;
 
             match opcode {
                 POP => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "pop", self.code[self.ip]);
+                        println!("{}: pop {}", self.ip - 1, self.code[self.ip]);
                     }
                     let index = self.code[self.ip] as usize + self.fp + 3;
                     self.stack.remove(index);
@@ -144,7 +331,7 @@ impl VM {
                 },
                 LOCAL_LOAD => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "local_load", self.code[self.ip]);
+                        println!("{}: local_load {}", self.ip - 1, self.code[self.ip]);
                     }
                     let index: usize = self.code[self.ip] as usize + self.fp + 3;
                     self.stack.push(self.stack[index]);
@@ -153,7 +340,7 @@ impl VM {
                 },
                 LOCAL_STORE => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "local_store", self.code[self.ip]);
+                        println!("{}: local_store {}", self.ip - 1, self.code[self.ip]);
                     }
                     let index: usize = self.code[self.ip] as usize + self.fp + 3;
                     let data = self.stack[self.sp - 1];
@@ -166,7 +353,7 @@ impl VM {
                 },
                 I_CONSTANT => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "i_constant", self.code[self.ip]);
+                        println!("{}: i_constant {}", self.ip - 1, self.code[self.ip]);
                     }
                     self.stack.push(self.code[self.ip]);
                     self.ip += 1;
@@ -174,7 +361,7 @@ impl VM {
                 },
                 I_ADD => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_add");
+                        println!("{}: i_add", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -186,7 +373,7 @@ impl VM {
                 },
                 I_SUB => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_sub");
+                        println!("{}: i_sub", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -198,7 +385,7 @@ impl VM {
                 },
                 I_MUL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_mul");
+                        println!("{}: i_mul", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -210,19 +397,23 @@ impl VM {
                 },
                 I_DIV => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_div");
+                        println!("{}: i_div", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
                     self.stack.pop();
                     self.stack.pop();
+                    if b == 0 {
+                        self.throw("Attempted to divide by zero.".to_string(), 0);
+                        continue;
+                    }
                     let (result, _did_overflow) = a.overflowing_div(b);
                     self.stack.push(result);
                     self.sp -= 1;
                 },
                 I_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_equal");
+                        println!("{}: i_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -237,7 +428,7 @@ impl VM {
                 },
                 I_LESS => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_less");
+                        println!("{}: i_less", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -252,7 +443,7 @@ impl VM {
                 },
                 I_GREATER => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_greater");
+                        println!("{}: i_greater", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -267,7 +458,7 @@ impl VM {
                 },
                 I_NOT_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_not_equal");
+                        println!("{}: i_not_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -282,7 +473,7 @@ impl VM {
                 },
                 I_LESS_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_less_equal");
+                        println!("{}: i_less_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -297,7 +488,7 @@ impl VM {
                 },
                 I_GREATER_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_greater_equal");
+                        println!("{}: i_greater_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -310,9 +501,388 @@ impl VM {
                     }
                     self.sp -= 1;
                 },
+                I_MOD => {
+                    if self.debug {
+                        println!("{}: i_mod", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    if b == 0 {
+                        self.throw("Attempted to take the remainder of a division by zero.".to_string(), 0);
+                        continue;
+                    }
+                    let (result, _did_overflow) = a.overflowing_rem(b);
+                    self.stack.push(result);
+                    self.sp -= 1;
+                },
+                I_BIT_AND => {
+                    if self.debug {
+                        println!("{}: i_bit_and", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(a & b);
+                    self.sp -= 1;
+                },
+                I_BIT_OR => {
+                    if self.debug {
+                        println!("{}: i_bit_or", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(a | b);
+                    self.sp -= 1;
+                },
+                I_BIT_XOR => {
+                    if self.debug {
+                        println!("{}: i_bit_xor", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(a ^ b);
+                    self.sp -= 1;
+                },
+                I_SHIFT_LEFT => {
+                    if self.debug {
+                        println!("{}: i_shift_left", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(a.wrapping_shl(b as u32));
+                    self.sp -= 1;
+                },
+                I_SHIFT_RIGHT => {
+                    if self.debug {
+                        println!("{}: i_shift_right", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(a.wrapping_shr(b as u32));
+                    self.sp -= 1;
+                },
+                I_POW => {
+                    if self.debug {
+                        println!("{}: i_pow", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    let result: i64;
+                    if b >= 0 {
+                        result = int_pow_wrapping(a, b as u64);
+                    } else if a == 0 {
+                        self.throw("Attempted to raise zero to a negative power.".to_string(), 0);
+                        continue;
+                    } else if a == 1 || (a == -1 && b % 2 == 0) {
+                        result = 1;
+                    } else if a == -1 {
+                        result = -1;
+                    } else {
+                        // integer power with a negative exponent truncates toward zero
+                        result = 0;
+                    }
+                    self.stack.push(result);
+                    self.sp -= 1;
+                },
+                I_NEG => {
+                    if self.debug {
+                        println!("{}: i_neg", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.push(a.wrapping_neg());
+                },
+                I_ADD_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_add_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    // the operands are not printed since they may be secret
+                    match a.checked_add(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Integer overflow in addition.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                I_SUB_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_sub_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    match a.checked_sub(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Integer overflow in subtraction.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                I_MUL_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_mul_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    match a.checked_mul(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Integer overflow in multiplication.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                I_DIV_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_div_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    if b == 0 {
+                        self.throw("Attempted to divide by zero.".to_string(), 0);
+                        continue;
+                    }
+                    match a.checked_div(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Integer overflow in division.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                I_POW_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_pow_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    let result: i64;
+                    if b >= 0 {
+                        result = match int_pow_checked(a, b as u64) {
+                            Some(x) => x,
+                            None => {
+                                self.throw("Integer overflow in power.".to_string(), 0);
+                                continue;
+                            },
+                        };
+                    } else if a == 0 {
+                        self.throw("Attempted to raise zero to a negative power.".to_string(), 0);
+                        continue;
+                    } else if a == 1 || (a == -1 && b % 2 == 0) {
+                        result = 1;
+                    } else if a == -1 {
+                        result = -1;
+                    } else {
+                        result = 0;
+                    }
+                    self.stack.push(result);
+                    self.sp -= 1;
+                },
+                I_NEG_CHECKED => {
+                    if self.debug {
+                        println!("{}: i_neg_checked", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    match a.checked_neg() {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Integer overflow in negation.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                },
+                D_ADD => {
+                    if self.debug {
+                        println!("{}: d_add", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    match a.checked_add(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Decimal overflow in addition.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                D_SUB => {
+                    if self.debug {
+                        println!("{}: d_sub", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    match a.checked_sub(b) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Decimal overflow in subtraction.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                D_MUL => {
+                    if self.debug {
+                        println!("{}: d_mul", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    match decimal::mul(a, b, &decimal::Rounding::HalfEven) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Decimal overflow in multiplication.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                D_DIV => {
+                    if self.debug {
+                        println!("{}: d_div", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2];
+                    let b = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    if b == 0 {
+                        self.throw("Attempted to divide by zero.".to_string(), 0);
+                        continue;
+                    }
+                    match decimal::div(a, b, &decimal::Rounding::HalfEven) {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Decimal overflow in division.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                    self.sp -= 1;
+                },
+                D_NEG => {
+                    if self.debug {
+                        println!("{}: d_neg", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    match a.checked_neg() {
+                        Some(x) => self.stack.push(x),
+                        None => {
+                            self.throw("Decimal overflow in negation.".to_string(), 0);
+                            continue;
+                        },
+                    }
+                },
+                B_ADD | B_SUB | B_MUL | B_DIV | B_MOD => {
+                    if self.debug {
+                        let name = match opcode {
+                            B_ADD => "b_add",
+                            B_SUB => "b_sub",
+                            B_MUL => "b_mul",
+                            B_DIV => "b_div",
+                            _ => "b_mod",
+                        };
+                        println!("{}: {}", self.ip - 1, name);
+                    }
+                    let a = self.bigint_constants[self.stack[self.sp - 2] as usize].clone();
+                    let b = self.bigint_constants[self.stack[self.sp - 1] as usize].clone();
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.sp -= 2;
+                    let result = match opcode {
+                        B_ADD => a.add(&b),
+                        B_SUB => a.sub(&b),
+                        B_MUL => a.mul(&b),
+                        _ => {
+                            let (quotient, remainder) = match a.divmod(&b) {
+                                Some(x) => x,
+                                None => {
+                                    self.throw("Attempted to divide by zero.".to_string(), 0);
+                                    continue;
+                                },
+                            };
+                            if opcode == B_DIV { quotient } else { remainder }
+                        },
+                    };
+                    self.push_bigint(result);
+                },
+                B_POW => {
+                    if self.debug {
+                        println!("{}: b_pow", self.ip - 1);
+                    }
+                    let a = self.bigint_constants[self.stack[self.sp - 2] as usize].clone();
+                    let b = self.bigint_constants[self.stack[self.sp - 1] as usize].to_i64();
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.sp -= 2;
+                    let exponent = match b {
+                        Some(x) if x >= 0 && x <= u32::MAX as i64 => x as u32,
+                        _ => {
+                            self.throw(format!("Bigint exponent must be between 0 and {}.", u32::MAX), 0);
+                            continue;
+                        },
+                    };
+                    self.push_bigint(a.pow(exponent));
+                },
+                B_NEG => {
+                    if self.debug {
+                        println!("{}: b_neg", self.ip - 1);
+                    }
+                    let a = self.bigint_constants[self.stack[self.sp - 1] as usize].neg();
+                    self.stack.pop();
+                    self.sp -= 1;
+                    self.push_bigint(a);
+                },
+                B_COMPARE => {
+                    if self.debug {
+                        println!("{}: b_compare", self.ip - 1);
+                    }
+                    let a = self.stack[self.sp - 2] as usize;
+                    let b = self.stack[self.sp - 1] as usize;
+                    let result = self.bigint_constants[a].compare(&self.bigint_constants[b]);
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(result);
+                    self.sp -= 1;
+                },
                 F_CONSTANT => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "f_constant", f64::from_be_bytes(self.code[self.ip].to_be_bytes()));
+                        println!("{}: f_constant {}", self.ip - 1, f64::from_be_bytes(self.code[self.ip].to_be_bytes()));
                     }
                     self.stack.push(self.code[self.ip]);
                     self.ip += 1;
@@ -320,7 +890,7 @@ impl VM {
                 },
                 F_ADD => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_add");
+                        println!("{}: f_add", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -335,7 +905,7 @@ impl VM {
                 },
                 F_SUB => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_sub");
+                        println!("{}: f_sub", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -350,7 +920,7 @@ impl VM {
                 },
                 F_MUL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_mul");
+                        println!("{}: f_mul", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -365,7 +935,7 @@ impl VM {
                 },
                 F_DIV => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_div");
+                        println!("{}: f_div", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -380,7 +950,7 @@ impl VM {
                 },
                 F_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_equal");
+                        println!("{}: f_equal", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -388,9 +958,8 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a == b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
@@ -398,7 +967,7 @@ impl VM {
                 },
                 F_LESS => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_less");
+                        println!("{}: f_less", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -406,9 +975,8 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a < b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
@@ -416,7 +984,7 @@ impl VM {
                 },
                 F_GREATER => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_greater");
+                        println!("{}: f_greater", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -424,9 +992,8 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a > b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
@@ -434,7 +1001,7 @@ impl VM {
                 },
                 F_NOT_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_not_equal");
+                        println!("{}: f_not_equal", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -442,9 +1009,8 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a != b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
@@ -452,7 +1018,7 @@ impl VM {
                 },
                 F_LESS_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_less_equal");
+                        println!("{}: f_less_equal", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -460,9 +1026,8 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a <= b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
@@ -470,7 +1035,7 @@ impl VM {
                 },
                 F_GREATER_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "f_greater_equal");
+                        println!("{}: f_greater_equal", self.ip - 1);
                     }
                     let a_bytes = self.stack[self.sp - 2].to_be_bytes();
                     let b_bytes = self.stack[self.sp - 1].to_be_bytes();
@@ -478,17 +1043,54 @@ impl VM {
                     let b = f64::from_be_bytes(b_bytes);
                     self.stack.pop();
                     self.stack.pop();
-                    let float_true: f64 = 1.0;
                     if a >= b {
-                        self.stack.push(i64::from_be_bytes(float_true.to_be_bytes()));
+                        self.stack.push(1);
                     } else {
                         self.stack.push(0);
                     }
                     self.sp -= 1;
                 },
+                F_MOD => {
+                    if self.debug {
+                        println!("{}: f_mod", self.ip - 1);
+                    }
+                    let a_bytes = self.stack[self.sp - 2].to_be_bytes();
+                    let b_bytes = self.stack[self.sp - 1].to_be_bytes();
+                    let a = f64::from_be_bytes(a_bytes);
+                    let b = f64::from_be_bytes(b_bytes);
+                    let answer_bytes = (a % b).to_be_bytes();
+                    let answer = i64::from_be_bytes(answer_bytes);
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(answer);
+                    self.sp -= 1;
+                },
+                F_POW => {
+                    if self.debug {
+                        println!("{}: f_pow", self.ip - 1);
+                    }
+                    let a_bytes = self.stack[self.sp - 2].to_be_bytes();
+                    let b_bytes = self.stack[self.sp - 1].to_be_bytes();
+                    let a = f64::from_be_bytes(a_bytes);
+                    let b = f64::from_be_bytes(b_bytes);
+                    let answer_bytes = (a.powf(b)).to_be_bytes();
+                    let answer = i64::from_be_bytes(answer_bytes);
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.stack.push(answer);
+                    self.sp -= 1;
+                },
+                F_NEG => {
+                    if self.debug {
+                        println!("{}: f_neg", self.ip - 1);
+                    }
+                    let a = f64::from_be_bytes(self.stack[self.sp - 1].to_be_bytes());
+                    self.stack.pop();
+                    self.stack.push(i64::from_be_bytes((-a).to_be_bytes()));
+                },
                 S_CONSTANT => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "s_constant");
+                        println!("{}: s_constant", self.ip - 1);
                     }
                     let mut string: String = String::new();
                     while self.code[self.ip] != 0 && self.code[self.ip] < 128 {
@@ -503,7 +1105,7 @@ impl VM {
                 },
                 S_ADD => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "s_add");
+                        println!("{}: s_add", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2] as usize;
                     let b = self.stack[self.sp - 1] as usize;
@@ -517,7 +1119,7 @@ impl VM {
                 },
                 S_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "s_equal");
+                        println!("{}: s_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2] as usize;
                     let b = self.stack[self.sp - 1] as usize;
@@ -532,7 +1134,7 @@ impl VM {
                 },
                 S_NOT_EQUAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "s_not_equal");
+                        println!("{}: s_not_equal", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2] as usize;
                     let b = self.stack[self.sp - 1] as usize;
@@ -547,7 +1149,7 @@ impl VM {
                 },
                 OP_AND => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_and");
+                        println!("{}: i_and", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -562,7 +1164,7 @@ impl VM {
                 },
                 OP_OR => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "i_or");
+                        println!("{}: i_or", self.ip - 1);
                     }
                     let a = self.stack[self.sp - 2];
                     let b = self.stack[self.sp - 1];
@@ -575,30 +1177,98 @@ impl VM {
                     }
                     self.sp -= 1;
                 },
-                JUMP_IF_FALSE => {
+                OP_NOT => {
                     if self.debug {
-                        println!("{}: {} {} {}", self.ip - 1, "jump_if_false", self.stack[self.sp - 1], self.code[self.ip]);
+                        println!("{}: op_not", self.ip - 1);
                     }
-                    let location = self.code[self.ip] as usize;
-                    let boolean_value = self.stack[self.sp - 1];
+                    let a = self.stack[self.sp - 1];
                     self.stack.pop();
-                    if boolean_value == 0 {
-                        self.ip = location;
+                    if a == 0 {
+                        self.stack.push(1);
                     } else {
-                        self.ip += 1;
+                        self.stack.push(0);
+                    }
+                },
+                JUMP_IF_FALSE => {
+                    if self.debug {
+                        println!("{}: jump_if_false {} {}", self.ip - 1, self.stack[self.sp - 1], self.code[self.ip]);
+                    }
+                    let location = self.code[self.ip] as usize;
+                    let boolean_value = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    if boolean_value == 0 {
+                        self.ip = location;
+                    } else {
+                        self.ip += 1;
                     }
                     self.sp -= 1;
                 },
+                JUMP_IF_TRUE => {
+                    if self.debug {
+                        println!("{}: jump_if_true {} {}", self.ip - 1, self.stack[self.sp - 1], self.code[self.ip]);
+                    }
+                    let location = self.code[self.ip] as usize;
+                    let boolean_value = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    if boolean_value != 0 {
+                        self.ip = location;
+                    } else {
+                        self.ip += 1;
+                    }
+                    self.sp -= 1;
+                },
+                GLOBAL_LOAD => {
+                    if self.debug {
+                        println!("{}: global_load {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let index = self.code[self.ip] as usize;
+                    self.stack.push(self.globals[index]);
+                    self.ip += 1;
+                    self.sp += 1;
+                },
+                GLOBAL_STORE => {
+                    if self.debug {
+                        println!("{}: global_store {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let index = self.code[self.ip] as usize;
+                    if index >= self.globals.len() {
+                        self.globals.resize(index + 1, 0);
+                    }
+                    self.globals[index] = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.sp -= 1;
+                    self.ip += 1;
+                },
+                JUMP_TABLE => {
+                    if self.debug {
+                        println!("{}: jump_table {} {} {}", self.ip - 1, self.stack[self.sp - 1], self.code[self.ip], self.code[self.ip + 1]);
+                    }
+                    // jump_table min count default_location location_0 ... location_count-1
+                    let value = self.stack[self.sp - 1];
+                    let min = self.code[self.ip];
+                    let count = self.code[self.ip + 1];
+                    self.stack.pop();
+                    self.sp -= 1;
+                    // values too far from min to subtract take the default arm
+                    match value.checked_sub(min) {
+                        Some(offset) if offset >= 0 && offset < count => {
+                            self.ip = self.code[self.ip + 3 + offset as usize] as usize;
+                        },
+                        _ => {
+                            self.ip = self.code[self.ip + 2] as usize;
+                        },
+                    }
+                },
                 JUMP => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "jump", self.code[self.ip]);
+                        println!("{}: jump {}", self.ip - 1, self.code[self.ip]);
                     }
                     let location = self.code[self.ip] as usize;
                     self.ip = location;
                 },
                 CALL => {
                     if self.debug {
-                        println!("{}: {} {} {}", self.ip - 1, "call", self.code[self.ip], self.code[self.ip + 1]);
+                        println!("{}: call {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
                     }
                     let address: i64 = self.code[self.ip];
                     let nargs: i64 = self.code[self.ip + 1];
@@ -615,12 +1285,12 @@ impl VM {
                 },
                 RETURN_VAL => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "return_val", self.stack[self.sp - 1]);
+                        println!("{}: return_val {}", self.ip - 1, self.stack[self.sp - 1]);
                     }
                     let return_value: i64 = self.stack[self.sp - 1];
-                    let fp: usize = self.stack[self.sp - 2] as usize;
-                    let nargs: usize = self.stack[self.sp - 3] as usize;
-                    let return_address: usize = self.stack[self.sp - 4] as usize;
+                    let fp: usize = self.stack[self.fp + 2] as usize;
+                    let nargs: usize = self.stack[self.fp + 1] as usize;
+                    let return_address: usize = self.stack[self.fp] as usize;
                     self.sp = self.fp - nargs;
                     self.fp = fp;
                     self.ip = return_address;
@@ -633,11 +1303,11 @@ impl VM {
                 },
                 RETURN_NON_VAL => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "return_non_val");
+                        println!("{}: return_non_val", self.ip - 1);
                     }
-                    let fp: usize = self.stack[self.sp - 1] as usize;
-                    let nargs: usize = self.stack[self.sp - 2] as usize;
-                    let return_address: usize = self.stack[self.sp - 3] as usize;
+                    let fp: usize = self.stack[self.fp + 2] as usize;
+                    let nargs: usize = self.stack[self.fp + 1] as usize;
+                    let return_address: usize = self.stack[self.fp] as usize;
                     self.sp = self.fp - nargs;
                     self.fp = fp;
                     self.ip = return_address;
@@ -648,7 +1318,7 @@ impl VM {
                 },
                 ARG_LOAD => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "arg_load", self.code[self.ip]);
+                        println!("{}: arg_load {}", self.ip - 1, self.code[self.ip]);
                     }
                     let offset: i64 = self.code[self.ip];
                     let num_args: i64 = self.stack[self.fp + 1];
@@ -659,7 +1329,7 @@ impl VM {
                 },
                 ARG_STORE => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "arg_store", self.code[self.ip]);
+                        println!("{}: arg_store {}", self.ip - 1, self.code[self.ip]);
                     }
                     let offset: i64 = self.code[self.ip];
                     let num_args: i64 = self.stack[self.fp + 1];
@@ -669,15 +1339,187 @@ impl VM {
                     self.sp -= 1;
                     self.ip += 1;
                 },
+                LOCAL_ADDRESS => {
+                    if self.debug {
+                        println!("{}: local_address {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    self.stack.push((self.code[self.ip] as usize + self.fp + 3) as i64);
+                    self.sp += 1;
+                    self.ip += 1;
+                },
+                ARG_ADDRESS => {
+                    if self.debug {
+                        println!("{}: arg_address {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let num_args: i64 = self.stack[self.fp + 1];
+                    self.stack.push((self.fp - num_args as usize + self.code[self.ip] as usize) as i64);
+                    self.sp += 1;
+                    self.ip += 1;
+                },
+                REF_LOAD => {
+                    if self.debug {
+                        println!("{}: ref_load {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let num_args: i64 = self.stack[self.fp + 1];
+                    let location = self.stack[self.fp - num_args as usize + self.code[self.ip] as usize] as usize;
+                    self.stack.push(self.stack[location]);
+                    self.sp += 1;
+                    self.ip += 1;
+                },
+                REF_STORE => {
+                    if self.debug {
+                        println!("{}: ref_store {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let num_args: i64 = self.stack[self.fp + 1];
+                    let location = self.stack[self.fp - num_args as usize + self.code[self.ip] as usize] as usize;
+                    self.stack[location] = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.sp -= 1;
+                    self.ip += 1;
+                },
+                ARGS_LOAD => {
+                    if self.debug {
+                        println!("{}: args_load", self.ip - 1);
+                    }
+                    self.vec_string_constants.push(self.args.clone());
+                    self.stack.push(self.vec_string_constants.len() as i64 - 1);
+                    self.sp += 1;
+                },
+                EXIT => {
+                    if self.debug {
+                        println!("{}: exit", self.ip - 1);
+                    }
+                    self.exit_code = self.stack[self.sp - 1];
+                    self.halt = true;
+                },
                 USE => {
                     if self.debug {
-                        println!("{}: {} {}", self.ip - 1, "use", self.code[self.ip]);
+                        println!("{}: use {}", self.ip - 1, self.code[self.ip]);
                     }
                     self.standard_library();
                 },
+                LOCAL_ALLOC => {
+                    if self.debug {
+                        println!("{}: local_alloc {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let num_locals = self.code[self.ip] as usize;
+                    for _ in 0..num_locals {
+                        self.stack.push(0);
+                    }
+                    self.sp += num_locals;
+                    self.ip += 1;
+                },
+                STRUCT_NEW => {
+                    if self.debug {
+                        println!("{}: struct_new {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    // the field values are on the stack and their security levels follow the field count
+                    let num_fields = self.code[self.ip] as usize;
+                    let fields = self.stack.split_off(self.sp - num_fields);
+                    let securities = self.code[self.ip + 1..self.ip + 1 + num_fields].to_vec();
+                    self.sp -= num_fields;
+                    self.struct_constants.push(fields);
+                    self.struct_securities.push(securities);
+                    self.stack.push(self.struct_constants.len() as i64 - 1);
+                    self.sp += 1;
+                    self.ip += num_fields + 1;
+                },
+                CLOSURE_NEW => {
+                    if self.debug {
+                        println!("{}: closure_new {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    }
+                    // a closure is kept like a struct with the function address before the captured values
+                    let address = self.code[self.ip];
+                    let num_captures = self.code[self.ip + 1] as usize;
+                    let mut closure = vec![address];
+                    closure.extend(self.stack.split_off(self.sp - num_captures));
+                    self.sp -= num_captures;
+                    self.struct_securities.push(vec![0; closure.len()]);
+                    self.struct_constants.push(closure);
+                    self.stack.push(self.struct_constants.len() as i64 - 1);
+                    self.sp += 1;
+                    self.ip += 2;
+                },
+                CALL_INDIRECT => {
+                    if self.debug {
+                        println!("{}: call_indirect {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    // the closure is below the arguments, its captured values are passed after them
+                    let nargs = self.code[self.ip] as usize;
+                    let location = self.stack.remove(self.sp - 1 - nargs) as usize;
+                    self.sp -= 1;
+                    let address = self.struct_constants[location][0];
+                    let captures = self.struct_constants[location][1..].to_vec();
+                    let return_address: i64 = self.ip as i64 + 1;
+
+                    let num_args = nargs + captures.len();
+                    self.sp += captures.len();
+                    self.stack.extend(captures);
+
+                    self.stack.push(return_address);
+                    self.stack.push(num_args as i64);
+                    self.stack.push(self.fp as i64);
+                    self.fp = self.sp;
+
+                    self.sp += 3;
+                    self.ip = address as usize;
+                },
+                FIELD_LOAD => {
+                    if self.debug {
+                        println!("{}: field_load {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    }
+                    let location = self.stack[self.sp - 1] as usize;
+                    let field = self.code[self.ip] as usize;
+                    let max_security = self.code[self.ip + 1];
+                    if self.struct_securities[location][field] > max_security {
+                        self.throw(format!("Field with security level {} read at security level {}.", self.struct_securities[location][field], max_security), 0);
+                        continue;
+                    }
+                    self.stack.pop();
+                    self.stack.push(self.struct_constants[location][field]);
+                    self.ip += 2;
+                },
+                FIELD_STORE => {
+                    if self.debug {
+                        println!("{}: field_store {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let location = self.stack[self.sp - 2] as usize;
+                    let value = self.stack[self.sp - 1];
+                    self.stack.pop();
+                    self.stack.pop();
+                    self.sp -= 2;
+                    self.struct_constants[location][self.code[self.ip] as usize] = value;
+                    self.ip += 1;
+                },
+                TRY_BEGIN => {
+                    if self.debug {
+                        println!("{}: try_begin {} {}", self.ip - 1, self.code[self.ip], self.code[self.ip + 1]);
+                    }
+                    self.handlers.push(Handler {
+                        address: self.code[self.ip] as usize,
+                        security: self.code[self.ip + 1],
+                        fp: self.fp,
+                        sp: self.sp,
+                    });
+                    self.ip += 2;
+                },
+                TRY_END => {
+                    if self.debug {
+                        println!("{}: try_end", self.ip - 1);
+                    }
+                    self.handlers.pop();
+                },
+                THROW_ERROR => {
+                    if self.debug {
+                        println!("{}: throw_error {}", self.ip - 1, self.code[self.ip]);
+                    }
+                    let message = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                    let security = self.code[self.ip];
+                    self.throw(message, security);
+                },
                 HALT => {
                     if self.debug {
-                        println!("{}: {}", self.ip - 1, "halt");
+                        println!("{}: halt", self.ip - 1);
                     }
                     self.halt = true;
                 },
@@ -690,6 +1532,31 @@ impl VM {
         }
     }
 
+    // Unwinds to the innermost handler allowed to see an error of the given security level and pushes the message
+    // for its catch block. Handlers below the level are dropped so a secret error never reaches a public one.
+    fn throw(&mut self, message: String, security: i64) {
+        while let Some(handler) = self.handlers.pop() {
+            if handler.security < security {
+                continue;
+            }
+            self.stack.truncate(handler.sp);
+            self.sp = handler.sp;
+            self.fp = handler.fp;
+            self.string_constants.push(message);
+            self.stack.push(self.string_constants.len() as i64 - 1);
+            self.sp += 1;
+            self.ip = handler.address;
+            return;
+        }
+        io::stdout().flush().unwrap();
+        if security == 0 {
+            eprintln!("Error: {}", message);
+        } else {
+            eprintln!("Error with security level {} was not caught.", security);
+        }
+        std::process::exit(1);
+    }
+
     fn standard_library(&mut self) {
         match self.code[self.ip] {
             PRINT => {
@@ -711,6 +1578,24 @@ impl VM {
                     std::io::stdout().flush().expect("Failed to flush stdout.");
                     self.stack.pop();
                     self.sp -= 1;
+                } else if self.code[self.ip] == BOOL {
+                    self.ip += 1;
+                    print!("{}", self.stack[self.sp - 1] != 0);
+                    std::io::stdout().flush().expect("Failed to flush stdout.");
+                    self.stack.pop();
+                    self.sp -= 1;
+                } else if self.code[self.ip] == DECIMAL {
+                    self.ip += 1;
+                    print!("{}", decimal::to_string(self.stack[self.sp - 1]));
+                    std::io::stdout().flush().expect("Failed to flush stdout.");
+                    self.stack.pop();
+                    self.sp -= 1;
+                } else if self.code[self.ip] == BIGINT {
+                    self.ip += 1;
+                    print!("{}", self.bigint_constants[self.stack[self.sp - 1] as usize]);
+                    std::io::stdout().flush().expect("Failed to flush stdout.");
+                    self.stack.pop();
+                    self.sp -= 1;
                 }
             },
             READ => {
@@ -718,7 +1603,7 @@ impl VM {
                 let mut input = String::new();
                 match io::stdin().read_line(&mut input) {
                     Ok(_x) => {
-                        if input.len() as i64 - 1 >= 0 {
+                        if input.len() as i64 > 0 {
                             while input.chars().nth(input.len() - 1).unwrap() == '\n' ||
                                 input.chars().nth(input.len() - 1).unwrap() == '\r' {
                                     input.pop();
@@ -731,22 +1616,25 @@ impl VM {
                     Err(_error) => input = "".to_string(),
                 }
                 if self.code[self.ip] == INT {
-                    let result: i64;
-                    match input.parse::<i64>() {
-                        Ok(x) => result = x,
-                        Err(_error) => result = 0,
-                    }
+                    let result: i64 = input.parse::<i64>().unwrap_or_default();
                     self.stack.push(result);
                 } else if self.code[self.ip] == FLOAT {
-                    let result: f64;
-                    match input.parse::<f64>() {
-                        Ok(x) => result = x,
-                        Err(_error) => result = 0.0,
-                    }
+                    let result: f64 = input.parse::<f64>().unwrap_or(0.0);
                     self.stack.push(i64::from_be_bytes(result.to_be_bytes()));
                 } else if self.code[self.ip] == STRING {
                     self.string_constants.push(input.clone());
                     self.stack.push(self.string_constants.len() as i64 - 1);
+                } else if self.code[self.ip] == BOOL {
+                    if input == "true" {
+                        self.stack.push(1);
+                    } else {
+                        self.stack.push(0);
+                    }
+                } else if self.code[self.ip] == DECIMAL {
+                    self.stack.push(decimal::parse(&input).unwrap_or(0));
+                } else if self.code[self.ip] == BIGINT {
+                    self.bigint_constants.push(BigInt::parse(&input).unwrap_or(BigInt::from_i64(0)));
+                    self.stack.push(self.bigint_constants.len() as i64 - 1);
                 }
                 self.sp += 1;
                 self.ip += 1;
@@ -808,9 +1696,8 @@ impl VM {
                 self.stack.pop();
                 self.stack.pop();
                 let mut new_string = String::new();
-                match self.string_constants[string_mem_location].chars().nth(index) {
-                    Some(x) => new_string.push(x),
-                    None => {},
+                if let Some(x) = self.string_constants[string_mem_location].chars().nth(index) {
+                    new_string.push(x);
                 }
                 self.string_constants.push(new_string);
                 self.stack.push(self.string_constants.len() as i64 - 1);
@@ -824,9 +1711,8 @@ impl VM {
                 self.stack.pop();
                 self.stack.pop();
                 self.stack.pop();
-                match self.string_constants[char_mem_location].chars().nth(0) {
-                    Some(x) => self.string_constants[string_mem_location].insert(index, x),
-                    None => {},
+                if let Some(x) = self.string_constants[char_mem_location].chars().nth(0) {
+                    self.string_constants[string_mem_location].insert(index, x);
                 }
                 self.sp -= 3;
             },
@@ -870,8 +1756,14 @@ impl VM {
                 let value = self.stack[self.sp - 1];
                 self.stack.pop();
                 self.stack.pop();
-                self.sp -= 2;
+                self.stack.pop();
+                self.sp -= 3;
 
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_int_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
                 self.vec_int_constants[location][value_location] = value;
             },
             VEC_INT_GET_INDEX => {
@@ -881,6 +1773,11 @@ impl VM {
                 self.stack.pop();
                 self.stack.pop();
 
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_int_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
                 self.stack.push(self.vec_int_constants[location][value_location]);
                 self.sp -= 1;
             },
@@ -896,7 +1793,7 @@ impl VM {
                 self.ip += 1;
                 let vec_float: Vec<f64> = Vec::new();
                 self.vec_float_constants.push(vec_float);
-                self.stack.push(self.vec_int_constants.len() as i64 - 1);
+                self.stack.push(self.vec_float_constants.len() as i64 - 1);
                 self.sp += 1;
             },
             VEC_FLOAT_PUSH => {
@@ -924,8 +1821,14 @@ impl VM {
                 let value = f64::from_be_bytes(self.stack[self.sp - 1].to_be_bytes());
                 self.stack.pop();
                 self.stack.pop();
-                self.sp -= 2;
+                self.stack.pop();
+                self.sp -= 3;
 
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_float_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
                 self.vec_float_constants[location][value_location] = value;
             },
             VEC_FLOAT_GET_INDEX => {
@@ -935,7 +1838,12 @@ impl VM {
                 self.stack.pop();
                 self.stack.pop();
 
-                self.stack.push(i64::from_be_bytes(self.vec_int_constants[location][value_location].to_be_bytes()));
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_float_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
+                self.stack.push(i64::from_be_bytes(self.vec_float_constants[location][value_location].to_be_bytes()));
                 self.sp -= 1;
             },
             VEC_FLOAT_LEN => {
@@ -943,11 +1851,559 @@ impl VM {
                 let location = self.stack[self.sp - 1] as usize;
                 self.stack.pop();
 
-                self.stack.push(self.vec_int_constants[location].len() as i64);
+                self.stack.push(self.vec_float_constants[location].len() as i64);
+            },
+
+            VEC_STRING_NEW => {
+                self.ip += 1;
+                let vec_string: Vec<String> = Vec::new();
+                self.vec_string_constants.push(vec_string);
+                self.stack.push(self.vec_string_constants.len() as i64 - 1);
+                self.sp += 1;
+            },
+            VEC_STRING_PUSH => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let value = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 2;
+
+                self.vec_string_constants[location].push(value);
+            },
+            VEC_STRING_POP => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.sp -= 1;
+
+                self.vec_string_constants[location].pop();
+            },
+            VEC_STRING_SET_INDEX => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 3] as usize;
+                let value_location = self.stack[self.sp - 2] as usize;
+                let value = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 3;
+
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_string_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
+                self.vec_string_constants[location][value_location] = value;
+            },
+            VEC_STRING_GET_INDEX => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let value_location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.stack.pop();
+
+                // the index is not printed since it may be secret
+                if value_location >= self.vec_string_constants[location].len() {
+                    self.throw("Vector index out of bounds.".to_string(), 0);
+                    return;
+                }
+                let new_string = self.vec_string_constants[location][value_location].clone();
+                self.string_constants.push(new_string);
+                self.stack.push(self.string_constants.len() as i64 - 1);
+                self.sp -= 1;
+            },
+            VEC_STRING_LEN => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+
+                self.stack.push(self.vec_string_constants[location].len() as i64);
+            },
+            MAP_NEW => {
+                self.ip += 1;
+                self.map_constants.push(BTreeMap::new());
+                self.map_key_types.push(self.code[self.ip]);
+                self.ip += 1;
+                self.stack.push(self.map_constants.len() as i64 - 1);
+                self.sp += 1;
+            },
+            MAP_INSERT => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 3] as usize;
+                let key = self.map_key(location, self.stack[self.sp - 2]);
+                let value = self.stack[self.sp - 1];
+                self.stack.pop();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 3;
+
+                self.map_constants[location].insert(key, value);
+            },
+            MAP_GET => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let key = self.map_key(location, self.stack[self.sp - 1]);
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 1;
+
+                match self.map_constants[location].get(&key) {
+                    Some(x) => self.stack.push(*x),
+                    None => {
+                        self.throw("Key not found in map.".to_string(), 0);
+                    },
+                }
+            },
+            MAP_REMOVE => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let key = self.map_key(location, self.stack[self.sp - 1]);
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 2;
+
+                self.map_constants[location].remove(&key);
+            },
+            MAP_CONTAINS => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let key = self.map_key(location, self.stack[self.sp - 1]);
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 1;
+
+                if self.map_constants[location].contains_key(&key) {
+                    self.stack.push(1);
+                } else {
+                    self.stack.push(0);
+                }
+            },
+            MAP_LEN => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+
+                self.stack.push(self.map_constants[location].len() as i64);
+            },
+            MAP_KEYS => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+
+                if self.map_key_types[location] == STRING {
+                    let mut vec_string: Vec<String> = Vec::new();
+                    for key in self.map_constants[location].keys() {
+                        if let MapKey::String(x) = key {
+                            vec_string.push(x.clone());
+                        }
+                    }
+                    self.vec_string_constants.push(vec_string);
+                    self.stack.push(self.vec_string_constants.len() as i64 - 1);
+                } else {
+                    let mut vec_int: Vec<i64> = Vec::new();
+                    for key in self.map_constants[location].keys() {
+                        if let MapKey::Int(x) = key {
+                            vec_int.push(*x);
+                        }
+                    }
+                    self.vec_int_constants.push(vec_int);
+                    self.stack.push(self.vec_int_constants.len() as i64 - 1);
+                }
+            },
+            WRAPPING_ADD | WRAPPING_SUB | WRAPPING_MUL | SATURATING_ADD | SATURATING_SUB | SATURATING_MUL => {
+                let function = self.code[self.ip];
+                self.ip += 1;
+                let a = self.stack[self.sp - 2];
+                let b = self.stack[self.sp - 1];
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 1;
+
+                let result = match function {
+                    WRAPPING_ADD => a.wrapping_add(b),
+                    WRAPPING_SUB => a.wrapping_sub(b),
+                    WRAPPING_MUL => a.wrapping_mul(b),
+                    SATURATING_ADD => a.saturating_add(b),
+                    SATURATING_SUB => a.saturating_sub(b),
+                    _ => a.saturating_mul(b),
+                };
+                self.stack.push(result);
+            },
+            INT_TO_DECIMAL => {
+                self.ip += 1;
+                let integer = self.stack[self.sp - 1];
+                self.stack.pop();
+                match integer.checked_mul(decimal::ONE) {
+                    Some(x) => self.stack.push(x),
+                    None => {
+                        self.throw("Integer is too large for a decimal.".to_string(), 0);
+                    },
+                }
+            },
+            DECIMAL_TO_INT => {
+                self.ip += 1;
+                let value = self.stack[self.sp - 1];
+                self.stack.pop();
+                // truncates toward zero like float_to_int
+                self.stack.push(value / decimal::ONE);
+            },
+            FLOAT_TO_DECIMAL => {
+                self.ip += 1;
+                let float = f64::from_be_bytes(self.stack[self.sp - 1].to_be_bytes());
+                self.stack.pop();
+                match decimal::from_float(float) {
+                    Some(x) => self.stack.push(x),
+                    None => {
+                        self.throw("Float cannot be converted to a decimal.".to_string(), 0);
+                    },
+                }
+            },
+            DECIMAL_TO_FLOAT => {
+                self.ip += 1;
+                let value = self.stack[self.sp - 1];
+                self.stack.pop();
+                let float = value as f64 / decimal::ONE as f64;
+                self.stack.push(i64::from_be_bytes(float.to_be_bytes()));
+            },
+            STRING_TO_DECIMAL => {
+                self.ip += 1;
+                let mem_location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.stack.push(decimal::parse(&self.string_constants[mem_location]).unwrap_or(0));
+            },
+            DECIMAL_TO_STRING => {
+                self.ip += 1;
+                let value = self.stack[self.sp - 1];
+                self.stack.pop();
+                self.string_constants.push(decimal::to_string(value));
+                self.stack.push(self.string_constants.len() as i64 - 1);
+            },
+            DECIMAL_MUL | DECIMAL_DIV | DECIMAL_ROUND => {
+                let function = self.code[self.ip];
+                self.ip += 1;
+                let a = self.stack[self.sp - 3];
+                let b = self.stack[self.sp - 2];
+                let mode_name = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 2;
+
+                let mode = match decimal::rounding(&mode_name) {
+                    Some(x) => x,
+                    None => {
+                        // the mode is not printed since it may be secret
+                        self.throw("Unknown rounding mode.".to_string(), 0);
+                        return;
+                    },
+                };
+                let result = match function {
+                    DECIMAL_MUL => decimal::mul(a, b, &mode),
+                    DECIMAL_DIV => {
+                        if b == 0 {
+                            self.throw("Attempted to divide by zero.".to_string(), 0);
+                            return;
+                        }
+                        decimal::div(a, b, &mode)
+                    },
+                    _ => decimal::round(a, b, &mode),
+                };
+                match result {
+                    Some(x) => self.stack.push(x),
+                    None => {
+                        self.throw("Decimal overflow.".to_string(), 0);
+                    },
+                }
+            },
+            INT_TO_BIGINT => {
+                self.ip += 1;
+                let integer = self.stack[self.sp - 1];
+                self.stack.pop();
+                self.bigint_constants.push(BigInt::from_i64(integer));
+                self.stack.push(self.bigint_constants.len() as i64 - 1);
+            },
+            BIGINT_TO_INT => {
+                self.ip += 1;
+                let value = self.bigint_constants[self.stack[self.sp - 1] as usize].to_i64();
+                self.stack.pop();
+                match value {
+                    Some(x) => self.stack.push(x),
+                    None => {
+                        self.throw("Bigint is too large for an int.".to_string(), 0);
+                    },
+                }
+            },
+            STRING_TO_BIGINT => {
+                self.ip += 1;
+                let mem_location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.bigint_constants.push(BigInt::parse(&self.string_constants[mem_location]).unwrap_or(BigInt::from_i64(0)));
+                self.stack.push(self.bigint_constants.len() as i64 - 1);
+            },
+            BIGINT_TO_STRING => {
+                self.ip += 1;
+                let string = self.bigint_constants[self.stack[self.sp - 1] as usize].to_string();
+                self.stack.pop();
+                self.string_constants.push(string);
+                self.stack.push(self.string_constants.len() as i64 - 1);
+            },
+            PARSE_INT | PARSE_FLOAT | PARSE_DECIMAL | PARSE_BIGINT => {
+                let function = self.code[self.ip];
+                self.ip += 1;
+                let string = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.sp -= 1;
+                let (value, type_name) = match function {
+                    PARSE_INT => (self.parse_value(&string, INT), "int"),
+                    PARSE_FLOAT => (self.parse_value(&string, FLOAT), "float"),
+                    PARSE_DECIMAL => (self.parse_value(&string, DECIMAL), "decimal"),
+                    _ => (self.parse_value(&string, BIGINT), "bigint"),
+                };
+                // Result::Ok(value) or Result::Err(message)
+                match value {
+                    Some(x) => self.push_variant(0, Some(x)),
+                    None => {
+                        self.string_constants.push(format!("'{}' is not a valid {}", string, type_name));
+                        let message = self.string_constants.len() as i64 - 1;
+                        self.push_variant(1, Some(message));
+                    },
+                }
+            },
+            READ_OPTION => {
+                self.ip += 1;
+                let value_type = self.code[self.ip];
+                self.ip += 1;
+                let mut input = String::new();
+                let value = match io::stdin().read_line(&mut input) {
+                    Ok(0) | Err(_) => None,
+                    Ok(_) => {
+                        while input.ends_with('\n') || input.ends_with('\r') {
+                            input.pop();
+                        }
+                        self.parse_value(&input, value_type)
+                    },
+                };
+                // Option::None or Option::Some(value)
+                match value {
+                    Some(x) => self.push_variant(1, Some(x)),
+                    None => self.push_variant(0, None),
+                }
+            },
+            ENV_GET => {
+                self.ip += 1;
+                let security = self.code[self.ip];
+                self.ip += 1;
+                let name = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.sp -= 1;
+                // the program was compiled against a policy giving the variable a lower level
+                if self.policy.level("env", &name) > security {
+                    self.throw("Environment variable is more secret than the program allows.".to_string(), 0);
+                    return;
+                }
+                match std::env::var(&name) {
+                    Ok(x) => {
+                        self.string_constants.push(x);
+                        let value = self.string_constants.len() as i64 - 1;
+                        self.push_variant(1, Some(value));
+                    },
+                    Err(_) => self.push_variant(0, None),
+                }
+            },
+            FILE_OPEN => {
+                self.ip += 1;
+                let security = self.code[self.ip];
+                self.ip += 1;
+                let path = self.string_constants[self.stack[self.sp - 2] as usize].clone();
+                let mode = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 2;
+                // a file can be read at a higher level and written at a lower one than the policy gives it
+                match self.file_level(&path) {
+                    Some(x) if mode == "r" && security < x => {
+                        self.throw("File is more secret than the program allows.".to_string(), 0);
+                        return;
+                    },
+                    Some(x) if mode != "r" && security > x => {
+                        self.throw("File is less secret than the data the program writes to it.".to_string(), 0);
+                        return;
+                    },
+                    _ => {},
+                }
+                let file = match mode.as_str() {
+                    "r" => fs::File::open(&path).map(|x| OpenFile { reader: Some(io::BufReader::new(x)), writer: None, security }),
+                    "w" => fs::File::create(&path).map(|x| OpenFile { reader: None, writer: Some(x), security }),
+                    _ => fs::OpenOptions::new().append(true).create(true).open(&path).map(|x| OpenFile { reader: None, writer: Some(x), security }),
+                };
+                match file {
+                    Ok(x) => {
+                        self.files.push(Some(x));
+                        self.stack.push(self.files.len() as i64 - 1);
+                        self.sp += 1;
+                    },
+                    Err(_) => {
+                        self.throw("Failed to open file.".to_string(), 0);
+                    },
+                }
+            },
+            FILE_READ_LINE => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.sp -= 1;
+                let mut line = String::new();
+                let result = match self.files.get_mut(location) {
+                    Some(Some(OpenFile { reader: Some(x), .. })) => x.read_line(&mut line).map_err(|_| "Failed to read from file."),
+                    Some(Some(_)) => Err("File is not open for reading."),
+                    _ => Err("File is closed."),
+                };
+                // Option::None at the end of the file or Option::Some(line)
+                match result {
+                    Ok(0) => self.push_variant(0, None),
+                    Ok(_) => {
+                        while line.ends_with('\n') || line.ends_with('\r') {
+                            line.pop();
+                        }
+                        self.string_constants.push(line);
+                        let value = self.string_constants.len() as i64 - 1;
+                        self.push_variant(1, Some(value));
+                    },
+                    Err(x) => {
+                        self.throw(x.to_string(), 0);
+                    },
+                }
+            },
+            FILE_WRITE => {
+                self.ip += 1;
+                let data_security = self.code[self.ip];
+                self.ip += 1;
+                let location = self.stack[self.sp - 2] as usize;
+                let data = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.stack.pop();
+                self.sp -= 2;
+                let result = match self.files.get_mut(location) {
+                    Some(Some(x)) if data_security > x.security => Err("Data is more secret than the file it is written to."),
+                    Some(Some(OpenFile { writer: Some(x), .. })) => x.write_all(data.as_bytes()).map_err(|_| "Failed to write to file."),
+                    Some(Some(_)) => Err("File is not open for writing."),
+                    _ => Err("File is closed."),
+                };
+                if let Err(x) = result {
+                    self.throw(x.to_string(), 0);
+                }
+            },
+            FILE_CLOSE => {
+                self.ip += 1;
+                let location = self.stack[self.sp - 1] as usize;
+                self.stack.pop();
+                self.sp -= 1;
+                if location < self.files.len() {
+                    self.files[location] = None;
+                }
+            },
+            FILE_EXISTS | DIR_EXISTS => {
+                let function = self.code[self.ip];
+                self.ip += 1;
+                let path = Path::new(&self.string_constants[self.stack[self.sp - 1] as usize]);
+                let exists = if function == FILE_EXISTS { path.is_file() } else { path.is_dir() };
+                self.stack.pop();
+                self.stack.push(exists as i64);
+            },
+            DIR_LIST => {
+                self.ip += 1;
+                let security = self.code[self.ip];
+                self.ip += 1;
+                let path = self.string_constants[self.stack[self.sp - 1] as usize].clone();
+                self.stack.pop();
+                self.sp -= 1;
+                if self.file_level(&path).is_some_and(|x| x > security) {
+                    self.throw("Directory is more secret than the program allows.".to_string(), 0);
+                    return;
+                }
+                let mut names: Vec<String> = match fs::read_dir(&path) {
+                    Ok(x) => x.filter_map(|x| x.ok()).map(|x| x.file_name().to_string_lossy().to_string()).collect(),
+                    Err(_) => {
+                        self.throw("Failed to list directory.".to_string(), 0);
+                        return;
+                    },
+                };
+                names.sort();
+                self.vec_string_constants.push(names);
+                self.stack.push(self.vec_string_constants.len() as i64 - 1);
+                self.sp += 1;
             },
             _ => {
                 panic!("Standard library function does not exist.");
             },
         }
     }
+
+    // The level the policy gives a path as it is written, as a full path or relative to the working directory,
+    // None when no entry matches it.
+    fn file_level(&self, path: &String) -> Option<i64> {
+        let mut level = self.policy.find("file", path);
+        // a file that does not exist yet is found through its directory
+        let full_path = fs::canonicalize(path).ok().or_else(|| {
+            let path = Path::new(path);
+            let directory = path.parent().filter(|x| !x.as_os_str().is_empty()).unwrap_or(Path::new("."));
+            Some(fs::canonicalize(directory).ok()?.join(path.file_name()?))
+        });
+        if let Some(full_path) = full_path {
+            level = std::cmp::max(level, self.policy.find("file", &full_path.to_string_lossy()));
+            let relative_path = fs::canonicalize(".").ok().and_then(|x| full_path.strip_prefix(x).ok().map(|x| x.to_path_buf()));
+            if let Some(relative_path) = relative_path {
+                level = std::cmp::max(level, self.policy.find("file", &relative_path.to_string_lossy()));
+            }
+        }
+        level
+    }
+
+    // A value of the given standard library type read from a string, new strings and bigints are stored.
+    fn parse_value(&mut self, string: &str, value_type: i64) -> Option<i64> {
+        match value_type {
+            INT => string.parse::<i64>().ok(),
+            FLOAT => string.parse::<f64>().ok().map(|x| i64::from_be_bytes(x.to_be_bytes())),
+            BOOL => match string {
+                "true" => Some(1),
+                "false" => Some(0),
+                _ => None,
+            },
+            DECIMAL => decimal::parse(string),
+            BIGINT => {
+                self.bigint_constants.push(BigInt::parse(string)?);
+                Some(self.bigint_constants.len() as i64 - 1)
+            },
+            _ => {
+                self.string_constants.push(string.to_owned());
+                Some(self.string_constants.len() as i64 - 1)
+            },
+        }
+    }
+
+    // Option and Result values are structs holding the variant number and the value if there is one.
+    fn push_variant(&mut self, variant: i64, value: Option<i64>) {
+        let mut fields = vec![variant];
+        fields.extend(value);
+        self.struct_securities.push(vec![0; fields.len()]);
+        self.struct_constants.push(fields);
+        self.stack.push(self.struct_constants.len() as i64 - 1);
+        self.sp += 1;
+    }
+
+    fn push_bigint(&mut self, value: BigInt) {
+        self.bigint_constants.push(value);
+        self.stack.push(self.bigint_constants.len() as i64 - 1);
+        self.sp += 1;
+    }
+
+    // String keys are compared by their contents rather than their location.
+    fn map_key(&self, location: usize, key: i64) -> MapKey {
+        if self.map_key_types[location] == STRING {
+            MapKey::String(self.string_constants[key as usize].clone())
+        } else {
+            MapKey::Int(key)
+        }
+    }
 }
\ No newline at end of file